@@ -1,11 +1,11 @@
 use chrono::Utc;
 use dialoguer::Confirm;
 use std::env;
-use tgl_cli::svc;
+use tgl_cli::Client;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let token = env::var("TOGGL_API_TOKEN").expect("missing TOGGL_API_TOKEN environment variable");
-    let client = svc::Client::new(token, Utc::now)?;
+    let client = Client::new(token, Utc::now)?;
     let workspaces = client.get_workspaces()?;
 
     if Confirm::new().with_prompt("Print workspaces?").interact()? {
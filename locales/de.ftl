@@ -0,0 +1,49 @@
+## Dates
+
+date-format = %d.%m.%Y
+day-heading-format = %a, %d.%m.%Y
+
+## Status
+
+logged-today = Heute { $duration } erfasst.
+logged-on = Am { $date } { $duration } erfasst.
+logged-between = Vom { $from } bis { $to } { $duration } erfasst.
+target-eta = { $target } sind um { $time } erreicht.
+status-entry-count =
+    { $count ->
+        [one] 1 Eintrag
+       *[other] { $count } Einträge
+    }
+
+## Weekly target
+
+week-logged = Diese Woche { $duration } erfasst.
+week-chart-target = │ markiert das Tagesziel von { $target }
+week-remaining =
+    Noch { $remaining } diese Woche: { $daily } pro Tag an { $days ->
+        [one] einem Arbeitstag
+       *[other] { $days } Arbeitstagen
+    }.
+week-remaining-none = Noch { $remaining } diese Woche, aber keine Arbeitstage mehr.
+week-target-met = Wochenziel von { $target } erreicht.
+
+## Report
+
+no-project = Kein Projekt
+no-rate = kein Stundensatz
+no-earnings = Keine abrechenbare Zeit mit Stundensatz.
+earned = { $amount } verdient.
+
+## Balance
+
+balance-week = Woche vom { $week }: { $tracked } von { $target } { $difference }  Saldo { $balance }
+balance-total =
+    Saldo vom { $from } bis { $to }: { $balance } ({ $owing ->
+        [true] Minusstunden
+       *[false] Überstunden
+    }).
+
+## Overtime
+
+overtime-day = { $date } { $tracked } { $overtime }  gesamt { $total }
+overtime-total = Überstunden vom { $from } bis { $to }: { $overtime }.
@@ -0,0 +1,52 @@
+# Messages for status and report output. Names ending in -format are chrono
+# date formats, which name days and months in the user's language.
+
+## Dates
+
+date-format = %Y-%m-%d
+day-heading-format = %a %Y-%m-%d
+
+## Status
+
+logged-today = { $duration } logged today.
+logged-on = { $duration } logged on { $date }.
+logged-between = { $duration } logged from { $from } to { $to }.
+target-eta = You'll reach { $target } logged at { $time }.
+status-entry-count =
+    { $count ->
+        [one] 1 entry
+       *[other] { $count } entries
+    }
+
+## Weekly target
+
+week-logged = { $duration } logged this week.
+week-chart-target = │ marks the daily target of { $target }
+week-remaining =
+    { $remaining } left this week: { $daily } a day over { $days ->
+        [one] the last work day
+       *[other] { $days } work days
+    }.
+week-remaining-none = { $remaining } left this week, with no work days to go.
+week-target-met = Weekly target of { $target } reached.
+
+## Report
+
+no-project = No project
+no-rate = no rate
+no-earnings = No billable time with a rate.
+earned = { $amount } earned.
+
+## Balance
+
+balance-week = Week of { $week }: { $tracked } of { $target } { $difference }  balance { $balance }
+balance-total =
+    Balance from { $from } to { $to }: { $balance } ({ $owing ->
+        [true] you owe time
+       *[false] you have credit
+    }).
+
+## Overtime
+
+overtime-day = { $date } { $tracked } { $overtime }  total { $total }
+overtime-total = Overtime from { $from } to { $to }: { $overtime }.
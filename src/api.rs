@@ -1,153 +1,813 @@
 //! Low-level client for interacting with the [Toggl API](https://developers.track.toggl.com/docs/).
 
-use chrono::NaiveDate;
-use reqwest::header;
-use serde::{Deserialize, Serialize};
-use serde_json::Number;
+use crate::{
+    cassette::{Cassette, RequestInfo},
+    svc::Error,
+};
+use chrono::{DateTime, SecondsFormat, Utc};
+use reqwest::{
+    blocking::{RequestBuilder, Response},
+    header, StatusCode,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Number, Value};
+use std::time::{Duration, Instant};
+use tracing::{debug, debug_span, warn};
 
 static BASE_API_URL: &str = "https://api.track.toggl.com/api/v9";
+static WEBHOOKS_API_URL: &str = "https://api.track.toggl.com/webhooks/api/v1";
 
 /// Low-level client for interacting with the [Toggl API](https://developers.track.toggl.com/docs/).
 pub struct Client {
     c: reqwest::blocking::Client,
+    cassette: Option<Cassette>,
+    dry_run: Option<DryRunHook>,
+    progress: Option<ProgressHook>,
     token: String,
 }
 
+type DryRunHook = Box<dyn Fn(&PlannedRequest) + Send + Sync>;
+type ProgressHook = Box<dyn Fn(Progress) + Send + Sync>;
+
+/// What the client is doing, for showing progress during slow requests.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Progress<'a> {
+    /// A request started, described like "Fetching projects".
+    Started(&'a str),
+    /// The request finished, successfully or not.
+    Finished,
+}
+
+/// How long to wait on Toggl before giving up on a request.
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub struct Timeouts {
+    /// How long to wait for a connection.
+    pub connect: Duration,
+    /// How long to wait for the whole request, including the connection.
+    pub request: Duration,
+}
+
+impl Timeouts {
+    pub fn new(connect: Duration, request: Duration) -> Self {
+        Self { connect, request }
+    }
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self {
+            connect: Duration::from_secs(10),
+            request: Duration::from_secs(30),
+        }
+    }
+}
+
+/// How the client reaches Toggl. Proxies set in `HTTPS_PROXY` and
+/// `NO_PROXY` are used unless [`Connection::proxy`] is set.
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct Connection {
+    pub timeouts: Timeouts,
+    /// URL of a proxy for all requests. Hosts in `NO_PROXY` still bypass it.
+    pub proxy: Option<String>,
+    /// PEM-encoded root certificate to trust in addition to the system's,
+    /// e.g. for a proxy that intercepts TLS.
+    pub root_certificate: Option<Vec<u8>>,
+}
+
+impl Connection {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn timeouts(mut self, timeouts: Timeouts) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    pub fn proxy(mut self, url: impl Into<String>) -> Self {
+        self.proxy = Some(url.into());
+        self
+    }
+
+    pub fn root_certificate(mut self, pem: Vec<u8>) -> Self {
+        self.root_certificate = Some(pem);
+        self
+    }
+}
+
+/// The response to a request made with a cached version's ETag.
+#[derive(Debug)]
+pub enum Conditional<T> {
+    /// The data changed, or no ETag was sent. `etag` identifies this version.
+    Modified { value: T, etag: Option<String> },
+    /// The cached version is still current.
+    NotModified,
+}
+
+/// A request that changes data in Toggl, reported instead of sent during a
+/// dry run.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct PlannedRequest {
+    pub method: &'static str,
+    pub url: String,
+    pub body: Option<Value>,
+}
+
 impl Client {
     /// Creates a new client with the given API token.
-    pub fn new(token: String) -> Result<Self, reqwest::Error> {
-        let mut headers = header::HeaderMap::new();
-
-        // Toggl API docs indicate that we should always include the JSON
-        // content type header.
-        headers.insert(
-            header::CONTENT_TYPE,
-            header::HeaderValue::from_static("application/json"),
-        );
-
+    pub fn new(token: String, connection: &Connection) -> Result<Self, reqwest::Error> {
         Ok(Client {
-            c: reqwest::blocking::Client::builder()
-                .default_headers(headers)
-                .build()?,
+            c: http_client(connection)?,
+            cassette: None,
+            dry_run: None,
+            progress: None,
             token,
         })
     }
 
-    pub fn get_time_entries(
-        &self,
-        start_end_dates: Option<(NaiveDate, NaiveDate)>,
-    ) -> Result<Vec<TimeEntry>, reqwest::Error> {
-        let url = match start_end_dates {
-            Some((start_date, end_date)) => {
-                format!(
-                    "{BASE_API_URL}/me/time_entries?start_date={start_date}&end_date={end_date}"
-                )
+    /// Changes how the client reaches Toggl.
+    pub fn set_connection(&mut self, connection: &Connection) -> Result<(), reqwest::Error> {
+        self.c = http_client(connection)?;
+        Ok(())
+    }
+
+    /// Stops sending requests that change data. Each one is passed to `hook`
+    /// instead, and a response is made up from the request and the current
+    /// data.
+    pub fn set_dry_run(&mut self, hook: DryRunHook) {
+        self.dry_run = Some(hook);
+    }
+
+    /// Reports the request to the dry-run hook, returning `true` if it
+    /// shouldn't be sent.
+    fn skip(&self, method: &'static str, url: &str, body: Option<Value>) -> bool {
+        let Some(hook) = &self.dry_run else {
+            return false;
+        };
+        hook(&PlannedRequest {
+            method,
+            url: url.to_string(),
+            body,
+        });
+
+        true
+    }
+
+    /// Records responses to `cassette`, or answers from it when it's
+    /// replaying.
+    pub fn set_cassette(&mut self, cassette: Cassette) {
+        self.cassette = Some(cassette);
+    }
+
+    /// Reports what the client is doing to `hook` as requests start and
+    /// finish.
+    pub fn set_progress(&mut self, hook: ProgressHook) {
+        self.progress = Some(hook);
+    }
+
+    /// Authenticates and sends the request, reporting `action` to the
+    /// progress hook while it's in flight and logging how it went.
+    fn send(&self, action: &str, request: RequestBuilder) -> Result<Response, reqwest::Error> {
+        let request = request.basic_auth(&self.token, Some("api_token")).build()?;
+        let span = debug_span!("api", method = %request.method(), url = %request.url());
+        let _enter = span.enter();
+
+        if let Some(hook) = &self.progress {
+            hook(Progress::Started(action));
+        }
+        let started = Instant::now();
+        let result = match &self.cassette {
+            Some(cassette) if cassette.is_replay() => {
+                Ok(cassette.respond(&RequestInfo::new(&request)))
+            }
+            Some(cassette) => {
+                let info = RequestInfo::new(&request);
+                self.c
+                    .execute(request)
+                    .and_then(|response| cassette.record_response(info, response))
             }
-            None => format!("{BASE_API_URL}/me/time_entries"),
+            None => self.c.execute(request),
         };
+        if let Some(hook) = &self.progress {
+            hook(Progress::Finished);
+        }
+        let elapsed_ms = started.elapsed().as_millis();
+        match &result {
+            Ok(response) if response.status().is_success() => {
+                debug!(status = response.status().as_u16(), elapsed_ms, "response")
+            }
+            Ok(response) => warn!(status = response.status().as_u16(), elapsed_ms, "response"),
+            Err(error) => warn!(%error, elapsed_ms, "request failed"),
+        }
+
+        result
+    }
+
+    pub fn get_time_entries(
+        &self,
+        start_end: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    ) -> Result<Vec<TimeEntry>, Error> {
+        // `meta` names each entry's project and client, which saves fetching
+        // them separately.
+        let mut req = self
+            .c
+            .get(format!("{BASE_API_URL}/me/time_entries"))
+            .query(&[("meta", "true")]);
+        if let Some((start, end)) = start_end {
+            req = req.query(&[
+                (
+                    "start_date",
+                    start.to_rfc3339_opts(SecondsFormat::Secs, true),
+                ),
+                ("end_date", end.to_rfc3339_opts(SecondsFormat::Secs, true)),
+            ]);
+        }
+
+        self.send("Fetching time entries", req)?
+            .parse::<Vec<TimeEntry>>()
+    }
 
-        self.c
-            .get(url)
-            .basic_auth(&self.token, Some("api_token"))
-            .send()?
-            .error_for_status()?
-            .json::<Vec<TimeEntry>>()
+    pub fn get_current_entry(&self) -> Result<Option<TimeEntry>, Error> {
+        self.send(
+            "Fetching the running entry",
+            self.c
+                .get(format!("{BASE_API_URL}/me/time_entries/current")),
+        )?
+        .parse()
     }
 
-    pub fn get_current_entry(&self) -> Result<Option<TimeEntry>, reqwest::Error> {
-        self.c
-            .get(format!("{BASE_API_URL}/me/time_entries/current"))
-            .basic_auth(&self.token, Some("api_token"))
-            .send()?
-            .error_for_status()?
-            .json()
+    pub fn get_time_entry(&self, time_entry_id: &Number) -> Result<TimeEntry, Error> {
+        self.send(
+            "Fetching time entry",
+            self.c
+                .get(format!("{BASE_API_URL}/me/time_entries/{time_entry_id}")),
+        )?
+        .parse()
     }
 
-    pub fn create_time_entry(&self, entry: NewTimeEntry) -> Result<TimeEntry, reqwest::Error> {
+    pub fn create_time_entry(&self, entry: NewTimeEntry) -> Result<TimeEntry, Error> {
         let url = format!(
             "{BASE_API_URL}/workspaces/{}/time_entries",
             entry.workspace_id
         );
+        if self.skip("POST", &url, serde_json::to_value(&entry).ok()) {
+            return Ok(merged(&entry, json!({ "id": 0 })));
+        }
+
+        self.send("Creating time entry", self.c.post(url).json(&entry))?
+            .parse()
+    }
+
+    pub fn update_time_entry(
+        &self,
+        workspace_id: &Number,
+        time_entry_id: &Number,
+        update: &TimeEntryUpdate,
+    ) -> Result<TimeEntry, Error> {
+        let url = format!("{BASE_API_URL}/workspaces/{workspace_id}/time_entries/{time_entry_id}");
+        if self.skip("PUT", &url, serde_json::to_value(update).ok()) {
+            let entry = self.get_time_entry(time_entry_id)?;
+            return Ok(merged(&entry, update));
+        }
+
+        self.send("Updating time entry", self.c.put(url).json(update))?
+            .parse()
+    }
+
+    /// Applies JSON Patch operations to several time entries in one request.
+    /// Toggl accepts at most 100 IDs per request.
+    pub fn patch_time_entries(
+        &self,
+        workspace_id: &Number,
+        time_entry_ids: &[Number],
+        operations: &[PatchOperation],
+    ) -> Result<PatchResult, Error> {
+        let ids: Vec<_> = time_entry_ids.iter().map(|id| id.to_string()).collect();
+        let url = format!(
+            "{BASE_API_URL}/workspaces/{workspace_id}/time_entries/{}",
+            ids.join(",")
+        );
+        if self.skip("PATCH", &url, serde_json::to_value(operations).ok()) {
+            return Ok(PatchResult {
+                success: time_entry_ids.to_vec(),
+                failure: Vec::new(),
+            });
+        }
+
+        self.send("Updating time entries", self.c.patch(url).json(operations))?
+            .parse()
+    }
+
+    pub fn delete_time_entry(
+        &self,
+        workspace_id: &Number,
+        time_entry_id: &Number,
+    ) -> Result<(), Error> {
+        let url = format!("{BASE_API_URL}/workspaces/{workspace_id}/time_entries/{time_entry_id}");
+        if self.skip("DELETE", &url, None) {
+            return Ok(());
+        }
+
+        self.send("Deleting time entry", self.c.delete(url))?
+            .check()?;
 
-        self.c
-            .post(url)
-            .json(&entry)
-            .basic_auth(&self.token, Some("api_token"))
-            .send()?
-            .error_for_status()?
-            .json()
+        Ok(())
     }
 
     pub fn stop_time_entry(
         &self,
         workspace_id: &Number,
         time_entry_id: &Number,
-    ) -> Result<TimeEntry, reqwest::Error> {
+    ) -> Result<TimeEntry, Error> {
         let url =
             format!("{BASE_API_URL}/workspaces/{workspace_id}/time_entries/{time_entry_id}/stop");
+        if self.skip("PATCH", &url, None) {
+            let entry = self.get_time_entry(time_entry_id)?;
+            let now = Utc::now();
+            let start = entry
+                .start
+                .as_deref()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(now);
+            return Ok(merged(
+                &entry,
+                json!({
+                    "duration": (now - start).num_seconds(),
+                    "stop": now.to_rfc3339(),
+                }),
+            ));
+        }
+
+        self.send("Stopping time entry", self.c.patch(url))?.parse()
+    }
+
+    /// Gets the workspace's projects, including archived ones, unless they
+    /// haven't changed since the version with `etag`.
+    pub fn get_projects(
+        &self,
+        workspace_id: &Number,
+        etag: Option<&str>,
+    ) -> Result<Conditional<Vec<Project>>, Error> {
+        self.send_conditional(
+            "Fetching projects",
+            self.c
+                .get(format!("{BASE_API_URL}/workspaces/{workspace_id}/projects"))
+                .query(&[("active", "both")]),
+            etag,
+        )
+    }
+
+    pub fn get_project(
+        &self,
+        workspace_id: &Number,
+        project_id: &Number,
+    ) -> Result<Project, Error> {
+        self.send(
+            "Fetching project",
+            self.c.get(format!(
+                "{BASE_API_URL}/workspaces/{workspace_id}/projects/{project_id}"
+            )),
+        )?
+        .parse()
+    }
+
+    pub fn create_project(
+        &self,
+        workspace_id: &Number,
+        project: &NewProject,
+    ) -> Result<Project, Error> {
+        let url = format!("{BASE_API_URL}/workspaces/{workspace_id}/projects");
+        if self.skip("POST", &url, serde_json::to_value(project).ok()) {
+            return Ok(merged(
+                project,
+                json!({ "id": 0, "workspace_id": workspace_id }),
+            ));
+        }
+
+        self.send("Creating project", self.c.post(url).json(project))?
+            .parse()
+    }
+
+    pub fn update_project(
+        &self,
+        workspace_id: &Number,
+        project_id: &Number,
+        update: &ProjectUpdate,
+    ) -> Result<Project, Error> {
+        let url = format!("{BASE_API_URL}/workspaces/{workspace_id}/projects/{project_id}");
+        if self.skip("PUT", &url, serde_json::to_value(update).ok()) {
+            let project = self.get_project(workspace_id, project_id)?;
+            return Ok(merged(&project, update));
+        }
+
+        self.send("Updating project", self.c.put(url).json(update))?
+            .parse()
+    }
+
+    /// Gets the workspace's clients, including archived ones.
+    pub fn get_clients(&self, workspace_id: &Number) -> Result<Vec<WorkspaceClient>, Error> {
+        self.send(
+            "Fetching clients",
+            self.c
+                .get(format!("{BASE_API_URL}/workspaces/{workspace_id}/clients"))
+                .query(&[("status", "both")]),
+        )?
+        .parse()
+    }
+
+    pub fn create_client(&self, client: &NewClient) -> Result<WorkspaceClient, Error> {
+        let url = format!("{BASE_API_URL}/workspaces/{}/clients", client.wid);
+        if self.skip("POST", &url, serde_json::to_value(client).ok()) {
+            return Ok(merged(client, json!({ "id": 0 })));
+        }
+
+        self.send("Creating client", self.c.post(url).json(client))?
+            .parse()
+    }
+
+    /// Archives a client along with its projects.
+    pub fn archive_client(&self, workspace_id: &Number, client_id: &Number) -> Result<(), Error> {
+        let url = format!("{BASE_API_URL}/workspaces/{workspace_id}/clients/{client_id}/archive");
+        if self.skip("POST", &url, None) {
+            return Ok(());
+        }
+
+        self.send("Archiving client", self.c.post(url))?.check()?;
+
+        Ok(())
+    }
+
+    pub fn get_webhook_subscriptions(
+        &self,
+        workspace_id: &Number,
+    ) -> Result<Vec<WebhookSubscription>, Error> {
+        self.send(
+            "Fetching webhook subscriptions",
+            self.c
+                .get(format!("{WEBHOOKS_API_URL}/subscriptions/{workspace_id}")),
+        )?
+        .parse()
+    }
+
+    /// Subscribes `subscription.url_callback` to events in the workspace.
+    /// Toggl pings the URL with a validation code before sending events.
+    pub fn create_webhook_subscription(
+        &self,
+        workspace_id: &Number,
+        subscription: &NewWebhookSubscription,
+    ) -> Result<WebhookSubscription, Error> {
+        let url = format!("{WEBHOOKS_API_URL}/subscriptions/{workspace_id}");
+        if self.skip("POST", &url, serde_json::to_value(subscription).ok()) {
+            return Ok(merged(
+                subscription,
+                json!({ "subscription_id": 0, "workspace_id": workspace_id }),
+            ));
+        }
+
+        self.send(
+            "Creating webhook subscription",
+            self.c.post(url).json(subscription),
+        )?
+        .parse()
+    }
+
+    pub fn delete_webhook_subscription(
+        &self,
+        workspace_id: &Number,
+        subscription_id: &Number,
+    ) -> Result<(), Error> {
+        let url = format!("{WEBHOOKS_API_URL}/subscriptions/{workspace_id}/{subscription_id}");
+        if self.skip("DELETE", &url, None) {
+            return Ok(());
+        }
+
+        self.send("Deleting webhook subscription", self.c.delete(url))?
+            .check()?;
+
+        Ok(())
+    }
+
+    pub fn get_favorites(&self) -> Result<Vec<Favorite>, Error> {
+        self.send(
+            "Fetching favorites",
+            self.c.get(format!("{BASE_API_URL}/me/favorites")),
+        )?
+        .parse()
+    }
+
+    /// Gets the user's workspaces, unless they haven't changed since the
+    /// version with `etag`.
+    pub fn get_workspaces(&self, etag: Option<&str>) -> Result<Conditional<Vec<Workspace>>, Error> {
+        self.send_conditional(
+            "Fetching workspaces",
+            self.c.get(format!("{BASE_API_URL}/workspaces")),
+            etag,
+        )
+    }
 
-        self.c
-            .patch(url)
-            .basic_auth(&self.token, Some("api_token"))
-            .send()?
-            .error_for_status()?
-            .json()
+    /// Sends a GET request with `If-None-Match` when there's an `etag`, so
+    /// that Toggl can skip sending data that hasn't changed.
+    fn send_conditional<T: DeserializeOwned>(
+        &self,
+        action: &str,
+        request: RequestBuilder,
+        etag: Option<&str>,
+    ) -> Result<Conditional<T>, Error> {
+        let request = match etag {
+            Some(etag) => request.header(header::IF_NONE_MATCH, etag),
+            None => request,
+        };
+        let response = self.send(action, request)?.check()?;
+        if response.status() == StatusCode::NOT_MODIFIED {
+            return Ok(Conditional::NotModified);
+        }
+        let etag = response
+            .headers()
+            .get(header::ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+
+        Ok(Conditional::Modified {
+            value: response.json()?,
+            etag,
+        })
     }
+}
+
+/// Turns error statuses into [`Error`]s that keep the service's explanation,
+/// which `error_for_status` would drop.
+pub(crate) trait ResponseExt: Sized {
+    fn check(self) -> Result<Self, Error>;
 
-    pub fn get_projects(&self, workspace_id: &Number) -> Result<Vec<Project>, reqwest::Error> {
-        self.c
-            .get(format!("{BASE_API_URL}/workspaces/{workspace_id}/projects"))
-            .basic_auth(&self.token, Some("api_token"))
-            .send()?
-            .error_for_status()?
-            .json()
+    fn parse<T: DeserializeOwned>(self) -> Result<T, Error>;
+}
+
+impl ResponseExt for Response {
+    fn check(self) -> Result<Self, Error> {
+        let status = self.status();
+        if status.is_client_error() || status.is_server_error() {
+            return Err(Error::from_response(
+                status,
+                &self.text().unwrap_or_default(),
+            ));
+        }
+
+        Ok(self)
     }
 
-    pub fn get_workspaces(&self) -> Result<Vec<Workspace>, reqwest::Error> {
-        self.c
-            .get(format!("{BASE_API_URL}/workspaces"))
-            .basic_auth(&self.token, Some("api_token"))
-            .send()?
-            .error_for_status()?
-            .json()
+    fn parse<T: DeserializeOwned>(self) -> Result<T, Error> {
+        Ok(self.check()?.json()?)
     }
 }
 
-#[derive(Deserialize, Debug)]
+fn http_client(connection: &Connection) -> Result<reqwest::blocking::Client, reqwest::Error> {
+    let mut headers = header::HeaderMap::new();
+
+    // Toggl API docs indicate that we should always include the JSON
+    // content type header.
+    headers.insert(
+        header::CONTENT_TYPE,
+        header::HeaderValue::from_static("application/json"),
+    );
+
+    let mut builder = reqwest::blocking::Client::builder()
+        .default_headers(headers)
+        // Entries and projects compress well, which helps on slow links.
+        .brotli(true)
+        .gzip(true)
+        .connect_timeout(connection.timeouts.connect)
+        .timeout(connection.timeouts.request);
+    if let Some(url) = &connection.proxy {
+        builder = builder.proxy(reqwest::Proxy::all(url)?.no_proxy(reqwest::NoProxy::from_env()));
+    }
+    if let Some(pem) = &connection.root_certificate {
+        builder = builder.add_root_certificate(reqwest::Certificate::from_pem(pem)?);
+    }
+
+    builder.build()
+}
+
+/// Makes up a dry-run response by applying the fields of `changes` to
+/// `base`.
+fn merged<T: DeserializeOwned>(base: &impl Serialize, changes: impl Serialize) -> T {
+    let mut value = serde_json::to_value(base).expect("models serialize to JSON");
+    if let (Some(fields), Value::Object(changes)) = (
+        value.as_object_mut(),
+        serde_json::to_value(changes).expect("models serialize to JSON"),
+    ) {
+        fields.extend(changes);
+    }
+
+    serde_json::from_value(value).expect("dry-run response matches the model")
+}
+
+#[derive(Serialize, Deserialize, Debug)]
 pub struct TimeEntry {
+    #[serde(default)]
+    pub billable: bool,
     pub description: Option<String>,
     pub duration: Number,
     pub id: Number,
     pub project_id: Option<Number>,
+    /// Only included when requested with `meta`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_name: Option<String>,
+    /// Only included when requested with `meta`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_name: Option<String>,
+    /// Only included when requested with `meta`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_color: Option<String>,
     pub start: Option<String>,
     pub stop: Option<String>,
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
     pub task_id: Option<Number>,
     pub workspace_id: Number,
 }
 
 #[derive(Serialize, Debug)]
 pub struct NewTimeEntry {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub billable: Option<bool>,
     pub created_with: String,
     pub description: Option<String>,
     pub duration: Number,
     pub project_id: Option<Number>,
     pub start: String,
     pub stop: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
     pub task_id: Option<Number>,
     pub workspace_id: Number,
 }
 
+/// Changes to an existing time entry. Fields that are `None` are left
+/// unchanged. For `project_id`, `Some(None)` removes the project.
+#[derive(Serialize, Debug, Default)]
+pub struct TimeEntryUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub billable: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<Number>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_id: Option<Option<Number>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start: Option<String>,
+    /// `Some(None)` clears the stop time.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop: Option<Option<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+}
+
+/// A saved time entry preset from the Toggl favorites list.
 #[derive(Deserialize, Debug)]
+pub struct Favorite {
+    #[serde(default)]
+    pub billable: bool,
+    pub description: Option<String>,
+    pub favorite_id: Number,
+    pub project_id: Option<Number>,
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+    pub workspace_id: Number,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
 pub struct Project {
     pub active: bool,
+    pub actual_hours: Option<f64>,
+    pub client_id: Option<Number>,
+    /// A hex color such as `#0b83d9`.
+    #[serde(default)]
+    pub color: Option<String>,
+    pub currency: Option<String>,
+    pub estimated_hours: Option<f64>,
+    pub id: Number,
+    pub name: String,
+    pub rate: Option<f64>,
+    pub workspace_id: Number,
+}
+
+/// A [JSON Patch](https://jsonpatch.com/) operation for bulk updates.
+#[derive(Serialize, Debug)]
+pub struct PatchOperation {
+    pub op: &'static str,
+    pub path: &'static str,
+    pub value: serde_json::Value,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PatchResult {
+    #[serde(default)]
+    pub success: Vec<Number>,
+    #[serde(default)]
+    pub failure: Vec<PatchFailure>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PatchFailure {
+    pub id: Number,
+    pub message: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct NewProject {
+    pub active: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub client_id: Option<Number>,
+    pub name: String,
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct ProjectUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active: Option<bool>,
+}
+
+/// A Toggl client, i.e. the customer that projects are done for.
+#[derive(Deserialize, Debug)]
+pub struct WorkspaceClient {
+    #[serde(default)]
+    pub archived: bool,
     pub id: Number,
     pub name: String,
+    pub wid: Number,
+}
+
+#[derive(Serialize, Debug)]
+pub struct NewClient {
+    pub name: String,
+    pub wid: Number,
+}
+
+/// Which events a webhook subscription is sent, e.g. `time_entry` and `*`
+/// for every change to time entries.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EventFilter {
+    pub entity: String,
+    pub action: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct WebhookSubscription {
+    pub description: String,
+    pub enabled: bool,
+    #[serde(default)]
+    pub event_filters: Vec<EventFilter>,
+    #[serde(default)]
+    pub secret: Option<String>,
+    pub subscription_id: Number,
+    pub url_callback: String,
+    #[serde(default)]
+    pub validated_at: Option<String>,
     pub workspace_id: Number,
 }
 
+#[derive(Serialize, Debug)]
+pub struct NewWebhookSubscription {
+    /// Toggl requires descriptions to be unique within a workspace.
+    pub description: String,
+    pub enabled: bool,
+    pub event_filters: Vec<EventFilter>,
+    pub url_callback: String,
+}
+
 #[derive(Deserialize, Debug)]
 pub struct Workspace {
+    pub default_currency: Option<String>,
+    pub default_hourly_rate: Option<f64>,
     pub id: Number,
+    #[serde(default)]
+    pub lock_date: Option<String>,
     pub name: String,
+    #[serde(default)]
+    pub rounding: i64,
+    #[serde(default)]
+    pub rounding_minutes: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merged_applies_changes_over_base() {
+        let base = NewClient {
+            name: "Acme".to_string(),
+            wid: 1.into(),
+        };
+        let client: WorkspaceClient = merged(&base, json!({ "id": 7 }));
+
+        assert_eq!("Acme", client.name);
+        assert_eq!(Number::from(7), client.id);
+        assert!(!client.archived);
+    }
 }
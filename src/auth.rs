@@ -0,0 +1,94 @@
+//! Commands that manage the saved API token without prompting, for scripts.
+
+use crate::{
+    client_options, decorate, icon, is_dry_run, keyring_entry, profile, ExitCode,
+    UNAUTHORIZED_EXIT_CODE,
+};
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use clap::{ArgGroup, Args, Subcommand};
+use std::{
+    fs,
+    io::{self, Read},
+    path::PathBuf,
+};
+use tgl_cli::Client;
+
+#[derive(Subcommand)]
+pub enum AuthCommand {
+    /// Check an API token with Toggl and save it in the keyring/keychain for
+    /// the profile
+    Login(LoginArgs),
+}
+
+#[derive(Args)]
+#[command(group(ArgGroup::new("source").required(true)))]
+pub struct LoginArgs {
+    /// Read the token from standard input
+    #[arg(long, group = "source")]
+    stdin: bool,
+    /// Read the token from this file
+    #[arg(long, group = "source", value_name = "PATH")]
+    token_file: Option<PathBuf>,
+}
+
+pub fn run_auth(command: &AuthCommand) -> Result<()> {
+    match command {
+        AuthCommand::Login(args) => run_login(args),
+    }
+}
+
+fn run_login(args: &LoginArgs) -> Result<()> {
+    let token = match &args.token_file {
+        Some(path) => fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?,
+        None => {
+            let mut token = String::new();
+            io::stdin()
+                .read_to_string(&mut token)
+                .context("Failed to read the token from standard input")?;
+            token
+        }
+    };
+    let token = token.trim();
+    if token.is_empty() {
+        bail!("The token is empty");
+    }
+
+    let client = Client::new(token.to_string(), Utc::now)
+        .context("Failed to create Toggle API client")?
+        .with_connection(&client_options().connection)
+        .context("Failed to create Toggle API client")?;
+    match client.get_workspaces() {
+        Ok(_) => {}
+        Err(tgl_cli::Error::Auth) => {
+            eprintln!(
+                "{} Toggl rejected the token, so it wasn't saved",
+                icon("🔑")
+            );
+            return Err(ExitCode(UNAUTHORIZED_EXIT_CODE).into());
+        }
+        Err(err) => return Err(err).context("Failed to check the token with Toggl"),
+    }
+
+    if is_dry_run() {
+        eprintln!(
+            "{} Would save the token for profile '{}'",
+            icon("🧪"),
+            profile()
+        );
+        return Ok(());
+    }
+    keyring_entry()
+        .set_password(token)
+        .context("Failed to save the API token to the keyring/keychain")?;
+    println!(
+        "{}",
+        decorate(
+            "✅",
+            &format!("Saved the token for profile '{}'", profile())
+        )
+    );
+
+    Ok(())
+}
@@ -0,0 +1,58 @@
+//! The time tracking backend that the commands shared by every backend work
+//! with. The rest need Toggl.
+
+use crate::{
+    config::{Backend, Config},
+    get_client,
+};
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use std::env;
+use tgl_cli::{ClockifyClient, DemoTracker, HarvestClient, TimeTracker};
+
+/// Reads a setting from the environment, falling back to the config file.
+fn setting(var: &str, configured: Option<String>) -> Option<String> {
+    env::var(var)
+        .ok()
+        .filter(|value| !value.is_empty())
+        .or(configured)
+}
+
+/// Connects to the configured backend.
+pub fn get_tracker(config: &Config) -> Result<Box<dyn TimeTracker>> {
+    Ok(match config.backend {
+        Backend::Toggl => Box::new(get_client()?),
+        Backend::Clockify => {
+            let api_key =
+                setting("CLOCKIFY_API_KEY", config.clockify.api_key.clone()).ok_or_else(|| {
+                    anyhow!("Set CLOCKIFY_API_KEY or 'api_key' under [clockify] in the config file")
+                })?;
+            Box::new(
+                ClockifyClient::new(api_key, Utc::now)
+                    .context("Failed to create Clockify API client")?,
+            )
+        }
+        Backend::Harvest => {
+            let token =
+                setting("HARVEST_ACCESS_TOKEN", config.harvest.token.clone()).ok_or_else(|| {
+                    anyhow!(
+                        "Set HARVEST_ACCESS_TOKEN or 'token' under [harvest] in the config file"
+                    )
+                })?;
+            let account_id = setting(
+                "HARVEST_ACCOUNT_ID",
+                config.harvest.account_id.map(|id| id.to_string()),
+            )
+            .ok_or_else(|| {
+                anyhow!("Set HARVEST_ACCOUNT_ID or 'account_id' under [harvest] in the config file")
+            })?
+            .parse()
+            .context("HARVEST_ACCOUNT_ID must be a number")?;
+            Box::new(
+                HarvestClient::new(token, account_id, Utc::now)
+                    .context("Failed to create Harvest API client")?,
+            )
+        }
+        Backend::Demo => Box::new(DemoTracker::new(Utc::now)),
+    })
+}
@@ -0,0 +1,126 @@
+//! Progress of projects against their estimated hours.
+
+use crate::{config::Config, decorate, get_client, icon_column};
+use anyhow::{Context, Result};
+use tgl_cli::{Client, Project, TimeEntry};
+
+/// How much of its estimate a project can use before it's "near" it.
+const BUDGET_NEAR_PERCENT: f64 = 90.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum BudgetState {
+    Ok,
+    Near,
+    Over,
+}
+
+/// Returns the percent of the project's estimate used so far, or `None` if
+/// the project has no estimate.
+fn percent_used(project: &Project) -> Option<f64> {
+    let estimated = project.estimated_hours.filter(|h| *h > 0.0)?;
+
+    Some(project.actual_hours.unwrap_or_default() / estimated * 100.0)
+}
+
+fn budget_state(percent: f64) -> BudgetState {
+    if percent > 100.0 {
+        BudgetState::Over
+    } else if percent >= BUDGET_NEAR_PERCENT {
+        BudgetState::Near
+    } else {
+        BudgetState::Ok
+    }
+}
+
+pub fn run_budget(config: &Config) -> Result<()> {
+    let client = get_client()?;
+    let workspaces = client
+        .get_workspaces()
+        .context("Failed to retrieve workspaces")?;
+    let mut budgets = Vec::new();
+    for workspace in &workspaces {
+        let projects = client
+            .get_projects(workspace.id)
+            .context("Failed to get projects")?;
+        for project in projects.into_iter().filter(|p| p.active) {
+            if let Some(percent) = percent_used(&project) {
+                budgets.push((percent, project));
+            }
+        }
+    }
+    if budgets.is_empty() {
+        println!("{}", decorate("🤷", "No active projects have an estimate"));
+        return Ok(());
+    }
+
+    budgets.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+    for (percent, project) in &budgets {
+        let emoji = match budget_state(*percent) {
+            BudgetState::Ok => None,
+            BudgetState::Near => Some("⚠️ "),
+            BudgetState::Over => Some("🔥"),
+        };
+        println!(
+            "{} {percent:>4.0}% {:>6.1}h of {:>6.1}h [{}]",
+            icon_column(emoji),
+            project.actual_hours.unwrap_or_default(),
+            project.estimated_hours.unwrap_or_default(),
+            config
+                .theme
+                .paint_project(&project.name, project.color.as_deref())
+        );
+    }
+
+    Ok(())
+}
+
+/// Describes how close the entry's project is to its estimate, if it's near
+/// or over it.
+pub fn budget_warning(client: &Client, entry: &TimeEntry) -> Result<Option<String>> {
+    let Some(project_id) = entry.project_id else {
+        return Ok(None);
+    };
+    let Some(project) = client
+        .get_project(entry.workspace_id, project_id)
+        .context("Failed to get project")?
+    else {
+        return Ok(None);
+    };
+    let Some(percent) = percent_used(project) else {
+        return Ok(None);
+    };
+
+    Ok(match budget_state(percent) {
+        BudgetState::Ok => None,
+        BudgetState::Near => Some(decorate(
+            "⚠️ ",
+            &format!(
+                "{} has used {percent:.0}% of its {}h estimate",
+                project.name,
+                project.estimated_hours.unwrap_or_default()
+            ),
+        )),
+        BudgetState::Over => Some(decorate(
+            "🔥",
+            &format!(
+                "{} is over its {}h estimate ({percent:.0}%)",
+                project.name,
+                project.estimated_hours.unwrap_or_default()
+            ),
+        )),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn budget_states() {
+        assert_eq!(BudgetState::Ok, budget_state(0.0));
+        assert_eq!(BudgetState::Ok, budget_state(89.9));
+        assert_eq!(BudgetState::Near, budget_state(90.0));
+        assert_eq!(BudgetState::Near, budget_state(100.0));
+        assert_eq!(BudgetState::Over, budget_state(100.1));
+    }
+}
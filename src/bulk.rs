@@ -0,0 +1,359 @@
+//! Commands that change many time entries at once.
+
+use crate::{
+    check::{fmt_entry, interval},
+    clear_prompt_cache, decorate, ensure_can_prompt, ensure_unlocked, find_workspaces, get_client,
+    icon, journal,
+    report::{get_range_entries, RangeArgs},
+    rounding::Interval,
+};
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Duration, Local, Utc};
+use clap::Args;
+use regex::Regex;
+use std::collections::BTreeMap;
+use tgl_cli::{BulkUpdate, Client, TimeEntry, TimeEntryUpdate};
+
+/// Selects time entries by date, project, and description.
+#[derive(Args)]
+pub struct EntryFilter {
+    #[command(flatten)]
+    range: RangeArgs,
+    /// Only entries in this project, ignoring case
+    #[arg(long, value_name = "NAME")]
+    project: Option<String>,
+    /// Only entries whose description matches this regular expression
+    #[arg(long, value_name = "REGEX")]
+    description: Option<Regex>,
+}
+
+impl EntryFilter {
+    /// Gets the entries in the filter's date range that match it.
+    pub fn get_entries(&self, client: &Client) -> Result<Vec<TimeEntry>> {
+        let (from, to) = self.range.resolve()?;
+        let mut entries = get_range_entries(client, from, to)?;
+        entries.retain(|e| self.matches(e));
+
+        Ok(entries)
+    }
+
+    fn matches(&self, entry: &TimeEntry) -> bool {
+        let project_matches = self.project.as_ref().is_none_or(|project| {
+            entry
+                .project_name
+                .as_ref()
+                .is_some_and(|name| name.eq_ignore_ascii_case(project))
+        });
+        let description_matches = self
+            .description
+            .as_ref()
+            .is_none_or(|re| re.is_match(entry.description.as_deref().unwrap_or_default()));
+
+        project_matches && description_matches
+    }
+}
+
+#[derive(Args)]
+pub struct BulkArgs {
+    #[command(flatten)]
+    filter: EntryFilter,
+    /// Move the entries to this project, or remove their project with ""
+    #[arg(long, value_name = "NAME")]
+    set_project: Option<String>,
+    /// Add a tag to the entries (repeatable)
+    #[arg(long = "add-tag", value_name = "TAG")]
+    add_tags: Vec<String>,
+    /// Mark the entries as billable or not
+    #[arg(long, value_name = "BOOL")]
+    billable: Option<bool>,
+    /// Don't ask for confirmation
+    #[arg(short, long)]
+    yes: bool,
+}
+
+#[derive(Args)]
+pub struct MoveArgs {
+    #[command(flatten)]
+    range: RangeArgs,
+    /// Project to move entries out of
+    #[arg(long, value_name = "NAME")]
+    from_project: String,
+    /// Project to move entries into
+    #[arg(long, value_name = "NAME")]
+    to_project: String,
+    /// Don't ask for confirmation
+    #[arg(short, long)]
+    yes: bool,
+}
+
+#[derive(Args)]
+pub struct MergeArgs {
+    #[command(flatten)]
+    range: RangeArgs,
+    /// Merge entries separated by at most this long, e.g. 1m or 10m
+    #[arg(long, value_name = "INTERVAL", default_value = "5m")]
+    max_gap: Interval,
+    /// Don't ask for confirmation
+    #[arg(short, long)]
+    yes: bool,
+}
+
+pub fn run_bulk(args: &BulkArgs) -> Result<()> {
+    if args.set_project.is_none() && args.add_tags.is_empty() && args.billable.is_none() {
+        bail!("Nothing to change, pass --set-project, --add-tag, or --billable");
+    }
+    let client = get_client()?;
+    let entries = args.filter.get_entries(&client)?;
+
+    let mut update = BulkUpdate::new().add_tags(args.add_tags.clone());
+    if let Some(billable) = args.billable {
+        update = update.billable(billable);
+    }
+    apply_to_entries(&client, &entries, args.yes, |workspace_id| {
+        match args.set_project.as_deref() {
+            None => Ok(update.clone()),
+            Some("") => Ok(update.clone().project_id(None)),
+            Some(name) => {
+                let project = client
+                    .find_project_by_name(workspace_id, name)
+                    .context("Failed to get projects")?
+                    .ok_or_else(|| anyhow!("Project '{name}' not found"))?;
+                Ok(update.clone().project_id(Some(project.id)))
+            }
+        }
+    })
+}
+
+pub fn run_move(args: &MoveArgs) -> Result<()> {
+    let client = get_client()?;
+    let mut from_ids = Vec::new();
+    for w in find_workspaces(&client, None)? {
+        if let Some(p) = client
+            .find_project_by_name(w.id, &args.from_project)
+            .context("Failed to get projects")?
+        {
+            from_ids.push(p.id);
+        }
+    }
+    if from_ids.is_empty() {
+        bail!("Project '{}' not found", args.from_project);
+    }
+
+    let (from, to) = args.range.resolve()?;
+    let mut entries = get_range_entries(&client, from, to)?;
+    entries.retain(|e| e.project_id.is_some_and(|id| from_ids.contains(&id)));
+    apply_to_entries(&client, &entries, args.yes, |workspace_id| {
+        let project = client
+            .find_project_by_name(workspace_id, &args.to_project)
+            .context("Failed to get projects")?
+            .ok_or_else(|| anyhow!("Project '{}' not found", args.to_project))?;
+        Ok(BulkUpdate::new().project_id(Some(project.id)))
+    })
+}
+
+pub fn run_merge(args: &MergeArgs) -> Result<()> {
+    let client = get_client()?;
+    let (from, to) = args.range.resolve()?;
+    let now = Utc::now();
+    let entries: Vec<_> = get_range_entries(&client, from, to)?
+        .into_iter()
+        .filter(|e| e.start.is_some())
+        .collect();
+    let spans: Vec<_> = entries
+        .iter()
+        .map(|e| {
+            let key = (e.workspace_id, e.project_id, e.description.as_deref());
+            let (start, stop) = interval(e, now);
+            (key, start, stop)
+        })
+        .collect();
+    let runs = find_runs(&spans, args.max_gap.duration());
+    if runs.is_empty() {
+        println!(
+            "{}",
+            decorate("✅", &format!("No entries to merge from {from} to {to}"))
+        );
+        return Ok(());
+    }
+
+    ensure_unlocked(&client, runs.iter().flatten().map(|&i| &entries[i]))?;
+    for run in &runs {
+        for &i in run {
+            println!("{}", fmt_dated_entry(&entries[i]));
+        }
+        println!();
+    }
+    if !args.yes {
+        ensure_can_prompt("confirmation", Some("Pass --yes to skip it"))?;
+        let confirmed = dialoguer::Confirm::new()
+            .with_prompt(format!("Merge these into {} entries?", runs.len()))
+            .default(false)
+            .interact()
+            .context("Failed to read confirmation")?;
+        if !confirmed {
+            bail!("Aborted");
+        }
+    }
+
+    for run in &runs {
+        // Keep the last entry so that a running entry keeps running.
+        let (last, rest) = run.split_last().expect("runs have several entries");
+        let first = &entries[rest[0]];
+        let start = first.start.expect("entry has a start time");
+        client
+            .update_time_entry(&entries[*last], &TimeEntryUpdate::new().start(start))
+            .context("Failed to update time entry")?;
+        for &i in rest {
+            client
+                .delete_time_entry(&entries[i])
+                .context("Failed to delete time entry")?;
+        }
+    }
+    journal::forget();
+    clear_prompt_cache();
+    println!(
+        "{}",
+        decorate(
+            "✅",
+            &format!(
+                "Merged {} entries into {}",
+                runs.iter().map(Vec::len).sum::<usize>(),
+                runs.len()
+            )
+        )
+    );
+
+    Ok(())
+}
+
+/// Finds runs of consecutive spans with the same key, each starting at most
+/// `max_gap` after the previous one stops. `spans` must be sorted by start
+/// time; only runs of two or more are returned, as indices.
+fn find_runs<K: PartialEq>(
+    spans: &[(K, DateTime<Utc>, DateTime<Utc>)],
+    max_gap: Duration,
+) -> Vec<Vec<usize>> {
+    let mut runs: Vec<Vec<usize>> = Vec::new();
+    let mut current = vec![];
+    for (i, (key, start, _)) in spans.iter().enumerate() {
+        let joins = current.last().is_some_and(|&prev: &usize| {
+            let (prev_key, _, prev_stop) = &spans[prev];
+            prev_key == key && *start - *prev_stop <= max_gap
+        });
+        if !joins {
+            if current.len() > 1 {
+                runs.push(std::mem::take(&mut current));
+            }
+            current.clear();
+        }
+        current.push(i);
+    }
+    if current.len() > 1 {
+        runs.push(current);
+    }
+
+    runs
+}
+
+/// Previews `entries`, asks for confirmation unless `yes`, and applies the
+/// update that `update_for` builds for each workspace.
+pub fn apply_to_entries(
+    client: &Client,
+    entries: &[TimeEntry],
+    yes: bool,
+    update_for: impl Fn(i64) -> Result<BulkUpdate>,
+) -> Result<()> {
+    if entries.is_empty() {
+        println!("{}", decorate("🤷", "No matching entries"));
+        return Ok(());
+    }
+    ensure_unlocked(client, entries)?;
+
+    let mut by_workspace: BTreeMap<i64, Vec<TimeEntry>> = BTreeMap::new();
+    for entry in entries {
+        by_workspace
+            .entry(entry.workspace_id)
+            .or_default()
+            .push(entry.clone());
+    }
+    // Resolve everything before changing anything.
+    let updates = by_workspace
+        .into_iter()
+        .map(|(workspace_id, entries)| Ok((update_for(workspace_id)?, entries)))
+        .collect::<Result<Vec<_>>>()?;
+
+    for entry in entries {
+        println!("{}", fmt_dated_entry(entry));
+    }
+    if !yes {
+        ensure_can_prompt("confirmation", Some("Pass --yes to skip it"))?;
+        let confirmed = dialoguer::Confirm::new()
+            .with_prompt(format!("Update these {} entries?", entries.len()))
+            .default(false)
+            .interact()
+            .context("Failed to read confirmation")?;
+        if !confirmed {
+            bail!("Aborted");
+        }
+    }
+
+    let mut updated = 0;
+    for (update, entries) in &updates {
+        let result = client
+            .bulk_update_time_entries(entries, update)
+            .context("Failed to update time entries")?;
+        updated += result.updated.len();
+        for (id, message) in &result.failed {
+            eprintln!("{} Entry {id} wasn't updated: {message}", icon("❌"));
+        }
+    }
+    journal::forget();
+    clear_prompt_cache();
+    println!(
+        "{}",
+        decorate(
+            "✅",
+            &format!("Updated {updated} of {} entries", entries.len())
+        )
+    );
+
+    Ok(())
+}
+
+pub fn fmt_dated_entry(entry: &TimeEntry) -> String {
+    let date = entry
+        .start
+        .map(|s| DateTime::<Local>::from(s).format("%Y-%m-%d").to_string())
+        .unwrap_or_default();
+
+    format!("{date} {}", fmt_entry(entry))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 6, 3, hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn find_runs_of_close_duplicates() {
+        let spans = [
+            ("a", at(9, 0), at(9, 30)),
+            ("a", at(9, 32), at(10, 0)),
+            ("a", at(10, 5), at(10, 30)),
+            ("b", at(10, 30), at(11, 0)),
+            ("a", at(11, 0), at(11, 30)),
+            ("a", at(11, 45), at(12, 0)),
+            ("c", at(12, 0), at(12, 30)),
+            ("c", at(12, 30), at(13, 0)),
+        ];
+
+        assert_eq!(
+            vec![vec![0, 1, 2], vec![6, 7]],
+            find_runs(&spans, Duration::minutes(5))
+        );
+    }
+}
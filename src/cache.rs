@@ -0,0 +1,171 @@
+//! Workspaces and projects saved to disk between runs, so that each command
+//! doesn't refetch them.
+
+use crate::svc::{Project, Workspace};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::{cell::RefCell, collections::BTreeMap, fs, path::PathBuf};
+
+pub(crate) struct DiskCache {
+    path: PathBuf,
+    ttl: Duration,
+    data: RefCell<CacheData>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct CacheData {
+    workspaces: Option<Cached<Vec<Workspace>>>,
+    #[serde(default)]
+    projects: BTreeMap<i64, Cached<Vec<Project>>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Cached<T> {
+    fetched_at: DateTime<Utc>,
+    value: T,
+    /// Toggl's ETag for the value, to check whether it changed once it's no
+    /// longer fresh.
+    #[serde(default)]
+    etag: Option<String>,
+}
+
+impl DiskCache {
+    /// Reads the cache file. A missing or unreadable file is treated as an
+    /// empty cache.
+    pub fn load(path: PathBuf, ttl: Duration) -> Self {
+        let data = fs::read(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_slice(&contents).ok())
+            .unwrap_or_default();
+
+        Self {
+            path,
+            ttl,
+            data: RefCell::new(data),
+        }
+    }
+
+    pub fn workspaces(&self, now: DateTime<Utc>) -> Option<Vec<Workspace>> {
+        let data = self.data.borrow();
+        let cached = data.workspaces.as_ref()?;
+
+        self.is_fresh(cached, now).then(|| cached.value.clone())
+    }
+
+    /// Gets the cached workspaces, fresh or not, if they have an ETag.
+    pub fn etagged_workspaces(&self) -> Option<(String, Vec<Workspace>)> {
+        let data = self.data.borrow();
+
+        data.workspaces.as_ref()?.etagged()
+    }
+
+    pub fn set_workspaces(
+        &self,
+        now: DateTime<Utc>,
+        workspaces: &[Workspace],
+        etag: Option<String>,
+    ) {
+        self.data.borrow_mut().workspaces = Some(Cached {
+            fetched_at: now,
+            value: workspaces.to_vec(),
+            etag,
+        });
+        self.save();
+    }
+
+    pub fn projects(&self, workspace_id: i64, now: DateTime<Utc>) -> Option<Vec<Project>> {
+        let data = self.data.borrow();
+        let cached = data.projects.get(&workspace_id)?;
+
+        self.is_fresh(cached, now).then(|| cached.value.clone())
+    }
+
+    /// Gets the workspace's cached projects, fresh or not, if they have an
+    /// ETag.
+    pub fn etagged_projects(&self, workspace_id: i64) -> Option<(String, Vec<Project>)> {
+        let data = self.data.borrow();
+
+        data.projects.get(&workspace_id)?.etagged()
+    }
+
+    pub fn set_projects(
+        &self,
+        workspace_id: i64,
+        now: DateTime<Utc>,
+        projects: &[Project],
+        etag: Option<String>,
+    ) {
+        self.data.borrow_mut().projects.insert(
+            workspace_id,
+            Cached {
+                fetched_at: now,
+                value: projects.to_vec(),
+                etag,
+            },
+        );
+        self.save();
+    }
+
+    /// Drops the workspace's projects after they changed in Toggl.
+    pub fn forget_projects(&self, workspace_id: i64) {
+        if self
+            .data
+            .borrow_mut()
+            .projects
+            .remove(&workspace_id)
+            .is_some()
+        {
+            self.save();
+        }
+    }
+
+    fn is_fresh<T>(&self, cached: &Cached<T>, now: DateTime<Utc>) -> bool {
+        now - cached.fetched_at < self.ttl
+    }
+
+    fn save(&self) {
+        // The cache only saves time, so failing to write it isn't worth
+        // failing the command over.
+        if let Some(dir) = self.path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        if let Ok(contents) = serde_json::to_vec(&*self.data.borrow()) {
+            let _ = fs::write(&self.path, contents);
+        }
+    }
+}
+
+impl<T: Clone> Cached<T> {
+    fn etagged(&self) -> Option<(String, T)> {
+        Some((self.etag.clone()?, self.value.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_expire_after_ttl() {
+        let path = std::env::temp_dir().join(format!("tgl-cache-test-{}", std::process::id()));
+        let fetched_at = Utc::now();
+        let cache = DiskCache::load(path.clone(), Duration::minutes(15));
+        cache.set_workspaces(fetched_at, &[], Some("\"v1\"".to_string()));
+
+        let cache = DiskCache::load(path.clone(), Duration::minutes(15));
+        assert!(cache
+            .workspaces(fetched_at + Duration::minutes(14))
+            .is_some());
+        assert!(cache
+            .workspaces(fetched_at + Duration::minutes(15))
+            .is_none());
+        assert!(cache.projects(1, fetched_at).is_none());
+        // Expired values are kept to revalidate with their ETag.
+        assert_eq!(
+            Some("\"v1\"".to_string()),
+            cache.etagged_workspaces().map(|(etag, _)| etag)
+        );
+
+        let _ = fs::remove_file(path);
+    }
+}
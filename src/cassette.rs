@@ -0,0 +1,282 @@
+//! Recording Toggl's responses to a file and serving them back, for
+//! end-to-end tests and bug reports that don't need a real account.
+
+use reqwest::{
+    blocking::{Request, Response},
+    header,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    fs, io,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+use tracing::warn;
+
+/// Response fields replaced before they're written to a cassette.
+const SECRET_FIELDS: [&str; 1] = ["api_token"];
+
+/// Request and response pairs kept in a JSON file.
+///
+/// A recording cassette passes requests through and saves each exchange as it
+/// happens. The API token is never saved. A replaying cassette answers from
+/// the file without sending anything. Requests are matched on method and
+/// URL, or failing that on method and path so that date ranges computed from
+/// the current time still find their response. When several match, the
+/// first one recorded after the last response served wins, so that a
+/// command replays the exchanges that followed each other when it was
+/// recorded. Clones share the file and where replay is up to.
+#[derive(Clone)]
+pub struct Cassette {
+    path: PathBuf,
+    replay: bool,
+    interactions: Arc<Mutex<Vec<Interaction>>>,
+    /// The index of the last interaction served.
+    cursor: Arc<Mutex<Option<usize>>>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct Interaction {
+    method: String,
+    url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    request_body: Option<Value>,
+    status: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    etag: Option<String>,
+    /// The response body, or null when it was empty. Bodies that aren't
+    /// JSON are kept as strings.
+    body: Value,
+}
+
+/// What's needed of a request once it's been handed to the HTTP client.
+pub(crate) struct RequestInfo {
+    method: String,
+    url: String,
+    body: Option<Value>,
+}
+
+impl RequestInfo {
+    pub(crate) fn new(request: &Request) -> Self {
+        Self {
+            method: request.method().to_string(),
+            url: request.url().to_string(),
+            body: request
+                .body()
+                .and_then(|b| b.as_bytes())
+                .and_then(|b| serde_json::from_slice(b).ok()),
+        }
+    }
+}
+
+impl Cassette {
+    /// Records every exchange into the file at `path`, after any already
+    /// there.
+    pub fn record(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let interactions = match fs::read(&path) {
+            Ok(json) => parse(&json)?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(err) => return Err(err),
+        };
+
+        Ok(Self {
+            path,
+            replay: false,
+            interactions: Arc::new(Mutex::new(interactions)),
+            cursor: Arc::new(Mutex::new(None)),
+        })
+    }
+
+    /// Answers requests from the file at `path` instead of sending them.
+    pub fn replay(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let interactions = parse(&fs::read(&path)?)?;
+
+        Ok(Self {
+            path,
+            replay: true,
+            interactions: Arc::new(Mutex::new(interactions)),
+            cursor: Arc::new(Mutex::new(None)),
+        })
+    }
+
+    /// Whether the cassette answers requests rather than recording them.
+    pub fn is_replay(&self) -> bool {
+        self.replay
+    }
+
+    /// Finds the recorded response to a request. Requests that weren't
+    /// recorded get a 501 Not Implemented.
+    pub(crate) fn respond(&self, request: &RequestInfo) -> Response {
+        let interactions = self.interactions.lock().unwrap();
+        let mut cursor = self.cursor.lock().unwrap();
+        let path = |url: &str| url.split('?').next().unwrap_or_default().to_string();
+        let matching = |matches: &dyn Fn(&Interaction) -> bool| -> Vec<usize> {
+            interactions
+                .iter()
+                .enumerate()
+                .filter(|(_, i)| i.method == request.method && matches(i))
+                .map(|(index, _)| index)
+                .collect()
+        };
+        let mut candidates = matching(&|i| i.url == request.url);
+        if candidates.is_empty() {
+            candidates = matching(&|i| path(&i.url) == path(&request.url));
+        }
+        let found = candidates
+            .iter()
+            .find(|&&index| cursor.is_none_or(|cursor| index > cursor))
+            .or(candidates.first());
+
+        let Some(&index) = found else {
+            warn!(method = %request.method, url = %request.url, "not in the cassette");
+            return build_response(501, None, &Value::String("Not in the cassette".into()));
+        };
+        *cursor = Some(index);
+        let interaction = &interactions[index];
+
+        build_response(
+            interaction.status,
+            interaction.etag.as_deref(),
+            &interaction.body,
+        )
+    }
+
+    /// Saves an exchange and hands back an equivalent response, since the
+    /// original's body has been read.
+    pub(crate) fn record_response(
+        &self,
+        request: RequestInfo,
+        response: Response,
+    ) -> Result<Response, reqwest::Error> {
+        let status = response.status().as_u16();
+        let etag = response
+            .headers()
+            .get(header::ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let bytes = response.bytes()?;
+        let body = match serde_json::from_slice(&bytes) {
+            Ok(body) => body,
+            Err(_) if bytes.is_empty() => Value::Null,
+            Err(_) => Value::String(String::from_utf8_lossy(&bytes).into_owned()),
+        };
+        let response = build_response(status, etag.as_deref(), &body);
+
+        let mut body = body;
+        redact(&mut body);
+        let mut interactions = self.interactions.lock().unwrap();
+        interactions.push(Interaction {
+            method: request.method,
+            url: request.url,
+            request_body: request.body,
+            status,
+            etag,
+            body,
+        });
+        let saved = serde_json::to_vec_pretty(&*interactions)
+            .map_err(io::Error::from)
+            .and_then(|json| fs::write(&self.path, json));
+        if let Err(error) = saved {
+            warn!(%error, path = %self.path.display(), "failed to save the cassette");
+        }
+
+        Ok(response)
+    }
+}
+
+fn parse(json: &[u8]) -> io::Result<Vec<Interaction>> {
+    serde_json::from_slice(json).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn build_response(status: u16, etag: Option<&str>, body: &Value) -> Response {
+    let mut builder = http::Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "application/json");
+    if let Some(etag) = etag {
+        builder = builder.header(header::ETAG, etag);
+    }
+    // Empty bodies come back as `null`, which nothing that expects an empty
+    // body reads.
+    let body = serde_json::to_vec(body).expect("JSON values always serialize");
+
+    builder
+        .body(body)
+        .expect("recorded statuses and headers are valid")
+        .into()
+}
+
+/// Replaces secrets anywhere in a response body.
+fn redact(value: &mut Value) {
+    match value {
+        Value::Object(fields) => {
+            for (key, field) in fields.iter_mut() {
+                if SECRET_FIELDS.contains(&key.as_str()) {
+                    *field = Value::String("REDACTED".into());
+                } else {
+                    redact(field);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn replays_what_followed_the_last_response() {
+        let interaction = |url: &str, body| Interaction {
+            method: "GET".into(),
+            url: url.into(),
+            request_body: None,
+            status: 200,
+            etag: None,
+            body,
+        };
+        let cassette = Cassette {
+            path: PathBuf::new(),
+            replay: true,
+            interactions: Arc::new(Mutex::new(vec![
+                interaction("https://x/me/time_entries?start=1", json!([1])),
+                interaction("https://x/workspaces", json!([2])),
+                interaction("https://x/me/time_entries?start=1", json!([3])),
+            ])),
+            cursor: Arc::new(Mutex::new(None)),
+        };
+        let get = |url: &str| {
+            cassette
+                .respond(&RequestInfo {
+                    method: "GET".into(),
+                    url: url.into(),
+                    body: None,
+                })
+                .json::<Value>()
+                .unwrap_or(Value::Null)
+        };
+
+        assert_eq!(json!([1]), get("https://x/me/time_entries?start=1"));
+        assert_eq!(json!([3]), get("https://x/me/time_entries?start=2"));
+        assert_eq!(json!([1]), get("https://x/me/time_entries?start=1"));
+        assert_eq!(json!([2]), get("https://x/workspaces"));
+        assert_eq!(json!([3]), get("https://x/me/time_entries?start=1"));
+        assert_eq!(json!("Not in the cassette"), get("https://x/me"));
+    }
+
+    #[test]
+    fn redacts_tokens() {
+        let mut body = json!({ "api_token": "secret", "workspaces": [{ "api_token": "x" }] });
+        redact(&mut body);
+
+        assert_eq!(
+            json!({ "api_token": "REDACTED", "workspaces": [{ "api_token": "REDACTED" }] }),
+            body
+        );
+    }
+}
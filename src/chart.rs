@@ -0,0 +1,261 @@
+//! Charts of tracked time drawn in the terminal.
+
+use crate::{
+    config::Config, decorate, duration::fmt_duration, eprintln_stale_banner, get_client, i18n,
+    report::get_range_entries, targets::week_forecast,
+};
+use anyhow::{bail, Result};
+use chrono::{DateTime, Datelike, Days, Duration, Local, NaiveDate};
+use clap::Args;
+use dialoguer::console::Style;
+use std::collections::BTreeMap;
+use tgl_cli::TimeEntry;
+
+#[derive(Args)]
+pub struct HeatmapArgs {
+    /// How many weeks to show, ending with this week
+    #[arg(long, default_value_t = 12)]
+    weeks: u64,
+}
+
+#[derive(Args)]
+pub struct WeekArgs {
+    /// Draw a bar chart of each day's hours, marking the daily target
+    #[arg(long)]
+    chart: bool,
+}
+
+/// How many columns the longest bar of `tgl week --chart` spans.
+const WEEK_CHART_WIDTH: usize = 40;
+
+/// Cells from no time to the daily target or more.
+const HEATMAP_CELLS: [&str; 5] = ["·", "░", "▒", "▓", "█"];
+const HEATMAP_COLORS: [u8; 5] = [240, 22, 28, 34, 40];
+
+pub fn run_heatmap(config: &Config, args: &HeatmapArgs) -> Result<()> {
+    if args.weeks == 0 {
+        bail!("--weeks must be at least 1");
+    }
+    let client = get_client()?;
+    let today = Local::now().date_naive();
+    let monday = today - Days::new(today.weekday().num_days_from_monday().into());
+    let from = monday - Days::new(7 * (args.weeks - 1));
+    let totals = daily_totals(&get_range_entries(&client, from, today)?);
+    let target = config.work_hours.daily_target.duration();
+    let weeks: Vec<_> = (0..args.weeks).map(|w| from + Days::new(7 * w)).collect();
+
+    // Label the first column of each month. Labels are ASCII, so byte
+    // length is the display width.
+    let mut header = String::new();
+    for (i, week) in weeks.iter().enumerate() {
+        let column = 4 + 2 * i;
+        let new_month = i == 0 || weeks[i - 1].month() != week.month();
+        if new_month && header.len() < column {
+            header.push_str(&" ".repeat(column - header.len()));
+            header.push_str(&week.format("%b").to_string());
+        }
+    }
+    println!("{header}");
+
+    for day in 0..7 {
+        let mut line = format!("{:<4}", (from + Days::new(day)).format("%a"));
+        for week in &weeks {
+            let date = *week + Days::new(day);
+            if date > today {
+                break;
+            }
+            let dur = totals.get(&date).copied().unwrap_or_else(Duration::zero);
+            line.push_str(&fmt_cell(heatmap_level(dur, target)));
+            line.push(' ');
+        }
+        println!("{}", line.trim_end());
+    }
+
+    println!();
+    let legend: Vec<_> = (0..HEATMAP_CELLS.len()).map(fmt_cell).collect();
+    println!(
+        "    Less {} More (target {})",
+        legend.join(" "),
+        fmt_duration(target, config.duration_format)
+    );
+
+    Ok(())
+}
+
+pub fn run_week(config: &Config, args: &WeekArgs) -> Result<()> {
+    let client = get_client()?;
+    let today = Local::now().date_naive();
+    let monday = today - Days::new(today.weekday().num_days_from_monday().into());
+    let sunday = monday + Days::new(6);
+    let totals = daily_totals(&get_range_entries(&client, monday, sunday)?);
+    eprintln_stale_banner(&client);
+    let target = config.work_hours.daily_target.duration();
+    let days: Vec<_> = (0..7)
+        .map(|d| {
+            let date = monday + Days::new(d);
+            (
+                date,
+                totals.get(&date).copied().unwrap_or_else(Duration::zero),
+            )
+        })
+        .collect();
+
+    // Leave some room past the target so it's clear when a day exceeds it.
+    let longest = days.iter().map(|(_, dur)| *dur).max().unwrap_or(target);
+    let full = longest.max(target + target / 4);
+    let marker = column(target, full, WEEK_CHART_WIDTH);
+
+    let mut total = Duration::zero();
+    for (date, dur) in &days {
+        total += *dur;
+        let label = i18n::fmt_date(*date, "day-heading-format");
+        let formatted = fmt_duration(*dur, config.duration_format);
+        if args.chart {
+            println!(
+                "{label} {} {formatted:>8}",
+                render_bar(eighths(*dur, full, WEEK_CHART_WIDTH), marker)
+            );
+        } else {
+            println!("{label} {formatted:>8}");
+        }
+    }
+    println!();
+    println!(
+        "{}",
+        decorate(
+            "⏱ ",
+            &i18n::tr(
+                "week-logged",
+                &[(
+                    "duration",
+                    config
+                        .theme
+                        .total
+                        .paint(fmt_duration(total, config.duration_format))
+                        .to_string()
+                        .into()
+                )]
+            )
+        )
+    );
+    if let Some(forecast) = week_forecast(config, total) {
+        println!("{forecast}");
+    }
+    if args.chart {
+        println!(
+            "   {}",
+            i18n::tr(
+                "week-chart-target",
+                &[(
+                    "target",
+                    fmt_duration(target, config.duration_format).into()
+                )]
+            )
+        );
+    }
+
+    Ok(())
+}
+
+fn fmt_cell(level: usize) -> String {
+    Style::new()
+        .color256(HEATMAP_COLORS[level])
+        .apply_to(HEATMAP_CELLS[level])
+        .to_string()
+}
+
+/// Sums the entries' durations by the local date they started on.
+pub fn daily_totals(entries: &[TimeEntry]) -> BTreeMap<NaiveDate, Duration> {
+    let mut totals = BTreeMap::new();
+    for entry in entries {
+        if let Some(start) = entry.start {
+            let date = DateTime::<Local>::from(start).date_naive();
+            *totals.entry(date).or_insert_with(Duration::zero) += entry.duration;
+        }
+    }
+
+    totals
+}
+
+/// The column of a `width`-column chart that `dur` reaches, where `full`
+/// spans the whole width.
+fn column(dur: Duration, full: Duration, width: usize) -> usize {
+    eighths(dur, full, width) / 8
+}
+
+/// Like [`column`], but in eighths of a column.
+fn eighths(dur: Duration, full: Duration, width: usize) -> usize {
+    let secs = dur.num_seconds().max(0) as u128;
+    let full = full.num_seconds().max(1) as u128;
+
+    (secs * width as u128 * 8 / full).min(width as u128 * 8) as usize
+}
+
+/// Draws a bar `eighths` eighths of a column long, padded to
+/// [`WEEK_CHART_WIDTH`] with a marker at the `marker` column if the bar
+/// doesn't reach it.
+fn render_bar(eighths: usize, marker: usize) -> String {
+    const PARTIAL: [char; 8] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+
+    let mut bar = String::new();
+    for col in 0..WEEK_CHART_WIDTH {
+        let filled = eighths.saturating_sub(col * 8).min(8);
+        bar.push(match filled {
+            8 => '█',
+            0 if col == marker => '│',
+            n => PARTIAL[n],
+        });
+    }
+
+    bar
+}
+
+/// Buckets a day's total into one of the [`HEATMAP_CELLS`], relative to the
+/// daily target.
+fn heatmap_level(dur: Duration, target: Duration) -> usize {
+    if dur <= Duration::zero() {
+        return 0;
+    }
+    let quarters = dur.num_seconds() * 4 / target.num_seconds().max(1);
+    match quarters {
+        0..=1 => 1,
+        2 => 2,
+        3 => 3,
+        _ => 4,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn heatmap_levels() {
+        let target = Duration::hours(8);
+        assert_eq!(0, heatmap_level(Duration::zero(), target));
+        assert_eq!(1, heatmap_level(Duration::minutes(1), target));
+        assert_eq!(1, heatmap_level(Duration::minutes(3 * 60 + 59), target));
+        assert_eq!(2, heatmap_level(Duration::hours(4), target));
+        assert_eq!(3, heatmap_level(Duration::hours(7), target));
+        assert_eq!(4, heatmap_level(Duration::hours(8), target));
+        assert_eq!(4, heatmap_level(Duration::hours(11), target));
+    }
+
+    #[test]
+    fn render_bars() {
+        let full = Duration::hours(10);
+        let marker = column(Duration::hours(8), full, WEEK_CHART_WIDTH);
+        assert_eq!(32, marker);
+
+        let bar = render_bar(
+            eighths(Duration::minutes(45), full, WEEK_CHART_WIDTH),
+            marker,
+        );
+        assert_eq!(WEEK_CHART_WIDTH, bar.chars().count());
+        assert_eq!("███", &bar[..9]);
+        assert_eq!(Some('│'), bar.chars().nth(32));
+
+        let bar = render_bar(eighths(Duration::hours(12), full, WEEK_CHART_WIDTH), marker);
+        assert!(bar.chars().all(|c| c == '█'));
+    }
+}
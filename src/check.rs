@@ -0,0 +1,347 @@
+//! Consistency checks over a range of time entries.
+
+use crate::{
+    clear_prompt_cache,
+    config::{Config, WorkHours},
+    decorate,
+    duration::fmt_duration,
+    ensure_can_prompt,
+    entry::at_local_time,
+    get_client, journal, prompt_entry_details,
+    report::{get_range_entries, RangeArgs},
+    rounding::Interval,
+    running_stop,
+};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, Days, Duration, Local, NaiveDate, Utc};
+use clap::{Args, Subcommand};
+use tgl_cli::{TimeEntry, TimeEntryUpdate};
+
+#[derive(Subcommand)]
+pub enum CheckCommand {
+    /// Find time entries that overlap each other
+    Overlaps(OverlapsArgs),
+    /// Find untracked time during working hours
+    Gaps(GapsArgs),
+}
+
+#[derive(Args)]
+pub struct OverlapsArgs {
+    #[command(flatten)]
+    range: RangeArgs,
+    /// Offer to trim one of the entries of each overlap
+    #[arg(long)]
+    fix: bool,
+}
+
+#[derive(Args)]
+pub struct GapsArgs {
+    #[command(flatten)]
+    range: RangeArgs,
+    /// Ignore gaps shorter than this, e.g. 5m or 1h
+    #[arg(long, value_name = "INTERVAL", default_value = "5m")]
+    min: Interval,
+    /// Offer to log a time entry in each gap
+    #[arg(long)]
+    fill: bool,
+}
+
+pub fn run_check(config: &Config, command: &CheckCommand) -> Result<()> {
+    match command {
+        CheckCommand::Overlaps(args) => run_overlaps(config, args),
+        CheckCommand::Gaps(args) => run_gaps(config, args),
+    }
+}
+
+fn run_overlaps(config: &Config, args: &OverlapsArgs) -> Result<()> {
+    let client = get_client()?;
+    let (from, to) = args.range.resolve()?;
+    let now = Utc::now();
+    let entries: Vec<_> = get_range_entries(&client, from, to)?
+        .into_iter()
+        .filter(|e| e.start.is_some())
+        .collect();
+    let intervals: Vec<_> = entries.iter().map(|e| interval(e, now)).collect();
+    let overlaps = find_overlaps(&intervals);
+
+    if overlaps.is_empty() {
+        println!(
+            "{}",
+            decorate("✅", &format!("No overlapping entries from {from} to {to}"))
+        );
+        return Ok(());
+    }
+
+    for &(a, b) in &overlaps {
+        let (first, second) = (&entries[a], &entries[b]);
+        let overlap = intervals[a].1.min(intervals[b].1) - intervals[b].0;
+        println!(
+            "{}",
+            decorate(
+                "⚠️ ",
+                &format!(
+                    "{} overlap on {}",
+                    fmt_duration(overlap, config.duration_format),
+                    DateTime::<Local>::from(intervals[b].0).date_naive()
+                )
+            )
+        );
+        println!("    {}", fmt_entry(first));
+        println!("    {}", fmt_entry(second));
+
+        if args.fix {
+            fix_overlap(&client, first, second)?;
+        }
+        println!();
+    }
+    println!("Found {} overlapping pair(s)", overlaps.len());
+
+    Ok(())
+}
+
+fn fix_overlap(client: &tgl_cli::Client, first: &TimeEntry, second: &TimeEntry) -> Result<()> {
+    let (Some(first_stop), Some(second_start)) = (first.stop, second.start) else {
+        // Only a stopped entry can be trimmed at its end.
+        return Ok(());
+    };
+    let items = [
+        format!(
+            "Stop the first entry at {}",
+            DateTime::<Local>::from(second_start).format("%H:%M")
+        ),
+        format!(
+            "Start the second entry at {}",
+            DateTime::<Local>::from(first_stop).format("%H:%M")
+        ),
+        "Skip".to_string(),
+    ];
+    ensure_can_prompt("a fix", Some("Drop --fix to only list overlaps"))?;
+    let choice = dialoguer::Select::with_theme(&dialoguer::theme::ColorfulTheme::default())
+        .with_prompt("How do you want to fix this?")
+        .items(&items)
+        .default(0)
+        .interact_on_opt(&dialoguer::console::Term::stderr())
+        .context("Failed to read selection")?;
+
+    let (entry, update) = match choice {
+        Some(0) => (first, TimeEntryUpdate::new().stop(second_start)),
+        Some(1) if second.stop.is_none_or(|stop| stop > first_stop) => {
+            (second, TimeEntryUpdate::new().start(first_stop))
+        }
+        Some(1) => {
+            println!("The second entry ends before the first one does, so it can't be trimmed");
+            return Ok(());
+        }
+        _ => return Ok(()),
+    };
+    client
+        .update_time_entry(entry, &update)
+        .context("Failed to update time entry")?;
+    journal::forget();
+    clear_prompt_cache();
+
+    Ok(())
+}
+
+fn run_gaps(config: &Config, args: &GapsArgs) -> Result<()> {
+    let client = get_client()?;
+    let (from, to) = args.range.resolve()?;
+    let now = Utc::now();
+    let intervals: Vec<_> = get_range_entries(&client, from, to)?
+        .iter()
+        .filter(|e| e.start.is_some())
+        .map(|e| interval(e, now))
+        .collect();
+
+    let mut count = 0;
+    let mut total = Duration::zero();
+    let mut date = from;
+    while date <= to {
+        if let Some(window) = work_window(&config.work_hours, date, now)? {
+            for (start, stop) in find_gaps(window, &intervals, args.min.duration()) {
+                count += 1;
+                total += stop - start;
+                println!(
+                    "{}",
+                    decorate(
+                        "⚠️ ",
+                        &format!(
+                            "{} untracked on {} from {} to {}",
+                            fmt_duration(stop - start, config.duration_format),
+                            date,
+                            DateTime::<Local>::from(start).format("%H:%M"),
+                            DateTime::<Local>::from(stop).format("%H:%M")
+                        )
+                    )
+                );
+                if args.fill {
+                    fill_gap(&client, start, stop)?;
+                    println!();
+                }
+            }
+        }
+        date = date + Days::new(1);
+    }
+
+    if count == 0 {
+        println!(
+            "{}",
+            decorate(
+                "✅",
+                &format!("No untracked gaps in working hours from {from} to {to}")
+            )
+        );
+    } else {
+        println!(
+            "Found {count} gap(s) totaling {}",
+            config
+                .theme
+                .total
+                .paint(fmt_duration(total, config.duration_format))
+        );
+    }
+
+    Ok(())
+}
+
+fn fill_gap(client: &tgl_cli::Client, start: DateTime<Utc>, stop: DateTime<Utc>) -> Result<()> {
+    ensure_can_prompt("confirmation", Some("Drop --fill to only list gaps"))?;
+    let fill = dialoguer::Confirm::with_theme(&dialoguer::theme::ColorfulTheme::default())
+        .with_prompt("Log a time entry in this gap?")
+        .default(false)
+        .interact_on(&dialoguer::console::Term::stderr())
+        .context("Failed to read confirmation")?;
+    if !fill {
+        return Ok(());
+    }
+
+    let (workspace_id, project_id, description) = prompt_entry_details(client)?;
+    client
+        .add_time_entry(workspace_id, project_id, Some(&description), start, stop)
+        .context("Failed to create time entry")?;
+    journal::forget();
+    clear_prompt_cache();
+
+    Ok(())
+}
+
+/// The working hours on `date`, cut off at `now`, or `None` if `date` isn't
+/// a work day or its working hours haven't started yet.
+fn work_window(
+    hours: &WorkHours,
+    date: NaiveDate,
+    now: DateTime<Utc>,
+) -> Result<Option<(DateTime<Utc>, DateTime<Utc>)>> {
+    if !hours.days.contains(&date.weekday()) {
+        return Ok(None);
+    }
+    let start = at_local_time(date, hours.start)?;
+    let stop = at_local_time(date, hours.end)?.min(now);
+
+    Ok((start < stop).then_some((start, stop)))
+}
+
+/// Finds the parts of `window` not covered by any of `intervals` that last
+/// at least `min`. `intervals` must be sorted by start time.
+fn find_gaps(
+    window: (DateTime<Utc>, DateTime<Utc>),
+    intervals: &[(DateTime<Utc>, DateTime<Utc>)],
+    min: Duration,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let (mut cursor, end) = window;
+    let mut gaps = Vec::new();
+    for &(start, stop) in intervals {
+        if start >= end {
+            break;
+        }
+        if start > cursor && start - cursor >= min {
+            gaps.push((cursor, start));
+        }
+        cursor = cursor.max(stop);
+    }
+    if end > cursor && end - cursor >= min {
+        gaps.push((cursor, end));
+    }
+
+    gaps
+}
+
+/// An entry's interval, treating a running entry as ending `now`.
+pub fn interval(entry: &TimeEntry, now: DateTime<Utc>) -> (DateTime<Utc>, DateTime<Utc>) {
+    let start = entry.start.expect("entry has a start time");
+    (start, entry.stop.unwrap_or(now).max(start))
+}
+
+pub fn fmt_entry(entry: &TimeEntry) -> String {
+    let fmt_time = |t: Option<DateTime<Utc>>| {
+        t.map(|t| DateTime::<Local>::from(t).format("%H:%M").to_string())
+            .unwrap_or_else(|| running_stop().to_string())
+    };
+    format!(
+        "{} - {} [{}] {} (id {})",
+        fmt_time(entry.start),
+        fmt_time(entry.stop),
+        entry.project_name.as_deref().unwrap_or_default(),
+        entry.description.as_deref().unwrap_or_default(),
+        entry.id
+    )
+}
+
+/// Finds the pairs of intervals that overlap. `intervals` must be sorted by
+/// start time; each pair is returned as `(earlier, later)` indices.
+fn find_overlaps(intervals: &[(DateTime<Utc>, DateTime<Utc>)]) -> Vec<(usize, usize)> {
+    let mut overlaps = Vec::new();
+    for (i, a) in intervals.iter().enumerate() {
+        for (j, b) in intervals.iter().enumerate().skip(i + 1) {
+            // Later intervals start even later, so none of them overlap `a`.
+            if b.0 >= a.1 {
+                break;
+            }
+            overlaps.push((i, j));
+        }
+    }
+
+    overlaps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 6, 3, hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn find_overlaps_pairs() {
+        let intervals = [
+            (at(9, 0), at(10, 0)),
+            (at(9, 30), at(9, 45)),
+            (at(9, 50), at(11, 0)),
+            (at(11, 0), at(12, 0)),
+        ];
+
+        assert_eq!(vec![(0, 1), (0, 2)], find_overlaps(&intervals));
+    }
+
+    #[test]
+    fn find_gaps_in_window() {
+        let intervals = [
+            (at(8, 0), at(9, 30)),
+            (at(9, 32), at(10, 0)),
+            (at(9, 40), at(11, 0)),
+            (at(12, 0), at(13, 0)),
+            (at(18, 0), at(19, 0)),
+        ];
+
+        assert_eq!(
+            vec![(at(11, 0), at(12, 0)), (at(13, 0), at(17, 0))],
+            find_gaps((at(9, 0), at(17, 0)), &intervals, Duration::minutes(5))
+        );
+        assert_eq!(
+            vec![(at(9, 0), at(17, 0))],
+            find_gaps((at(9, 0), at(17, 0)), &[], Duration::minutes(5))
+        );
+    }
+}
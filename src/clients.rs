@@ -0,0 +1,126 @@
+//! Commands that manage Toggl clients, i.e. the customers projects are for.
+
+use crate::{config::Config, decorate, find_workspaces, get_client};
+use anyhow::{bail, Context, Result};
+use clap::{Args, Subcommand};
+use tgl_cli::{Client, WorkspaceClient};
+
+#[derive(Subcommand)]
+pub enum ClientCommand {
+    /// List the clients in your workspaces
+    List(ClientListArgs),
+    /// Create a client
+    Create(ClientArgs),
+    /// Archive a client along with its projects
+    Archive(ClientArgs),
+}
+
+#[derive(Args)]
+pub struct ClientListArgs {
+    /// Only list the clients in this workspace
+    #[arg(long)]
+    workspace: Option<String>,
+    /// Include archived clients
+    #[arg(long)]
+    archived: bool,
+}
+
+#[derive(Args)]
+pub struct ClientArgs {
+    /// Name of the client
+    name: String,
+    /// Name of the client's workspace, required if you have several
+    #[arg(long)]
+    workspace: Option<String>,
+}
+
+pub fn run_client(config: &Config, command: &ClientCommand) -> Result<()> {
+    let client = get_client()?;
+    match command {
+        ClientCommand::List(args) => run_client_list(config, &client, args),
+        ClientCommand::Create(args) => {
+            let workspace_id = single_workspace(&client, args.workspace.as_deref())?;
+            let created = client
+                .create_client(workspace_id, &args.name)
+                .context("Failed to create client")?;
+            println!("{}", decorate("✅", &format!("Created {}", created.name)));
+
+            Ok(())
+        }
+        ClientCommand::Archive(args) => {
+            let target = find_client(&client, &args.name, args.workspace.as_deref())?;
+            if target.archived {
+                println!(
+                    "{}",
+                    decorate("🤷", &format!("{} is already archived", target.name))
+                );
+                return Ok(());
+            }
+            client
+                .archive_client(&target)
+                .context("Failed to archive client")?;
+            println!(
+                "{}",
+                decorate("🗄️ ", &format!("Archived {} and its projects", target.name))
+            );
+
+            Ok(())
+        }
+    }
+}
+
+fn run_client_list(config: &Config, client: &Client, args: &ClientListArgs) -> Result<()> {
+    let workspaces = find_workspaces(client, args.workspace.as_deref())?;
+    for workspace in &workspaces {
+        let mut clients = client
+            .get_clients(workspace.id)
+            .context("Failed to retrieve clients")?;
+        clients.retain(|c| args.archived || !c.archived);
+        clients.sort_by_key(|c| c.name.to_lowercase());
+
+        if workspaces.len() > 1 {
+            println!("{}", config.theme.project.paint(&workspace.name));
+        }
+        if clients.is_empty() {
+            println!("{}", decorate("🤷", "No clients found"));
+        }
+        for c in &clients {
+            if c.archived {
+                println!("{} (archived)", c.name);
+            } else {
+                println!("{}", c.name);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Gets the ID of the workspace called `name`, or of your only workspace.
+pub fn single_workspace(client: &Client, name: Option<&str>) -> Result<i64> {
+    match &find_workspaces(client, name)?[..] {
+        [] => bail!("No Toggl workspaces found"),
+        [w] => Ok(w.id),
+        _ => bail!("You have several workspaces, pass --workspace"),
+    }
+}
+
+/// Finds a client by name, ignoring case, across workspaces.
+fn find_client(client: &Client, name: &str, workspace: Option<&str>) -> Result<WorkspaceClient> {
+    let mut found = Vec::new();
+    for w in find_workspaces(client, workspace)? {
+        found.extend(
+            client
+                .get_clients(w.id)
+                .context("Failed to retrieve clients")?
+                .into_iter()
+                .filter(|c| c.name.eq_ignore_ascii_case(name)),
+        );
+    }
+
+    match found.len() {
+        0 => bail!("Client '{name}' not found"),
+        1 => Ok(found.remove(0)),
+        _ => bail!("Client '{name}' exists in several workspaces, pass --workspace"),
+    }
+}
@@ -0,0 +1,341 @@
+//! Client for the [Clockify API](https://docs.clockify.me/), as an
+//! alternative [`TimeTracker`] backend.
+
+use crate::{
+    api::ResponseExt,
+    svc::{Error, Project, Result, TimeEntry, Workspace},
+    tracker::TimeTracker,
+};
+use chrono::{DateTime, SecondsFormat, Utc};
+use reqwest::{
+    blocking::{RequestBuilder, Response},
+    StatusCode,
+};
+use serde::Deserialize;
+use serde_json::json;
+use std::{
+    cell::{OnceCell, RefCell},
+    collections::HashMap,
+};
+
+static BASE_API_URL: &str = "https://api.clockify.me/api/v1";
+
+/// The most items Clockify returns in one page.
+const PAGE_SIZE: &str = "5000";
+
+/// Client for Clockify.
+///
+/// Clockify identifies everything with strings, which are given numeric IDs
+/// as they're seen so that they fit the shared models. The numbers are only
+/// meaningful to the client that handed them out.
+pub struct ClockifyClient {
+    c: reqwest::blocking::Client,
+    api_key: String,
+    get_now: fn() -> DateTime<Utc>,
+    user: OnceCell<User>,
+    ids: RefCell<Vec<String>>,
+    /// Projects by Clockify ID.
+    projects: RefCell<HashMap<String, Project>>,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct User {
+    id: String,
+    active_workspace: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiWorkspace {
+    id: String,
+    name: String,
+    hourly_rate: Option<Rate>,
+}
+
+#[derive(Deserialize)]
+struct Rate {
+    /// In cents.
+    amount: i64,
+    currency: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiProject {
+    id: String,
+    name: String,
+    client_id: Option<String>,
+    color: Option<String>,
+    #[serde(default)]
+    archived: bool,
+    hourly_rate: Option<Rate>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiTimeEntry {
+    id: String,
+    description: Option<String>,
+    project_id: Option<String>,
+    #[serde(default)]
+    billable: bool,
+    workspace_id: String,
+    time_interval: TimeInterval,
+}
+
+#[derive(Deserialize)]
+struct TimeInterval {
+    start: DateTime<Utc>,
+    end: Option<DateTime<Utc>>,
+}
+
+impl ClockifyClient {
+    pub fn new(api_key: String, get_now: fn() -> DateTime<Utc>) -> Result<Self> {
+        Ok(Self {
+            c: reqwest::blocking::Client::builder().build()?,
+            api_key,
+            get_now,
+            user: OnceCell::new(),
+            ids: RefCell::new(Vec::new()),
+            projects: RefCell::new(HashMap::new()),
+        })
+    }
+
+    /// Gets the numeric ID for a Clockify ID, handing out the next one if
+    /// it's new.
+    fn id(&self, clockify_id: &str) -> i64 {
+        let mut ids = self.ids.borrow_mut();
+        let index = match ids.iter().position(|id| id == clockify_id) {
+            Some(index) => index,
+            None => {
+                ids.push(clockify_id.to_string());
+                ids.len() - 1
+            }
+        };
+
+        index as i64 + 1
+    }
+
+    /// Gets the Clockify ID behind a numeric ID from [`ClockifyClient::id`].
+    fn clockify_id(&self, id: i64) -> Result<String> {
+        usize::try_from(id - 1)
+            .ok()
+            .and_then(|index| self.ids.borrow().get(index).cloned())
+            .ok_or(Error::UnknownId(id))
+    }
+
+    fn send(&self, request: RequestBuilder) -> Result<Response> {
+        request.header("X-Api-Key", &self.api_key).send()?.check()
+    }
+
+    fn user(&self) -> Result<&User> {
+        if let Some(user) = self.user.get() {
+            return Ok(user);
+        }
+        let user = self
+            .send(self.c.get(format!("{BASE_API_URL}/user")))?
+            .json()?;
+
+        Ok(self.user.get_or_init(|| user))
+    }
+
+    fn project(&self, workspace_id: &str, project_id: &str) -> Result<Option<Project>> {
+        if let Some(project) = self.projects.borrow().get(project_id) {
+            return Ok(Some(project.clone()));
+        }
+        self.projects(self.id(workspace_id))?;
+
+        Ok(self.projects.borrow().get(project_id).cloned())
+    }
+
+    fn build_time_entry(&self, entry: ApiTimeEntry) -> Result<TimeEntry> {
+        let now = (self.get_now)();
+        let project = match &entry.project_id {
+            Some(project_id) => self.project(&entry.workspace_id, project_id)?,
+            None => None,
+        };
+        let interval = entry.time_interval;
+
+        Ok(TimeEntry {
+            billable: entry.billable,
+            client_id: None,
+            client_name: None,
+            description: entry.description.filter(|d| !d.is_empty()),
+            duration: interval.end.unwrap_or(now) - interval.start,
+            id: self.id(&entry.id),
+            is_running: interval.end.is_none(),
+            project_id: entry.project_id.as_deref().map(|id| self.id(id)),
+            project_color: project.as_ref().and_then(|p| p.color.clone()),
+            project_name: project.map(|p| p.name),
+            start: Some(interval.start),
+            stop: interval.end,
+            tags: Vec::new(),
+            task_id: None,
+            workspace_id: self.id(&entry.workspace_id),
+        })
+    }
+}
+
+impl TimeTracker for ClockifyClient {
+    fn workspaces(&self) -> Result<Vec<Workspace>> {
+        let workspaces: Vec<ApiWorkspace> = self
+            .send(self.c.get(format!("{BASE_API_URL}/workspaces")))?
+            .json()?;
+
+        Ok(workspaces
+            .into_iter()
+            .map(|w| Workspace {
+                default_currency: w.hourly_rate.as_ref().and_then(|r| r.currency.clone()),
+                default_hourly_rate: w.hourly_rate.map(|r| r.amount as f64 / 100.0),
+                id: self.id(&w.id),
+                lock_date: None,
+                name: w.name,
+                rounding: 0,
+                rounding_minutes: 0,
+            })
+            .collect())
+    }
+
+    fn projects(&self, workspace_id: i64) -> Result<Vec<Project>> {
+        let workspace = self.clockify_id(workspace_id)?;
+        let projects: Vec<ApiProject> = self
+            .send(
+                self.c
+                    .get(format!("{BASE_API_URL}/workspaces/{workspace}/projects"))
+                    .query(&[("page-size", PAGE_SIZE)]),
+            )?
+            .json()?;
+
+        let mut cached = self.projects.borrow_mut();
+        Ok(projects
+            .into_iter()
+            .map(|p| {
+                let project = Project {
+                    active: !p.archived,
+                    actual_hours: None,
+                    client_id: p.client_id.as_deref().map(|id| self.id(id)),
+                    color: p.color,
+                    currency: p.hourly_rate.as_ref().and_then(|r| r.currency.clone()),
+                    estimated_hours: None,
+                    id: self.id(&p.id),
+                    name: p.name,
+                    rate: p.hourly_rate.map(|r| r.amount as f64 / 100.0),
+                    workspace_id,
+                };
+                cached.insert(p.id, project.clone());
+                project
+            })
+            .collect())
+    }
+
+    /// Gets the entries in the user's active workspace.
+    fn time_entries(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<TimeEntry>> {
+        let user = self.user()?.clone();
+        let workspace = &user.active_workspace;
+        let fmt = |t: DateTime<Utc>| t.to_rfc3339_opts(SecondsFormat::Secs, true);
+        let entries: Vec<ApiTimeEntry> = self
+            .send(
+                self.c
+                    .get(format!(
+                        "{BASE_API_URL}/workspaces/{workspace}/user/{}/time-entries",
+                        user.id
+                    ))
+                    .query(&[
+                        ("start", fmt(start)),
+                        ("end", fmt(end)),
+                        ("page-size", PAGE_SIZE.to_string()),
+                    ]),
+            )?
+            .json()?;
+
+        entries
+            .into_iter()
+            .map(|e| self.build_time_entry(e))
+            .collect()
+    }
+
+    fn current_entry(&self) -> Result<Option<TimeEntry>> {
+        let user = self.user()?.clone();
+        let entries: Vec<ApiTimeEntry> = self
+            .send(
+                self.c
+                    .get(format!(
+                        "{BASE_API_URL}/workspaces/{}/user/{}/time-entries",
+                        user.active_workspace, user.id
+                    ))
+                    .query(&[("in-progress", "true")]),
+            )?
+            .json()?;
+
+        entries
+            .into_iter()
+            .next()
+            .map(|e| self.build_time_entry(e))
+            .transpose()
+    }
+
+    fn start_entry(
+        &self,
+        workspace_id: i64,
+        project_id: Option<i64>,
+        description: Option<&str>,
+        start: DateTime<Utc>,
+    ) -> Result<TimeEntry> {
+        let workspace = self.clockify_id(workspace_id)?;
+        let project = project_id.map(|id| self.clockify_id(id)).transpose()?;
+        let entry: ApiTimeEntry = self
+            .send(
+                self.c
+                    .post(format!(
+                        "{BASE_API_URL}/workspaces/{workspace}/time-entries"
+                    ))
+                    .json(&json!({
+                        "start": start.to_rfc3339_opts(SecondsFormat::Secs, true),
+                        "description": description,
+                        "projectId": project,
+                    })),
+            )?
+            .json()?;
+
+        self.build_time_entry(entry)
+    }
+
+    fn stop_current_entry(&self) -> Result<Option<TimeEntry>> {
+        let user = self.user()?.clone();
+        let now = (self.get_now)();
+        let response = self
+            .c
+            .patch(format!(
+                "{BASE_API_URL}/workspaces/{}/user/{}/time-entries",
+                user.active_workspace, user.id
+            ))
+            .header("X-Api-Key", &self.api_key)
+            .json(&json!({ "end": now.to_rfc3339_opts(SecondsFormat::Secs, true) }))
+            .send()?;
+        // Clockify answers Not Found when no timer is running.
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let entry = response.parse()?;
+
+        self.build_time_entry(entry).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ids_round_trip() {
+        let client = ClockifyClient::new("key".to_string(), Utc::now).unwrap();
+        let a = client.id("5f1e2d3c4b5a697887766554");
+        let b = client.id("5f1e2d3c4b5a697887766555");
+
+        assert_ne!(a, b);
+        assert_eq!(a, client.id("5f1e2d3c4b5a697887766554"));
+        assert_eq!("5f1e2d3c4b5a697887766555", client.clockify_id(b).unwrap());
+        assert!(client.clockify_id(99).is_err());
+    }
+}
@@ -0,0 +1,539 @@
+//! User configuration, read from `config.toml` in the tgl config directory.
+
+use crate::{
+    rounding::Interval,
+    template::Template,
+    theme::{ColorChoice, Icons, Theme},
+};
+use anyhow::{Context, Result};
+use chrono::{Datelike, Duration, NaiveDate, NaiveTime, Weekday};
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::{collections::BTreeMap, env, fs, io, path::PathBuf};
+use tracing::debug;
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct Config {
+    /// Which time tracking service to use.
+    pub backend: Backend,
+    /// How long to keep workspaces and projects between runs.
+    pub cache: CacheConfig,
+    /// How to reach Clockify when it's the backend.
+    pub clockify: ClockifyConfig,
+    /// Whether to colorize output.
+    pub color: ColorChoice,
+    /// How durations are rendered in status and report output.
+    pub duration_format: DurationFormat,
+    /// How to reach Harvest when it's the backend.
+    pub harvest: HarvestConfig,
+    /// Whether messages are marked with emoji or ASCII stand-ins.
+    pub icons: Icons,
+    /// How much history `status`, `restart`, and `continue` look through.
+    pub history: HistoryConfig,
+    /// Commands to run when a timer starts, stops, or switches.
+    pub hooks: HooksConfig,
+    /// When `tgl daemon` decides you were away from the computer.
+    pub idle: IdleConfig,
+    /// The language of status and report output, such as `de_DE` [default:
+    /// from `LC_ALL`, `LC_MESSAGES`, or `LANG`].
+    pub locale: Option<String>,
+    /// Where `tgl daemon` publishes the timer's state.
+    pub mqtt: MqttConfig,
+    /// How to reach Toggl.
+    pub network: NetworkConfig,
+    /// Which reminders `tgl nudge` gives.
+    pub nudge: NudgeConfig,
+    /// Whether long listings go through a pager.
+    pub pager: PagerConfig,
+    /// Whether status and reports name projects after their clients, as
+    /// `[Client / Project]`.
+    pub show_clients: bool,
+    /// How many hours you aim to log each week, for the forecast in `tgl
+    /// status` and `tgl week`.
+    pub target_hours_per_week: Option<f64>,
+    /// Weekly targets for `tgl targets`, keyed by project name.
+    pub targets: BTreeMap<String, ProjectTarget>,
+    /// Named presets for `tgl start <name>`, keyed by name.
+    pub templates: BTreeMap<String, EntryTemplate>,
+    /// How `tgl status` lays out entries and the summary after them.
+    pub status: StatusConfig,
+    /// Whether to keep a local copy of data for offline reads.
+    pub store: StoreConfig,
+    /// Where `tgl suggest` reads meetings and how it maps them to projects.
+    pub suggest: SuggestConfig,
+    /// Styles for colorized output.
+    pub theme: Theme,
+    /// How times of day are rendered in status output.
+    pub time_format: TimeFormat,
+    /// Whether `tgl daemon` describes entries after the windows you used.
+    pub window: WindowConfig,
+    /// When you're expected to be tracking time.
+    pub work_hours: WorkHours,
+}
+
+impl Config {
+    /// How much time you aim to log each week, if you've set a target.
+    pub fn weekly_target(&self) -> Option<Duration> {
+        self.target_hours_per_week.map(hours)
+    }
+}
+
+/// A preset for new time entries, configured under `[templates.<name>]`.
+/// Workspace and project are given by name and resolved when used.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct EntryTemplate {
+    pub billable: Option<bool>,
+    pub description: Option<String>,
+    pub project: Option<String>,
+    pub tags: Vec<String>,
+    pub workspace: Option<String>,
+}
+
+/// How much time a project should get each week, configured under
+/// `[targets.<project>]`.
+#[derive(Deserialize)]
+pub struct ProjectTarget {
+    pub hours_per_week: f64,
+}
+
+impl ProjectTarget {
+    pub fn duration(&self) -> Duration {
+        hours(self.hours_per_week)
+    }
+}
+
+fn hours(hours: f64) -> Duration {
+    Duration::seconds((hours * 3600.0) as i64)
+}
+
+/// The time tracking service behind tgl.
+#[derive(Deserialize, Default, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    #[default]
+    Toggl,
+    /// Clockify, which supports only `status`, `current`, `start <name>`,
+    /// and `stop`.
+    Clockify,
+    /// Harvest, which supports the same commands as Clockify.
+    Harvest,
+    /// Made-up sample data that lives only as long as one command, as with
+    /// `--demo`.
+    Demo,
+}
+
+impl Backend {
+    pub fn name(self) -> &'static str {
+        match self {
+            Backend::Toggl => "Toggl",
+            Backend::Clockify => "Clockify",
+            Backend::Harvest => "Harvest",
+            Backend::Demo => "the demo",
+        }
+    }
+}
+
+/// Settings for Clockify, configured under `[clockify]`.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct ClockifyConfig {
+    /// API key from your Clockify profile settings. `CLOCKIFY_API_KEY`
+    /// takes precedence.
+    pub api_key: Option<String>,
+}
+
+/// Settings for Harvest, configured under `[harvest]`.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct HarvestConfig {
+    /// ID of the Harvest account to track time in. `HARVEST_ACCOUNT_ID`
+    /// takes precedence.
+    pub account_id: Option<i64>,
+    /// Personal access token from Harvest's developer settings.
+    /// `HARVEST_ACCESS_TOKEN` takes precedence.
+    pub token: Option<String>,
+}
+
+/// Working hours, configured under `[work_hours]`. Used to find untracked
+/// gaps and to judge how much of a day was tracked.
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct WorkHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    pub days: Vec<Weekday>,
+    /// How much time you aim to log on each work day.
+    pub daily_target: Interval,
+    /// Holidays and time off, which have no target.
+    pub days_off: Vec<NaiveDate>,
+    /// A calendar whose all-day events are days off too, as a file path or
+    /// an http(s) URL.
+    pub days_off_ics: Option<String>,
+}
+
+impl WorkHours {
+    /// Whether a date has a target: one of the work days, and not a day off.
+    pub fn is_work_day(&self, date: NaiveDate) -> bool {
+        self.days.contains(&date.weekday()) && !self.days_off.contains(&date)
+    }
+}
+
+impl Default for WorkHours {
+    fn default() -> Self {
+        Self {
+            start: NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(17, 0, 0).unwrap(),
+            days: vec![
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+            ],
+            daily_target: "8h".parse().unwrap(),
+            days_off: Vec::new(),
+            days_off_ics: None,
+        }
+    }
+}
+
+/// Commands run through the shell when tgl starts, stops, or switches a
+/// timer, configured under `[hooks]`. Each gets the entry's details as
+/// `TGL_*` environment variables.
+#[derive(Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct HooksConfig {
+    pub on_start: Option<String>,
+    pub on_stop: Option<String>,
+    /// Runs when one entry is stopped for another, as by `tgl switch`.
+    /// Without it, `on_stop` and then `on_start` run instead.
+    pub on_switch: Option<String>,
+}
+
+/// Where `tgl daemon` publishes the timer's state, configured under
+/// `[mqtt]`. Nothing is published without a broker.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct MqttConfig {
+    /// The broker as `host:port`, or just `host` for port 1883.
+    pub broker: Option<String>,
+    pub topic: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        Self {
+            broker: None,
+            topic: "tgl/timer".to_string(),
+            username: None,
+            password: None,
+        }
+    }
+}
+
+/// Reminders for `tgl nudge`, configured under `[nudge]`. Each one can be
+/// turned off on its own.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct NudgeConfig {
+    /// Remind when no timer runs during work hours.
+    pub no_timer: bool,
+    /// Remind when the running entry has gone on for longer than
+    /// `long_entry_after`.
+    pub long_entry: bool,
+    pub long_entry_after: Interval,
+    /// Remind once the day's logged time passes the daily target.
+    pub past_target: bool,
+}
+
+impl Default for NudgeConfig {
+    fn default() -> Self {
+        Self {
+            no_timer: true,
+            long_entry: true,
+            long_entry_after: "4h".parse().unwrap(),
+            past_target: true,
+        }
+    }
+}
+
+/// Settings for the workspace and project cache, configured under `[cache]`.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct CacheConfig {
+    /// How long cached workspaces and projects are used before refetching.
+    pub ttl: Interval,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            ttl: "15m".parse().unwrap(),
+        }
+    }
+}
+
+/// Settings for fetching recent entries, configured under `[history]`.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct HistoryConfig {
+    /// How many days back to fetch entries. Toggl picks when unset.
+    pub days: Option<u32>,
+    /// The most entries to fetch, newest first.
+    pub limit: Option<usize>,
+}
+
+/// Idle detection in `tgl daemon`, configured under `[idle]`. Nothing is
+/// detected without `after`.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct IdleConfig {
+    /// How long without keyboard or mouse input counts as being away.
+    pub after: Option<Interval>,
+}
+
+/// Describing entries after the windows you used, configured under
+/// `[window]`. Off unless `capture` is set, since window titles can be
+/// private.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct WindowConfig {
+    /// Have `tgl daemon` note the focused window on each poll, and describe
+    /// an entry that stops without a description after the one used most.
+    pub capture: bool,
+}
+
+/// Layouts for `tgl status`, configured under `[status]` with the
+/// placeholders of `--format`.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct StatusConfig {
+    /// Replaces the default layout of each entry.
+    pub line: Option<Template>,
+    /// Replaces the summary after the entries. `{duration}` is the total, and
+    /// `{start}` and `{stop}` span the entries.
+    pub footer: Option<Template>,
+    /// Merges consecutive entries for the same work, as `--collapse` does.
+    pub collapse: bool,
+}
+
+/// Settings for talking to Toggl, configured under `[network]`.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct NetworkConfig {
+    /// How long to wait for a connection.
+    pub connect_timeout: Interval,
+    /// How long to wait for each request as a whole.
+    pub timeout: Interval,
+    /// Proxy URL to use instead of the one in `HTTPS_PROXY`.
+    pub proxy: Option<String>,
+    /// Path to a PEM file with an extra root certificate to trust.
+    pub ca_cert: Option<PathBuf>,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout: "10s".parse().unwrap(),
+            timeout: "30s".parse().unwrap(),
+            proxy: None,
+            ca_cert: None,
+        }
+    }
+}
+
+/// Settings for paging long listings, configured under `[pager]`.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct PagerConfig {
+    /// Page `report` and multi-day `status` output through `$PAGER` when
+    /// it doesn't fit the terminal.
+    pub enabled: bool,
+}
+
+impl Default for PagerConfig {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+/// Settings for the local store, configured under `[store]`.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct StoreConfig {
+    /// Mirror fetched data to SQLite so that `status`, `week`, and `report`
+    /// work when Toggl can't be reached.
+    pub enabled: bool,
+}
+
+/// Settings for `tgl suggest`, configured under `[suggest]`.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct SuggestConfig {
+    /// The calendar to read, as a file path or an http(s) URL.
+    pub ics: Option<String>,
+    /// Rules tried in order; the first whose pattern matches an event's
+    /// title picks the template for its entry.
+    pub rules: Vec<SuggestRule>,
+}
+
+/// Maps calendar events to a template, configured as `[[suggest.rules]]`.
+#[derive(Deserialize)]
+pub struct SuggestRule {
+    /// A regular expression matched against event titles.
+    pub pattern: String,
+    /// The name of a template under `[templates]`.
+    pub template: String,
+}
+
+#[derive(Deserialize, ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum DurationFormat {
+    /// Hours, minutes, and seconds, e.g. `1:45:00`
+    #[default]
+    Clock,
+    /// Hours and minutes, e.g. `1:45`
+    Compact,
+    /// Hours and minutes with units, e.g. `1h 45m`
+    HoursMinutes,
+    /// Decimal hours, e.g. `1.75h`
+    Decimal,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TimeFormat {
+    /// A 24-hour clock, e.g. `13:45`
+    #[default]
+    #[serde(rename = "24h")]
+    TwentyFourHour,
+    /// A 12-hour clock, e.g. `01:45 PM`
+    #[serde(rename = "12h")]
+    TwelveHour,
+}
+
+impl TimeFormat {
+    /// The chrono format for a time of day.
+    pub fn pattern(self) -> &'static str {
+        match self {
+            TimeFormat::TwentyFourHour => "%H:%M",
+            TimeFormat::TwelveHour => "%I:%M %p",
+        }
+    }
+}
+
+/// Returns the path of the config file. `TGL_CONFIG` overrides the default
+/// location in the platform's config directory.
+pub fn path() -> Option<PathBuf> {
+    match env::var_os("TGL_CONFIG") {
+        Some(path) if !path.is_empty() => Some(path.into()),
+        _ => dirs::config_dir().map(|dir| dir.join("tgl").join("config.toml")),
+    }
+}
+
+/// Loads the config file, falling back to defaults if it doesn't exist.
+pub fn load() -> Result<Config> {
+    let Some(path) = path() else {
+        return Ok(Config::default());
+    };
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Config::default()),
+        Err(err) => return Err(err).with_context(|| format!("Failed to read {}", path.display())),
+    };
+
+    debug!(path = %path.display(), "loading config");
+    toml::from_str(&contents).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_config() {
+        let config: Config = toml::from_str(
+            r#"
+            duration_format = "decimal"
+            time_format = "12h"
+            backend = "clockify"
+            show_clients = true
+            target_hours_per_week = 32
+
+            [work_hours]
+            start = "08:30"
+            daily_target = "450m"
+            days = ["mon", "tue", "thu"]
+            days_off = ["2024-12-24"]
+
+            [targets.Website]
+            hours_per_week = 12.5
+
+            [templates.standup]
+            project = "Internal"
+            tags = ["meeting"]
+
+            [cache]
+            ttl = "1h"
+
+            [history]
+            days = 30
+
+            [status]
+            line = "{index}. {start}-{stop} {project}"
+
+            [harvest]
+            account_id = 123456
+
+            [[suggest.rules]]
+            pattern = "(?i)standup"
+            template = "standup"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(DurationFormat::Decimal, config.duration_format);
+        assert_eq!(TimeFormat::TwelveHour, config.time_format);
+        assert_eq!(Backend::Clockify, config.backend);
+        assert!(config.show_clients);
+        assert_eq!(None, config.clockify.api_key);
+        assert_eq!(Some(123456), config.harvest.account_id);
+        assert_eq!(ColorChoice::Auto, config.color);
+        assert!(config.pager.enabled);
+        assert_eq!(Some(30), config.history.days);
+        assert_eq!(None, config.history.limit);
+        assert!(config.status.line.is_some());
+        assert!(config.status.footer.is_none());
+        assert_eq!(Some(Duration::hours(32)), config.weekly_target());
+        assert_eq!(Duration::minutes(750), config.targets["Website"].duration());
+        let standup = &config.templates["standup"];
+        assert_eq!(Some("Internal"), standup.project.as_deref());
+        assert_eq!(vec!["meeting".to_string()], standup.tags);
+        assert_eq!(None, standup.billable);
+        assert_eq!(
+            NaiveTime::from_hms_opt(8, 30, 0),
+            Some(config.work_hours.start)
+        );
+        assert_eq!(
+            NaiveTime::from_hms_opt(17, 0, 0),
+            Some(config.work_hours.end)
+        );
+        assert_eq!(
+            vec![Weekday::Mon, Weekday::Tue, Weekday::Thu],
+            config.work_hours.days
+        );
+        let date = |d| NaiveDate::from_ymd_opt(2024, 12, d).unwrap();
+        assert!(!config.work_hours.is_work_day(date(24)));
+        assert!(config.work_hours.is_work_day(date(23)));
+        assert!(!config.work_hours.is_work_day(date(25)));
+        assert_eq!(
+            chrono::Duration::minutes(450),
+            config.work_hours.daily_target.duration()
+        );
+        assert_eq!(chrono::Duration::hours(1), config.cache.ttl.duration());
+        assert_eq!("standup", config.suggest.rules[0].template);
+    }
+}
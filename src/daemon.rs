@@ -0,0 +1,445 @@
+//! `tgl daemon`: one long-running loop that polls Toggl, so that `tgl prompt`
+//! and `tgl current` can read the running entry from a local socket instead
+//! of the network, that gives the `[nudge]` reminders as they come up, and
+//! that can serve the HTTP API in [`http_api`], receive the Toggl webhooks
+//! in [`webhook`], notice when you were [`idle`] with a timer running, and
+//! describe entries after the [`window`]s you used.
+
+use crate::{
+    cache_path, clear_prompt_cache,
+    config::Config,
+    decorate, forget_prompt_cache, get_client, get_todays_entries, hooks, http_api, icon,
+    idle::{self, Away},
+    journal, mqtt,
+    nudge::{fmt_nudge, nudges, Nudge},
+    profile, resolve_template, restart_entry,
+    rounding::Interval,
+    service::{run_service, ServiceCommand},
+    sort_newest_first, stop_timer,
+    targets::target_work_hours,
+    webhook::{self, run_webhook, WebhookCommand},
+    window, NewEntry,
+};
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Local, Utc};
+use clap::{Args, Subcommand};
+use serde::{Deserialize, Serialize};
+use std::{mem::Discriminant, path::PathBuf, sync::mpsc};
+use tgl_cli::{Client, TimeEntry};
+
+#[derive(Args)]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct DaemonArgs {
+    #[command(subcommand)]
+    command: Option<DaemonCommand>,
+    /// How often to poll Toggl, e.g. 30s or 2m
+    #[arg(long, default_value = "30s")]
+    interval: Interval,
+    /// Also serve an HTTP API on this port of localhost
+    #[arg(long)]
+    port: Option<u16>,
+    /// Also receive Toggl webhooks on this port of localhost
+    #[arg(long, value_name = "PORT")]
+    webhook_port: Option<u16>,
+}
+
+#[derive(Subcommand)]
+enum DaemonCommand {
+    #[command(flatten)]
+    Service(ServiceCommand),
+    /// Manage the Toggl webhooks that tell the daemon about changes made
+    /// elsewhere
+    #[command(subcommand)]
+    Webhook(WebhookCommand),
+}
+
+/// What the daemon last saw, as sent over the socket.
+#[derive(Serialize, Deserialize)]
+struct State {
+    fetched_at: DateTime<Utc>,
+    /// Seconds between polls. Readers ignore state more than two polls old,
+    /// in case the daemon is stuck.
+    interval_secs: i64,
+    running: Option<TimeEntry>,
+}
+
+impl State {
+    fn is_fresh(&self, now: DateTime<Utc>) -> bool {
+        (now - self.fetched_at).num_seconds() < 2 * self.interval_secs
+    }
+
+    /// The running entry, with its duration brought up to `now`.
+    fn running_at(&self, now: DateTime<Utc>) -> Option<TimeEntry> {
+        let mut running = self.running.clone();
+        if let Some(entry) = &mut running {
+            if let Some(start) = entry.start {
+                entry.duration = now - start;
+            }
+        }
+
+        running
+    }
+}
+
+/// What wakes the polling loop before its interval is up.
+enum Wake {
+    /// The timer changed, here or elsewhere, so poll again.
+    Refresh,
+    /// Carry out a request from the HTTP API and send back the result.
+    Act(
+        http_api::Action,
+        mpsc::Sender<Result<Option<TimeEntry>, String>>,
+    ),
+}
+
+/// Each profile's daemon has its own socket, since it polls with that
+/// profile's token.
+fn socket_path() -> Option<PathBuf> {
+    cache_path(&format!("daemon-{}.sock", profile()))
+}
+
+/// Gets the running entry from `tgl daemon`, or `None` if no daemon answers
+/// or what it has is out of date.
+pub fn read_current() -> Option<Option<TimeEntry>> {
+    let state: Option<State> = serde_json::from_slice(&request("state")?).ok()?;
+    let now = Utc::now();
+
+    Some(state.filter(|s| s.is_fresh(now))?.running_at(now))
+}
+
+/// Whether a daemon answers on this profile's socket.
+pub fn is_running() -> bool {
+    request("state").is_some()
+}
+
+/// Asks a running daemon to poll again now, since the timer just changed.
+pub fn refresh() {
+    let _ = request("refresh");
+}
+
+/// Gets the time the daemon last saw you away with a timer running, unless
+/// it's been dealt with.
+pub fn read_away() -> Option<Away> {
+    serde_json::from_slice(&request("away")?).ok()?
+}
+
+/// Tells the daemon that the time you were away has been dealt with.
+pub fn forget_away() {
+    let _ = request("away-done");
+}
+
+/// Finds the nudges that weren't there on the last poll, so that each one is
+/// given once rather than on every poll.
+fn new_nudges<'a>(nudges: &'a [Nudge], shown: &[Discriminant<Nudge>]) -> Vec<&'a Nudge> {
+    nudges
+        .iter()
+        .filter(|n| !shown.contains(&std::mem::discriminant(*n)))
+        .collect()
+}
+
+pub fn run_daemon(config: &Config, args: &DaemonArgs) -> Result<()> {
+    match &args.command {
+        Some(DaemonCommand::Service(command)) => run_service(command),
+        Some(DaemonCommand::Webhook(command)) => run_webhook(command),
+        None => serve(config, args),
+    }
+}
+
+/// Carries out a request from the HTTP API, returning the running entry
+/// after it.
+fn act(config: &Config, client: &Client, action: http_api::Action) -> Result<Option<TimeEntry>> {
+    match action {
+        http_api::Action::Toggle => {
+            if stop_timer(client)?.is_some() {
+                return Ok(None);
+            }
+            let mut entries = client
+                .get_latest_entries()
+                .context("Failed to retrieve latest time entries")?;
+            sort_newest_first(&mut entries);
+            let latest = entries
+                .first()
+                .ok_or_else(|| anyhow!("No recent entries to restart"))?;
+
+            Ok(Some(restart_entry(client, latest)?))
+        }
+        http_api::Action::Start {
+            template,
+            description,
+        } => {
+            let mut entry = resolve_template(client, config, &template)?;
+            if let (NewEntry::Manual { description: d, .. }, Some(description)) =
+                (&mut entry, description)
+            {
+                *d = description;
+            }
+            let started = entry.start_at(client, Utc::now())?;
+            let actions = vec![journal::Action::Started {
+                entry: started.clone(),
+            }];
+            hooks::run(&actions);
+            journal::record(actions);
+            clear_prompt_cache();
+
+            Ok(Some(started))
+        }
+    }
+}
+
+#[cfg(unix)]
+fn serve(config: &Config, args: &DaemonArgs) -> Result<()> {
+    use std::{
+        fs,
+        net::TcpListener,
+        os::unix::net::{UnixListener, UnixStream},
+        sync::{mpsc::RecvTimeoutError, Arc, Mutex},
+        thread,
+    };
+
+    let path = socket_path().ok_or_else(|| anyhow!("Failed to find a cache directory"))?;
+    if UnixStream::connect(&path).is_ok() {
+        bail!("tgl daemon is already running for this profile");
+    }
+    // A daemon that was killed leaves its socket behind.
+    let _ = fs::remove_file(&path);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).context("Failed to create the cache directory")?;
+    }
+    let listener = UnixListener::bind(&path)
+        .with_context(|| format!("Failed to listen on {}", path.display()))?;
+    // Only this machine may control the timer. Webhooks get a port of their
+    // own, so that a tunnel for them doesn't expose the HTTP API too.
+    let bind = |port: Option<u16>| {
+        port.map(|port| {
+            TcpListener::bind(("127.0.0.1", port))
+                .with_context(|| format!("Failed to listen on port {port}"))
+        })
+        .transpose()
+    };
+    let http = bind(args.port)?;
+    let webhooks = bind(args.webhook_port)?;
+    let client = get_client()?;
+    let work_hours = target_work_hours(config);
+    let interval = args.interval.duration();
+
+    let mut watcher = idle::Watcher::new(&config.idle);
+    if watcher.is_some() && idle::idle_time().is_none() {
+        eprintln!(
+            "{} Couldn't read how long you've been idle, so [idle] does nothing. On X11, install xprintidle.",
+            icon("⚠️")
+        );
+    }
+
+    let state = Arc::new(Mutex::new(None::<State>));
+    let away = Arc::new(Mutex::new(None::<Away>));
+    let (wake, wakes) = mpsc::channel();
+    thread::spawn({
+        let (state, away, wake) = (Arc::clone(&state), Arc::clone(&away), wake.clone());
+        move || {
+            for stream in listener.incoming().flatten() {
+                let _ = answer(stream, &state, &away, &wake);
+            }
+        }
+    });
+    if let Some(webhooks) = webhooks {
+        if !webhook::has_secrets() {
+            eprintln!(
+                "{} No webhook secrets saved, so events will be turned away. Subscribe with tgl daemon webhook add",
+                icon("⚠️")
+            );
+        }
+        let (state, wake) = (Arc::clone(&state), wake.clone());
+        thread::spawn(move || {
+            // The timer changed elsewhere, so nothing cached is current.
+            webhook::serve(webhooks, move || {
+                *state.lock().unwrap() = None;
+                forget_prompt_cache();
+                let _ = wake.send(Wake::Refresh);
+            });
+        });
+    }
+    if let Some(http) = http {
+        let state = Arc::clone(&state);
+        thread::spawn(move || {
+            let current = || {
+                let state = state.lock().unwrap();
+                state.as_ref().map(|s| s.running_at(Utc::now()))
+            };
+            let call = |action| {
+                let (reply, replies) = mpsc::channel();
+                wake.send(Wake::Act(action, reply)).ok()?;
+                replies.recv().ok()
+            };
+            http_api::serve(http, current, call);
+        });
+    }
+
+    let mut publisher = mqtt::Publisher::new(&config.mqtt);
+    let mut windows = config.window.capture.then(window::Tracker::default);
+    let mut shown = Vec::new();
+    loop {
+        match get_todays_entries(&client) {
+            Ok(entries) => {
+                let running = entries.iter().find(|e| e.is_running);
+                *state.lock().unwrap() = Some(State {
+                    fetched_at: Utc::now(),
+                    interval_secs: interval.num_seconds(),
+                    running: running.cloned(),
+                });
+                if let Some(publisher) = &mut publisher {
+                    if let Err(err) = publisher.update(running, Utc::now()) {
+                        eprintln!("{} {err:#}", icon("⚠️"));
+                    }
+                }
+                if let Some(watcher) = &mut watcher {
+                    let mut away = away.lock().unwrap();
+                    // There's nothing left to offer once that entry stops.
+                    if away
+                        .as_ref()
+                        .is_some_and(|a| running.is_none_or(|e| e.id != a.entry_id))
+                    {
+                        *away = None;
+                    }
+                    if let Some((from, to)) =
+                        idle::idle_time().and_then(|idle| watcher.sample(idle, Utc::now()))
+                    {
+                        // An entry started while you were away didn't run
+                        // through it.
+                        if let Some(entry) =
+                            running.filter(|e| e.start.is_some_and(|start| start <= from))
+                        {
+                            println!("{}", fmt_away(config, from, to));
+                            *away = Some(Away {
+                                entry_id: entry.id,
+                                from,
+                                to,
+                            });
+                        }
+                    }
+                }
+                if let Some(windows) = &mut windows {
+                    if let Some((entry, description)) =
+                        windows.sample(&entries, window::focused_window())
+                    {
+                        window::describe_entry(config, &client, entry, &description);
+                    }
+                }
+                let nudges = nudges(
+                    &config.nudge,
+                    &work_hours,
+                    Local::now().naive_local(),
+                    &entries,
+                );
+                for nudge in new_nudges(&nudges, &shown) {
+                    println!("{}", fmt_nudge(config, &work_hours, nudge));
+                }
+                shown = nudges.iter().map(std::mem::discriminant).collect();
+            }
+            // Keep polling through outages; readers fall back to Toggl once
+            // the state is out of date.
+            Err(err) => eprintln!("{} {err:#}", icon("⚠️")),
+        }
+
+        // A command that changed the timer cuts the wait short, and so does
+        // a request to change it.
+        let wait = interval.to_std()?;
+        match wakes.recv_timeout(wait) {
+            Ok(Wake::Act(action, reply)) => {
+                let result = act(config, &client, action).map_err(|err| format!("{err:#}"));
+                let _ = reply.send(result);
+            }
+            Ok(Wake::Refresh) | Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => thread::sleep(wait),
+        }
+    }
+}
+
+fn fmt_away(config: &Config, from: DateTime<Utc>, to: DateTime<Utc>) -> String {
+    let fmt = |time: DateTime<Utc>| {
+        DateTime::<Local>::from(time)
+            .format(config.time_format.pattern())
+            .to_string()
+    };
+
+    decorate(
+        "💤",
+        &format!(
+            "You were away from {} to {} with a timer running. Run `tgl idle` to take that time out of the entry, or to stop it when you left.",
+            fmt(from),
+            fmt(to)
+        ),
+    )
+}
+
+#[cfg(not(unix))]
+fn serve(_config: &Config, _args: &DaemonArgs) -> Result<()> {
+    anyhow::bail!("tgl daemon is only supported on Unix")
+}
+
+/// Answers one request on the socket: `state` for the latest state as JSON,
+/// `refresh` to drop it and poll again, `away` for the time you were last
+/// away as JSON, or `away-done` to forget it.
+#[cfg(unix)]
+fn answer(
+    mut stream: std::os::unix::net::UnixStream,
+    state: &std::sync::Mutex<Option<State>>,
+    away: &std::sync::Mutex<Option<Away>>,
+    wake: &mpsc::Sender<Wake>,
+) -> std::io::Result<()> {
+    use std::io::{BufRead, BufReader};
+
+    stream.set_read_timeout(Some(std::time::Duration::from_secs(1)))?;
+    let mut command = String::new();
+    BufReader::new(&stream).read_line(&mut command)?;
+    match command.trim() {
+        "state" => serde_json::to_writer(&mut stream, &*state.lock().unwrap())?,
+        "refresh" => {
+            *state.lock().unwrap() = None;
+            let _ = wake.send(Wake::Refresh);
+        }
+        "away" => serde_json::to_writer(&mut stream, &*away.lock().unwrap())?,
+        "away-done" => *away.lock().unwrap() = None,
+        _ => {}
+    }
+
+    Ok(())
+}
+
+#[cfg(unix)]
+fn request(command: &str) -> Option<Vec<u8>> {
+    use std::{
+        io::{Read, Write},
+        os::unix::net::UnixStream,
+        time::Duration,
+    };
+
+    let mut stream = UnixStream::connect(socket_path()?).ok()?;
+    // Prompts can't wait on a daemon that's stuck.
+    stream
+        .set_read_timeout(Some(Duration::from_millis(200)))
+        .ok()?;
+    writeln!(stream, "{command}").ok()?;
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).ok()?;
+
+    Some(response)
+}
+
+#[cfg(not(unix))]
+fn request(_command: &str) -> Option<Vec<u8>> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn gives_each_nudge_once() {
+        let shown = [std::mem::discriminant(&Nudge::NoTimer)];
+        let nudges = [Nudge::NoTimer, Nudge::PastTarget(Duration::hours(9))];
+
+        assert_eq!(vec![&nudges[1]], new_nudges(&nudges, &shown));
+    }
+}
@@ -0,0 +1,261 @@
+//! An in-memory [`TimeTracker`] with made-up data, for demos and tests that
+//! can't reach a real service.
+
+use crate::{
+    svc::{Error, Project, Result, TimeEntry, Workspace},
+    tracker::TimeTracker,
+};
+use chrono::{DateTime, Datelike, Days, Duration, Local, NaiveTime, TimeZone, Utc, Weekday};
+use std::cell::RefCell;
+
+const WORKSPACE_ID: i64 = 1;
+const WEBSITE: i64 = 10;
+const INTERNAL: i64 = 11;
+const RESEARCH: i64 = 12;
+
+/// A fake time tracker seeded with a week of plausible entries, ending in
+/// one that's running now. Changes last only as long as the tracker.
+///
+/// ```
+/// use chrono::Utc;
+/// use tgl_cli::{DemoTracker, TimeTracker};
+///
+/// let tracker = DemoTracker::new(Utc::now);
+/// let running = tracker.current_entry()?.expect("the demo has a running entry");
+/// let stopped = tracker.stop_current_entry()?.unwrap();
+///
+/// assert_eq!(running.id, stopped.id);
+/// assert!(tracker.current_entry()?.is_none());
+/// # Ok::<(), tgl_cli::Error>(())
+/// ```
+pub struct DemoTracker {
+    get_now: fn() -> DateTime<Utc>,
+    projects: Vec<Project>,
+    entries: RefCell<Vec<TimeEntry>>,
+}
+
+impl DemoTracker {
+    pub fn new(get_now: fn() -> DateTime<Utc>) -> Self {
+        let projects = [
+            (WEBSITE, "Website", "#0b83d9", Some(120.0)),
+            (INTERNAL, "Internal", "#9e5bd9", None),
+            (RESEARCH, "Research", "#e36a00", Some(90.0)),
+        ]
+        .into_iter()
+        .map(|(id, name, color, rate)| Project {
+            active: true,
+            actual_hours: None,
+            client_id: None,
+            color: Some(color.to_string()),
+            currency: rate.map(|_| "USD".to_string()),
+            estimated_hours: None,
+            id,
+            name: name.to_string(),
+            rate,
+            workspace_id: WORKSPACE_ID,
+        })
+        .collect();
+        let tracker = Self {
+            get_now,
+            projects,
+            entries: RefCell::new(Vec::new()),
+        };
+        tracker.seed();
+
+        tracker
+    }
+
+    fn now(&self) -> DateTime<Utc> {
+        (self.get_now)()
+    }
+
+    fn push(
+        &self,
+        project_id: Option<i64>,
+        description: Option<&str>,
+        start: DateTime<Utc>,
+        stop: Option<DateTime<Utc>>,
+    ) -> TimeEntry {
+        let mut entries = self.entries.borrow_mut();
+        let project = self.projects.iter().find(|p| Some(p.id) == project_id);
+        let entry = TimeEntry {
+            billable: project.is_some_and(|p| p.rate.is_some()),
+            client_id: None,
+            client_name: None,
+            description: description.map(str::to_string),
+            duration: stop.unwrap_or_else(|| self.now()) - start,
+            id: 1000 + entries.len() as i64,
+            is_running: stop.is_none(),
+            project_id,
+            project_color: project.and_then(|p| p.color.clone()),
+            project_name: project.map(|p| p.name.clone()),
+            start: Some(start),
+            stop,
+            tags: Vec::new(),
+            task_id: None,
+            workspace_id: WORKSPACE_ID,
+        };
+        entries.push(entry.clone());
+
+        entry
+    }
+
+    /// Fills in the past week of work days, then today up to now.
+    fn seed(&self) {
+        const DAY: [(i64, i64, Option<i64>, &str); 5] = [
+            (0, 120, Some(WEBSITE), "Fix login bug"),
+            (120, 15, Some(INTERNAL), "Standup"),
+            (150, 90, Some(RESEARCH), "Competitor review"),
+            (300, 150, Some(WEBSITE), "Landing page copy"),
+            (460, 20, None, "Email"),
+        ];
+        let now = self.now();
+        let today = now.with_timezone(&Local).date_naive();
+        for back in (1..=7).rev() {
+            let date = today - Days::new(back);
+            if matches!(date.weekday(), Weekday::Sat | Weekday::Sun) {
+                continue;
+            }
+            let Some(nine) = Local
+                .from_local_datetime(&date.and_time(NaiveTime::MIN))
+                .earliest()
+                .map(|t| t.to_utc() + Duration::hours(9))
+            else {
+                continue;
+            };
+            // Vary the days a little so that charts aren't flat.
+            let stretch = (back as i64 % 3) * 10;
+            for (offset, minutes, project_id, description) in DAY {
+                let start = nine + Duration::minutes(offset + stretch);
+                let stop = start + Duration::minutes(minutes + stretch);
+                self.push(project_id, Some(description), start, Some(stop));
+            }
+        }
+
+        let at = |minutes_ago| now - Duration::minutes(minutes_ago);
+        self.push(
+            Some(WEBSITE),
+            Some("Landing page copy"),
+            at(150),
+            Some(at(105)),
+        );
+        self.push(Some(INTERNAL), Some("Standup"), at(100), Some(at(85)));
+        self.push(Some(WEBSITE), Some("Fix login bug"), at(80), Some(at(25)));
+        self.push(Some(RESEARCH), Some("Competitor review"), at(25), None);
+    }
+
+    /// Brings a running entry's duration up to now.
+    fn refreshed(&self, mut entry: TimeEntry) -> TimeEntry {
+        if let (true, Some(start)) = (entry.is_running, entry.start) {
+            entry.duration = self.now() - start;
+        }
+
+        entry
+    }
+}
+
+impl TimeTracker for DemoTracker {
+    fn workspaces(&self) -> Result<Vec<Workspace>> {
+        Ok(vec![Workspace {
+            default_currency: Some("USD".to_string()),
+            default_hourly_rate: None,
+            id: WORKSPACE_ID,
+            lock_date: None,
+            name: "Demo".to_string(),
+            rounding: 0,
+            rounding_minutes: 0,
+        }])
+    }
+
+    fn projects(&self, workspace_id: i64) -> Result<Vec<Project>> {
+        if workspace_id != WORKSPACE_ID {
+            return Err(Error::UnknownId(workspace_id));
+        }
+
+        Ok(self.projects.clone())
+    }
+
+    fn time_entries(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<TimeEntry>> {
+        let mut entries: Vec<_> = self
+            .entries
+            .borrow()
+            .iter()
+            .filter(|e| e.start.is_some_and(|s| s >= start && s < end))
+            .map(|e| self.refreshed(e.clone()))
+            .collect();
+        entries.sort_unstable_by_key(|e| std::cmp::Reverse(e.start));
+
+        Ok(entries)
+    }
+
+    fn current_entry(&self) -> Result<Option<TimeEntry>> {
+        Ok(self
+            .entries
+            .borrow()
+            .iter()
+            .find(|e| e.is_running)
+            .map(|e| self.refreshed(e.clone())))
+    }
+
+    /// Starts an entry, stopping the running one as Toggl does.
+    fn start_entry(
+        &self,
+        workspace_id: i64,
+        project_id: Option<i64>,
+        description: Option<&str>,
+        start: DateTime<Utc>,
+    ) -> Result<TimeEntry> {
+        if workspace_id != WORKSPACE_ID {
+            return Err(Error::UnknownId(workspace_id));
+        }
+        if let Some(project_id) = project_id.filter(|id| !self.projects.iter().any(|p| p.id == *id))
+        {
+            return Err(Error::UnknownId(project_id));
+        }
+        self.stop_current_entry()?;
+
+        Ok(self.push(project_id, description, start, None))
+    }
+
+    fn stop_current_entry(&self) -> Result<Option<TimeEntry>> {
+        let now = self.now();
+        let mut entries = self.entries.borrow_mut();
+        let Some(entry) = entries.iter_mut().find(|e| e.is_running) else {
+            return Ok(None);
+        };
+        entry.is_running = false;
+        entry.stop = Some(now);
+        if let Some(start) = entry.start {
+            entry.duration = now - start;
+        }
+
+        Ok(Some(entry.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn start_stops_the_running_entry() {
+        let tracker = DemoTracker::new(Utc::now);
+        let running = tracker.current_entry().unwrap().unwrap();
+        let started = tracker
+            .start_entry(WORKSPACE_ID, Some(WEBSITE), Some("Deploy"), Utc::now())
+            .unwrap();
+
+        let today = tracker
+            .time_entries(
+                Utc::now() - Duration::hours(3),
+                Utc::now() + Duration::hours(1),
+            )
+            .unwrap();
+        assert_eq!(started.id, today[0].id);
+        assert!(today.iter().any(|e| e.id == running.id && !e.is_running));
+        assert_eq!(1, today.iter().filter(|e| e.is_running).count());
+        assert!(tracker
+            .start_entry(WORKSPACE_ID, Some(99), None, Utc::now())
+            .is_err());
+    }
+}
@@ -0,0 +1,54 @@
+//! Rendering durations in the style chosen with `duration_format`, for
+//! status, reports, and the shell prompt alike.
+
+use crate::config::DurationFormat;
+use chrono::Duration;
+
+pub fn fmt_duration(dur: Duration, format: DurationFormat) -> String {
+    let (hours, minutes, seconds) = get_duration_parts(dur);
+    match format {
+        DurationFormat::Clock => format!("{hours}:{minutes:02}:{seconds:02}"),
+        DurationFormat::Compact => format!("{hours}:{minutes:02}"),
+        DurationFormat::HoursMinutes => format!("{hours}h {minutes}m"),
+        DurationFormat::Decimal => format!("{}h", fmt_duration_decimal(dur)),
+    }
+}
+
+/// Formats a duration with an explicit `+` or `-` sign.
+pub fn fmt_signed(dur: Duration, format: DurationFormat) -> String {
+    if dur < Duration::zero() {
+        format!("-{}", fmt_duration(-dur, format))
+    } else {
+        format!("+{}", fmt_duration(dur, format))
+    }
+}
+
+pub fn fmt_duration_decimal(dur: Duration) -> String {
+    format!("{:.2}", dur.num_seconds() as f64 / 3600.0)
+}
+
+fn get_duration_parts(dur: Duration) -> (i64, i64, i64) {
+    let minutes = (dur - Duration::hours(dur.num_hours())).num_minutes();
+    let seconds = (dur - Duration::minutes(dur.num_minutes())).num_seconds();
+
+    (dur.num_hours(), minutes, seconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_each_style() {
+        let dur = Duration::seconds(6330);
+
+        assert_eq!("1:45:30", fmt_duration(dur, DurationFormat::Clock));
+        assert_eq!("1:45", fmt_duration(dur, DurationFormat::Compact));
+        assert_eq!("1h 45m", fmt_duration(dur, DurationFormat::HoursMinutes));
+        assert_eq!("1.76h", fmt_duration(dur, DurationFormat::Decimal));
+        assert_eq!(
+            "-0h 5m",
+            fmt_signed(-Duration::minutes(5), DurationFormat::HoursMinutes)
+        );
+    }
+}
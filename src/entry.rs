@@ -0,0 +1,586 @@
+//! Commands that act on existing time entries, one at a time.
+
+use crate::{
+    clear_prompt_cache,
+    config::Config,
+    decorate, ensure_can_prompt, ensure_unlocked, ensure_unlocked_at, get_client,
+    get_todays_entries, icon, is_quiet,
+    journal::{self, Action},
+    local_midnight, println_entry, report_change,
+    rounding::Interval,
+    run_status, select_entry,
+    when::parse_date,
+    StatusArgs,
+};
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Days, Local, NaiveDate, NaiveTime, TimeZone, Utc};
+use clap::{ArgGroup, Args};
+use tgl_cli::{Client, TimeEntry, TimeEntryUpdate};
+
+/// Identifies an entry by its index in `tgl status` or by its Toggl ID.
+#[derive(Args)]
+pub struct EntryArgs {
+    /// Index of the entry as shown by `tgl status`
+    #[arg(conflicts_with = "id")]
+    index: Option<usize>,
+    /// Toggl ID of the entry, as shown by `tgl status --ids`
+    #[arg(long)]
+    id: Option<i64>,
+}
+
+impl EntryArgs {
+    /// Looks up the referenced entry, or returns `None` if neither an index
+    /// nor an ID was given.
+    pub fn resolve(&self, client: &Client) -> Result<Option<TimeEntry>> {
+        if let Some(id) = self.id {
+            let entry = client
+                .get_time_entry(id)
+                .with_context(|| format!("Failed to retrieve time entry {id}"))?;
+            return Ok(Some(entry));
+        }
+
+        match self.index {
+            Some(index) => {
+                let mut entries = get_todays_entries(client)?;
+                if index == 0 || index > entries.len() {
+                    bail!(
+                        "There's no entry {index} today, expected 1 to {}",
+                        entries.len()
+                    );
+                }
+                Ok(Some(entries.swap_remove(index - 1)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Looks up the referenced entry, asking the user to pick one of
+    /// today's entries if none was given.
+    fn resolve_or_select(&self, client: &Client, prompt: &str) -> Result<TimeEntry> {
+        if let Some(entry) = self.resolve(client)? {
+            return Ok(entry);
+        }
+
+        let mut entries = get_todays_entries(client)?;
+        entries.reverse();
+
+        Ok(select_entry(&entries, prompt)?.clone())
+    }
+}
+
+#[derive(Args)]
+pub struct EditArgs {
+    #[command(flatten)]
+    entry: EntryArgs,
+    /// New description; skips the interactive prompts
+    #[arg(long)]
+    description: Option<String>,
+    /// Name of the new project, or "" to remove it; skips the interactive
+    /// prompts
+    #[arg(long)]
+    project: Option<String>,
+    /// New start time (HH:MM); skips the interactive prompts
+    #[arg(long)]
+    start: Option<NaiveTime>,
+    /// New stop time (HH:MM); skips the interactive prompts
+    #[arg(long)]
+    stop: Option<NaiveTime>,
+    /// Step through each of a day's entries instead, e.g. yesterday
+    /// [default: today]
+    #[arg(
+        long,
+        value_name = "DATE",
+        value_parser = parse_date,
+        num_args = 0..=1,
+        conflicts_with_all = ["index", "id", "description", "project", "start", "stop"]
+    )]
+    day: Option<Option<NaiveDate>>,
+}
+
+#[derive(Args)]
+pub struct SplitArgs {
+    #[command(flatten)]
+    entry: EntryArgs,
+    /// Time to split the entry at (HH:MM)
+    #[arg(long)]
+    at: NaiveTime,
+    /// Description of the second part [default: the entry's]
+    #[arg(long)]
+    description: Option<String>,
+    /// Name of the second part's project, or "" for none [default: the
+    /// entry's]
+    #[arg(long)]
+    project: Option<String>,
+}
+
+#[derive(Args)]
+#[command(group(ArgGroup::new("when").required(true)))]
+pub struct AdjustArgs {
+    /// New start time (HH:MM), the latest one that isn't in the future
+    #[arg(long, group = "when")]
+    start: Option<NaiveTime>,
+    /// Move the start this much earlier, e.g. 15m
+    #[arg(long, group = "when", value_name = "INTERVAL")]
+    back: Option<Interval>,
+}
+
+#[derive(Args)]
+#[command(group(ArgGroup::new("change").required(true).multiple(true)))]
+pub struct RenameArgs {
+    /// New description
+    #[arg(group = "change")]
+    description: Option<String>,
+    /// Name of the new project, or "" to remove it
+    #[arg(long, group = "change")]
+    project: Option<String>,
+}
+
+#[derive(Args)]
+pub struct DeleteArgs {
+    #[command(flatten)]
+    entry: EntryArgs,
+    /// Don't ask for confirmation
+    #[arg(short, long)]
+    yes: bool,
+}
+
+pub fn run_edit(config: &Config, args: &EditArgs) -> Result<()> {
+    if let Some(day) = args.day {
+        return edit_day(config, day.unwrap_or_else(|| Local::now().date_naive()));
+    }
+    let client = get_client()?;
+    let entry = args
+        .entry
+        .resolve_or_select(&client, "Select an entry to edit")?;
+    ensure_unlocked(&client, [&entry])?;
+    let interactive = args.description.is_none()
+        && args.project.is_none()
+        && args.start.is_none()
+        && args.stop.is_none();
+    let update = if interactive {
+        prompt_update(&client, &entry)?
+    } else {
+        flag_update(&client, &entry, args)?
+    };
+
+    match apply_update(&client, &entry, &update)? {
+        Some(updated) => report_change(config, &client, Some(&updated)),
+        None => {
+            if is_quiet() {
+                println!("Nothing to change");
+            } else {
+                println!("{}", decorate("🤷", "Nothing to change\n"));
+            }
+            report_change(config, &client, None)
+        }
+    }
+}
+
+/// Offers to edit each of a day's entries in turn, oldest first.
+fn edit_day(config: &Config, date: NaiveDate) -> Result<()> {
+    let client = get_client()?;
+    let is_today = date == Local::now().date_naive();
+    let entries = if is_today {
+        get_todays_entries(&client)?
+    } else {
+        let mut entries = client
+            .get_entries_touching(
+                local_midnight(date).into(),
+                local_midnight(date + Days::new(1)).into(),
+            )
+            .context("Failed to retrieve time entries")?;
+        entries.sort_unstable_by_key(|e| e.start);
+        entries
+    };
+    if entries.is_empty() {
+        println!("{}", decorate("🤷", "No entries to edit\n"));
+        return Ok(());
+    }
+    ensure_unlocked(&client, &entries)?;
+    ensure_can_prompt("changes", Some("Edit entries one at a time with --id"))?;
+
+    let term = dialoguer::console::Term::stderr();
+    let mut updated = 0;
+    for (i, entry) in entries.iter().enumerate() {
+        println_entry(config, is_today.then_some(i + 1), entry, false);
+        // Escape ends the pass early, keeping the changes made so far.
+        let Some(edit) = dialoguer::Confirm::new()
+            .with_prompt("Change this entry?")
+            .default(false)
+            .report(false)
+            .interact_on_opt(&term)
+            .context("Failed to read confirmation")?
+        else {
+            break;
+        };
+        if edit && edit_entry(&client, entry)?.is_some() {
+            updated += 1;
+        }
+    }
+
+    if is_quiet() {
+        return Ok(());
+    }
+    match updated {
+        0 => println!("{}", decorate("🤷", "Nothing to change\n")),
+        1 => println!("{}", decorate("✅", "Updated 1 entry\n")),
+        n => println!("{}", decorate("✅", &format!("Updated {n} entries\n"))),
+    }
+    run_status(
+        config,
+        &StatusArgs {
+            date: (!is_today).then_some(date),
+            ..Default::default()
+        },
+    )
+}
+
+/// Prompts for changes to an entry and saves them, returning the updated
+/// entry, or `None` if nothing changed.
+pub fn edit_entry(client: &Client, entry: &TimeEntry) -> Result<Option<TimeEntry>> {
+    ensure_unlocked(client, [entry])?;
+    let update = prompt_update(client, entry)?;
+
+    apply_update(client, entry, &update)
+}
+
+/// Saves `update` to an entry once it checks out, returning the updated entry,
+/// or `None` if there was nothing to change.
+pub fn apply_update(
+    client: &Client,
+    entry: &TimeEntry,
+    update: &TimeEntryUpdate,
+) -> Result<Option<TimeEntry>> {
+    let start = update.start.or(entry.start);
+    let stop = update.stop.or(entry.stop);
+    if matches!((start, stop), (Some(start), Some(stop)) if stop < start) {
+        bail!("The entry can't stop before it starts");
+    }
+    if let Some(start) = update.start {
+        let workspaces = client
+            .get_workspaces()
+            .context("Failed to retrieve workspaces")?;
+        ensure_unlocked_at(&workspaces, entry.workspace_id, start)?;
+    }
+    if update.is_empty() {
+        return Ok(None);
+    }
+
+    let updated = client
+        .update_time_entry(entry, update)
+        .context("Failed to update time entry")?;
+    journal::forget();
+    clear_prompt_cache();
+
+    Ok(Some(updated))
+}
+
+fn flag_update(client: &Client, entry: &TimeEntry, args: &EditArgs) -> Result<TimeEntryUpdate> {
+    let mut update = TimeEntryUpdate::new();
+    if let Some(description) = &args.description {
+        update = update.description(description);
+    }
+    if let Some(project) = &args.project {
+        update = update.project_id(find_project_id(client, entry.workspace_id, project)?);
+    }
+
+    let date = entry_date(entry);
+    let start = args.start.map(|t| at_local_time(date, t)).transpose()?;
+    if let Some(start) = start {
+        update = update.start(start);
+    }
+    if let Some(stop) = args.stop {
+        if entry.is_running {
+            bail!("Can't set the stop time of a running entry, stop it first");
+        }
+        let start = start.or(entry.start).unwrap_or_else(Utc::now);
+        update = update.stop(stop_after(start, stop)?);
+    }
+
+    Ok(update)
+}
+
+/// Looks up a project's ID by name, where "" means no project.
+fn find_project_id(client: &Client, workspace_id: i64, name: &str) -> Result<Option<i64>> {
+    if name.is_empty() {
+        return Ok(None);
+    }
+    let project = client
+        .find_project_by_name(workspace_id, name)
+        .context("Failed to get projects")?
+        .ok_or_else(|| anyhow!("Project '{name}' not found"))?;
+
+    Ok(Some(project.id))
+}
+
+fn prompt_update(client: &Client, entry: &TimeEntry) -> Result<TimeEntryUpdate> {
+    ensure_can_prompt(
+        "changes",
+        Some("Pass them with --description, --project, --start, or --stop"),
+    )?;
+    let theme = dialoguer::theme::ColorfulTheme::default();
+    let term = dialoguer::console::Term::stderr();
+    let mut update = TimeEntryUpdate::new();
+
+    let current_description = entry.description.clone().unwrap_or_default();
+    let description: String = dialoguer::Input::with_theme(&theme)
+        .with_prompt("Description")
+        .with_initial_text(&current_description)
+        .allow_empty(true)
+        .report(!is_quiet())
+        .interact_text_on(&term)
+        .context("Failed to read description input")?;
+    if description != current_description {
+        update = update.description(description);
+    }
+
+    let projects = client
+        .get_projects(entry.workspace_id)
+        .context("Failed to get projects")?;
+    let projects: Vec<_> = projects
+        .iter()
+        .filter(|p| p.active || Some(p.id) == entry.project_id)
+        .collect();
+    let mut project_names = vec!["(no project)".to_string()];
+    project_names.extend(projects.iter().map(|p| p.name.to_string()));
+    let current_idx = entry
+        .project_id
+        .and_then(|id| projects.iter().position(|p| p.id == id))
+        .map_or(0, |i| i + 1);
+    let project_idx = dialoguer::FuzzySelect::with_theme(&theme)
+        .with_prompt("Project")
+        .items(&project_names)
+        .default(current_idx)
+        .report(!is_quiet())
+        .interact_on_opt(&term)
+        .context("Failed to read project selection")?
+        .unwrap_or(current_idx);
+    if project_idx != current_idx {
+        update = update.project_id(project_idx.checked_sub(1).map(|i| projects[i].id));
+    }
+
+    let date = entry_date(entry);
+    let fmt_time = |t: DateTime<Utc>| DateTime::<Local>::from(t).format("%H:%M").to_string();
+    let mut start = entry.start;
+    if let Some(current) = entry.start {
+        let input: String = dialoguer::Input::with_theme(&theme)
+            .with_prompt("Start (HH:MM)")
+            .with_initial_text(fmt_time(current))
+            .validate_with(|s: &String| parse_time(s).map(|_| ()))
+            .report(!is_quiet())
+            .interact_text_on(&term)
+            .context("Failed to read start time input")?;
+        if input != fmt_time(current) {
+            let new_start = at_local_time(date, parse_time(&input).map_err(|e| anyhow!(e))?)?;
+            update = update.start(new_start);
+            start = Some(new_start);
+        }
+    }
+    if let (Some(current), Some(start)) = (entry.stop, start) {
+        let input: String = dialoguer::Input::with_theme(&theme)
+            .with_prompt("Stop (HH:MM)")
+            .with_initial_text(fmt_time(current))
+            .validate_with(|s: &String| parse_time(s).map(|_| ()))
+            .report(!is_quiet())
+            .interact_text_on(&term)
+            .context("Failed to read stop time input")?;
+        if input != fmt_time(current) || Some(start) != entry.start {
+            update = update.stop(stop_after(
+                start,
+                parse_time(&input).map_err(|e| anyhow!(e))?,
+            )?);
+        }
+    }
+
+    Ok(update)
+}
+
+pub fn run_split(config: &Config, args: &SplitArgs) -> Result<()> {
+    let client = get_client()?;
+    let entry = args
+        .entry
+        .resolve_or_select(&client, "Select an entry to split")?;
+    ensure_unlocked(&client, [&entry])?;
+    let start = entry
+        .start
+        .ok_or_else(|| anyhow!("The entry has no start time"))?;
+    let at = stop_after(start, args.at)?;
+    let end = if entry.is_running {
+        Utc::now()
+    } else {
+        entry.stop.unwrap_or(start)
+    };
+    if at <= start || at >= end {
+        bail!("{} isn't within the entry", args.at.format("%H:%M"));
+    }
+
+    let mut second = TimeEntryUpdate::new().start(at);
+    if let Some(description) = &args.description {
+        second = second.description(description);
+    }
+    if let Some(project) = &args.project {
+        second = second.project_id(find_project_id(&client, entry.workspace_id, project)?);
+    }
+
+    // Truncate first, so a running entry is stopped before its remainder
+    // starts running.
+    client
+        .update_time_entry(&entry, &TimeEntryUpdate::new().stop(at))
+        .context("Failed to update time entry")?;
+    let second = client
+        .copy_time_entry(&entry, &second)
+        .context("Failed to create time entry")?;
+    journal::forget();
+    clear_prompt_cache();
+
+    report_change(config, &client, Some(&second))
+}
+
+pub fn run_adjust(config: &Config, args: &AdjustArgs) -> Result<()> {
+    let client = get_client()?;
+    let entry = client
+        .get_current_entry()
+        .context("Failed to retrieve current time entry")?
+        .ok_or_else(|| anyhow!("{} No timers running", icon("🤷")))?;
+    let current = entry
+        .start
+        .ok_or_else(|| anyhow!("The entry has no start time"))?;
+    let now = Utc::now();
+    let start = match (args.start, args.back) {
+        (Some(start), _) => start_before(now, start)?,
+        (None, Some(back)) => current
+            .checked_sub_signed(back.duration())
+            .ok_or_else(|| anyhow!("Can't move the start back that far"))?,
+        (None, None) => unreachable!("clap requires --start or --back"),
+    };
+    if start > now {
+        bail!("The entry can't start in the future");
+    }
+    ensure_unlocked(&client, [&entry])?;
+
+    let updated = apply_update(&client, &entry, &TimeEntryUpdate::new().start(start))?;
+    report_change(config, &client, updated.as_ref())
+}
+
+pub fn run_rename(config: &Config, args: &RenameArgs) -> Result<()> {
+    let client = get_client()?;
+    let entry = client
+        .get_current_entry()
+        .context("Failed to retrieve current time entry")?
+        .ok_or_else(|| anyhow!("{} No timers running", icon("🤷")))?;
+    ensure_unlocked(&client, [&entry])?;
+    let mut update = TimeEntryUpdate::new();
+    if let Some(description) = &args.description {
+        update = update.description(description);
+    }
+    if let Some(project) = &args.project {
+        update = update.project_id(find_project_id(&client, entry.workspace_id, project)?);
+    }
+
+    let updated = apply_update(&client, &entry, &update)?;
+    report_change(config, &client, updated.as_ref())
+}
+
+/// Places `time` on today's date, or yesterday's if that would be after
+/// `now`, for a timer that was started before midnight.
+fn start_before(now: DateTime<Utc>, time: NaiveTime) -> Result<DateTime<Utc>> {
+    let date = DateTime::<Local>::from(now).date_naive();
+    let candidate = at_local_time(date, time)?;
+    if candidate <= now {
+        Ok(candidate)
+    } else {
+        at_local_time(date - Days::new(1), time)
+    }
+}
+
+pub fn run_delete(config: &Config, args: &DeleteArgs) -> Result<()> {
+    let client = get_client()?;
+    let entry = args
+        .entry
+        .resolve_or_select(&client, "Select an entry to delete")?;
+    ensure_unlocked(&client, [&entry])?;
+
+    if !args.yes {
+        ensure_can_prompt("confirmation", Some("Pass --yes to skip it"))?;
+        println_entry(config, None, &entry, false);
+        let confirmed = dialoguer::Confirm::new()
+            .with_prompt("Delete this entry?")
+            .default(false)
+            .report(!is_quiet())
+            .interact()
+            .context("Failed to read confirmation")?;
+        if !confirmed {
+            bail!("Aborted");
+        }
+    }
+
+    delete_entry(&client, &entry)?;
+
+    report_change(config, &client, Some(&entry))
+}
+
+/// Deletes an entry without asking, so that `tgl undo` can restore it.
+pub fn delete_entry(client: &Client, entry: &TimeEntry) -> Result<()> {
+    client
+        .delete_time_entry(entry)
+        .context("Failed to delete time entry")?;
+    journal::record(vec![Action::Deleted {
+        entry: entry.clone(),
+    }]);
+    clear_prompt_cache();
+
+    Ok(())
+}
+
+/// The local date an entry started on, or today.
+fn entry_date(entry: &TimeEntry) -> NaiveDate {
+    entry
+        .start
+        .map(|s| DateTime::<Local>::from(s).date_naive())
+        .unwrap_or_else(|| Local::now().date_naive())
+}
+
+fn parse_time(s: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(s.trim(), "%H:%M")
+        .map_err(|_| format!("Invalid time '{s}', expected HH:MM"))
+}
+
+pub fn at_local_time(date: NaiveDate, time: NaiveTime) -> Result<DateTime<Utc>> {
+    Local
+        .from_local_datetime(&date.and_time(time))
+        .earliest()
+        .map(|t| t.with_timezone(&Utc))
+        .ok_or_else(|| anyhow!("{date} {time} doesn't exist in the local time zone"))
+}
+
+/// Places `stop` on the start's date, or the next day if that would be
+/// before the start (i.e. the entry runs past midnight).
+fn stop_after(start: DateTime<Utc>, stop: NaiveTime) -> Result<DateTime<Utc>> {
+    let date = DateTime::<Local>::from(start).date_naive();
+    let candidate = at_local_time(date, stop)?;
+    if candidate >= start {
+        Ok(candidate)
+    } else {
+        at_local_time(date + Days::new(1), stop)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn starts_before_now() {
+        let now = at_local_time(
+            NaiveDate::from_ymd_opt(2024, 6, 4).unwrap(),
+            NaiveTime::from_hms_opt(0, 10, 0).unwrap(),
+        )
+        .unwrap();
+        let local = |t: DateTime<Utc>| DateTime::<Local>::from(t).naive_local().to_string();
+
+        let start = start_before(now, NaiveTime::from_hms_opt(0, 5, 0).unwrap()).unwrap();
+        assert_eq!("2024-06-04 00:05:00", local(start));
+        let start = start_before(now, NaiveTime::from_hms_opt(23, 50, 0).unwrap()).unwrap();
+        assert_eq!("2024-06-03 23:50:00", local(start));
+    }
+}
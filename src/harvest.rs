@@ -0,0 +1,387 @@
+//! Client for the [Harvest API v2](https://help.getharvest.com/api-v2/), as
+//! an alternative [`TimeTracker`] backend.
+
+use crate::{
+    api::ResponseExt,
+    svc::{Error, Project, Result, TimeEntry, Workspace},
+    tracker::TimeTracker,
+};
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveTime, TimeZone, Utc};
+use reqwest::blocking::{RequestBuilder, Response};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::json;
+use std::{
+    cell::{OnceCell, RefCell},
+    collections::HashMap,
+};
+
+static BASE_API_URL: &str = "https://api.harvestapp.com/v2";
+
+/// The most items Harvest returns in one page.
+const PAGE_SIZE: &str = "2000";
+
+/// Client for Harvest, authenticated with a personal access token.
+///
+/// Harvest has no workspaces, so the account is the only one. Entries are
+/// logged against a project's task; starting an entry uses the first task
+/// assigned to its project.
+pub struct HarvestClient {
+    c: reqwest::blocking::Client,
+    token: String,
+    account_id: i64,
+    get_now: fn() -> DateTime<Utc>,
+    user_id: OnceCell<i64>,
+    /// The first task assigned to each project, by project ID.
+    project_tasks: RefCell<HashMap<i64, i64>>,
+}
+
+#[derive(Deserialize)]
+struct Page<T> {
+    #[serde(alias = "time_entries", alias = "project_assignments")]
+    items: Vec<T>,
+    next_page: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct User {
+    id: i64,
+}
+
+#[derive(Deserialize)]
+struct Company {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct Named {
+    id: i64,
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct ProjectAssignment {
+    project: Named,
+    client: Option<Named>,
+    is_active: bool,
+    hourly_rate: Option<f64>,
+    #[serde(default)]
+    task_assignments: Vec<TaskAssignment>,
+}
+
+#[derive(Deserialize)]
+struct TaskAssignment {
+    task: Named,
+    is_active: bool,
+}
+
+#[derive(Deserialize)]
+struct ApiTimeEntry {
+    id: i64,
+    spent_date: NaiveDate,
+    hours: f64,
+    notes: Option<String>,
+    is_running: bool,
+    billable: bool,
+    project: Option<Named>,
+    client: Option<Named>,
+    task: Option<Named>,
+    /// When a running timer was started.
+    timer_started_at: Option<DateTime<Utc>>,
+    /// Wall-clock start like `8:00am`, for accounts that track timestamps.
+    started_time: Option<String>,
+    created_at: DateTime<Utc>,
+}
+
+impl HarvestClient {
+    pub fn new(token: String, account_id: i64, get_now: fn() -> DateTime<Utc>) -> Result<Self> {
+        Ok(Self {
+            c: reqwest::blocking::Client::builder()
+                // Harvest rejects requests without a user agent.
+                .user_agent("github.com/blachniet/tgl")
+                .build()?,
+            token,
+            account_id,
+            get_now,
+            user_id: OnceCell::new(),
+            project_tasks: RefCell::new(HashMap::new()),
+        })
+    }
+
+    fn send(&self, request: RequestBuilder) -> Result<Response> {
+        request
+            .bearer_auth(&self.token)
+            .header("Harvest-Account-Id", self.account_id)
+            .send()?
+            .check()
+    }
+
+    /// Gets every page of a listing.
+    fn get_all<T: DeserializeOwned>(&self, path: &str, query: &[(&str, String)]) -> Result<Vec<T>> {
+        let mut items = Vec::new();
+        let mut page = 1;
+        loop {
+            let response: Page<T> = self
+                .send(
+                    self.c
+                        .get(format!("{BASE_API_URL}{path}"))
+                        .query(query)
+                        .query(&[
+                            ("page", page.to_string()),
+                            ("per_page", PAGE_SIZE.to_string()),
+                        ]),
+                )?
+                .json()?;
+            items.extend(response.items);
+            match response.next_page {
+                Some(next) => page = next,
+                None => return Ok(items),
+            }
+        }
+    }
+
+    fn user_id(&self) -> Result<i64> {
+        if let Some(id) = self.user_id.get() {
+            return Ok(*id);
+        }
+        let user: User = self
+            .send(self.c.get(format!("{BASE_API_URL}/users/me")))?
+            .json()?;
+
+        Ok(*self.user_id.get_or_init(|| user.id))
+    }
+
+    fn user_entries(&self, query: &[(&str, String)]) -> Result<Vec<TimeEntry>> {
+        let mut query = query.to_vec();
+        query.push(("user_id", self.user_id()?.to_string()));
+        let entries: Vec<ApiTimeEntry> = self.get_all("/time_entries", &query)?;
+
+        Ok(entries
+            .into_iter()
+            .map(|e| self.build_time_entry(e))
+            .collect())
+    }
+
+    fn build_time_entry(&self, entry: ApiTimeEntry) -> TimeEntry {
+        let duration = Duration::seconds((entry.hours * 3600.0).round() as i64);
+        let start = entry_start(&entry);
+        let stop = match (entry.is_running, start) {
+            (false, Some(start)) => Some(start + duration),
+            _ => None,
+        };
+        let (project_id, project_name) = match entry.project {
+            Some(p) => (Some(p.id), Some(p.name)),
+            None => (None, None),
+        };
+        let (client_id, client_name) = match entry.client {
+            Some(c) => (Some(c.id), Some(c.name)),
+            None => (None, None),
+        };
+        let duration = match entry.timer_started_at {
+            // Harvest only counts a running timer's hours in whole minutes.
+            Some(started) if entry.is_running => duration.max((self.get_now)() - started),
+            _ => duration,
+        };
+
+        TimeEntry {
+            billable: entry.billable,
+            client_id,
+            client_name,
+            description: entry.notes.filter(|n| !n.is_empty()),
+            duration,
+            id: entry.id,
+            is_running: entry.is_running,
+            project_id,
+            project_color: None,
+            project_name,
+            start,
+            stop,
+            tags: Vec::new(),
+            task_id: entry.task.map(|t| t.id),
+            workspace_id: self.account_id,
+        }
+    }
+
+    fn project_task(&self, project_id: i64) -> Result<i64> {
+        if let Some(task_id) = self.project_tasks.borrow().get(&project_id) {
+            return Ok(*task_id);
+        }
+        self.projects(self.account_id)?;
+
+        self.project_tasks
+            .borrow()
+            .get(&project_id)
+            .copied()
+            .ok_or(Error::ProjectRequired)
+    }
+}
+
+/// Works out when an entry started. Harvest only keeps the exact start of
+/// running timers, and the wall-clock start on accounts that track
+/// timestamps. Other entries fall back to when they were created.
+fn entry_start(entry: &ApiTimeEntry) -> Option<DateTime<Utc>> {
+    if let Some(started) = entry.timer_started_at {
+        return Some(started);
+    }
+    let started_time = entry
+        .started_time
+        .as_deref()
+        .and_then(|t| NaiveTime::parse_from_str(t, "%I:%M%p").ok());
+    match started_time {
+        Some(time) => Local
+            .from_local_datetime(&entry.spent_date.and_time(time))
+            .earliest()
+            .map(|t| t.into()),
+        None => Some(entry.created_at),
+    }
+}
+
+impl TimeTracker for HarvestClient {
+    fn workspaces(&self) -> Result<Vec<Workspace>> {
+        let company: Company = self
+            .send(self.c.get(format!("{BASE_API_URL}/company")))?
+            .json()?;
+
+        Ok(vec![Workspace {
+            default_currency: None,
+            default_hourly_rate: None,
+            id: self.account_id,
+            lock_date: None,
+            name: company.name,
+            rounding: 0,
+            rounding_minutes: 0,
+        }])
+    }
+
+    /// Gets the projects you're assigned to.
+    fn projects(&self, workspace_id: i64) -> Result<Vec<Project>> {
+        if workspace_id != self.account_id {
+            return Err(Error::UnknownId(workspace_id));
+        }
+        let assignments: Vec<ProjectAssignment> =
+            self.get_all("/users/me/project_assignments", &[])?;
+
+        let mut tasks = self.project_tasks.borrow_mut();
+        Ok(assignments
+            .into_iter()
+            .map(|a| {
+                if let Some(t) = a.task_assignments.iter().find(|t| t.is_active) {
+                    tasks.insert(a.project.id, t.task.id);
+                }
+                Project {
+                    active: a.is_active,
+                    actual_hours: None,
+                    client_id: a.client.map(|c| c.id),
+                    color: None,
+                    currency: None,
+                    estimated_hours: None,
+                    id: a.project.id,
+                    name: a.project.name,
+                    rate: a.hourly_rate,
+                    workspace_id,
+                }
+            })
+            .collect())
+    }
+
+    /// Gets the entries spent on the local days that `[start, end)` covers.
+    /// Harvest files entries by day, so this doesn't narrow them further.
+    fn time_entries(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<TimeEntry>> {
+        let day = |t: DateTime<Utc>| t.with_timezone(&Local).date_naive().to_string();
+
+        self.user_entries(&[
+            ("from", day(start)),
+            ("to", day(end - Duration::seconds(1))),
+        ])
+    }
+
+    fn current_entry(&self) -> Result<Option<TimeEntry>> {
+        Ok(self
+            .user_entries(&[("is_running", "true".to_string())])?
+            .into_iter()
+            .next())
+    }
+
+    /// Starts a timer on the project's first task. Harvest needs a project,
+    /// and takes the start from the request's time rather than `start`.
+    fn start_entry(
+        &self,
+        _workspace_id: i64,
+        project_id: Option<i64>,
+        description: Option<&str>,
+        start: DateTime<Utc>,
+    ) -> Result<TimeEntry> {
+        let project_id = project_id.ok_or(Error::ProjectRequired)?;
+        let task_id = self.project_task(project_id)?;
+        let entry = self
+            .send(
+                self.c
+                    .post(format!("{BASE_API_URL}/time_entries"))
+                    .json(&json!({
+                        "project_id": project_id,
+                        "task_id": task_id,
+                        "spent_date": start.with_timezone(&Local).date_naive(),
+                        "notes": description,
+                    })),
+            )?
+            .json()?;
+
+        Ok(self.build_time_entry(entry))
+    }
+
+    fn stop_current_entry(&self) -> Result<Option<TimeEntry>> {
+        let Some(running) = self.current_entry()? else {
+            return Ok(None);
+        };
+        let entry = self
+            .send(
+                self.c
+                    .patch(format!("{BASE_API_URL}/time_entries/{}/stop", running.id)),
+            )?
+            .json()?;
+
+        Ok(Some(self.build_time_entry(entry)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entry_start_prefers_exact_times() {
+        let entry = |timer_started_at: Option<&str>, started_time: Option<&str>| ApiTimeEntry {
+            id: 1,
+            spent_date: NaiveDate::from_ymd_opt(2024, 6, 3).unwrap(),
+            hours: 1.5,
+            notes: None,
+            is_running: false,
+            billable: false,
+            project: None,
+            client: None,
+            task: None,
+            timer_started_at: timer_started_at.map(|t| t.parse().unwrap()),
+            started_time: started_time.map(str::to_string),
+            created_at: "2024-06-03T18:00:00Z".parse().unwrap(),
+        };
+        let local = |h, m| {
+            Local
+                .with_ymd_and_hms(2024, 6, 3, h, m, 0)
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+
+        assert_eq!(
+            Some("2024-06-03T07:15:00Z".parse().unwrap()),
+            entry_start(&entry(Some("2024-06-03T07:15:00Z"), Some("8:00am")))
+        );
+        assert_eq!(Some(local(8, 0)), entry_start(&entry(None, Some("8:00am"))));
+        assert_eq!(
+            Some(local(13, 30)),
+            entry_start(&entry(None, Some("1:30pm")))
+        );
+        assert_eq!(
+            Some("2024-06-03T18:00:00Z".parse().unwrap()),
+            entry_start(&entry(None, None))
+        );
+    }
+}
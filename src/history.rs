@@ -0,0 +1,66 @@
+//! Prompt suggestions drawn from recent time entry descriptions.
+
+use dialoguer::{Completion, History};
+use std::cell::OnceCell;
+use tgl_cli::Client;
+
+/// Recent descriptions, newest first. They're fetched the first time a
+/// suggestion is requested, so prompts that never ask cost no requests.
+pub struct RecentDescriptions<'a> {
+    client: &'a Client,
+    descriptions: OnceCell<Vec<String>>,
+}
+
+impl<'a> RecentDescriptions<'a> {
+    pub fn new(client: &'a Client) -> Self {
+        Self {
+            client,
+            descriptions: OnceCell::new(),
+        }
+    }
+
+    fn descriptions(&self) -> &[String] {
+        self.descriptions.get_or_init(|| {
+            // Suggestions are a convenience, so don't fail the prompt over
+            // them.
+            let mut entries = self.client.get_latest_entries().unwrap_or_default();
+            entries.sort_by_key(|e| std::cmp::Reverse(e.start));
+
+            let mut descriptions: Vec<String> = Vec::new();
+            for d in entries.into_iter().filter_map(|e| e.description) {
+                if !d.is_empty() && !descriptions.contains(&d) {
+                    descriptions.push(d);
+                }
+            }
+
+            descriptions
+        })
+    }
+
+    /// Returns [`History`] over the descriptions, for use with the Up and
+    /// Down arrow keys.
+    pub fn history(&self) -> DescriptionHistory<'_, 'a> {
+        DescriptionHistory(self)
+    }
+}
+
+/// Completes the input to the most recent description that starts with it.
+impl Completion for RecentDescriptions<'_> {
+    fn get(&self, input: &str) -> Option<String> {
+        let input = input.to_lowercase();
+        self.descriptions()
+            .iter()
+            .find(|d| d.len() > input.len() && d.to_lowercase().starts_with(&input))
+            .cloned()
+    }
+}
+
+pub struct DescriptionHistory<'r, 'a>(&'r RecentDescriptions<'a>);
+
+impl History<String> for DescriptionHistory<'_, '_> {
+    fn read(&self, pos: usize) -> Option<String> {
+        self.0.descriptions().get(pos).cloned()
+    }
+
+    fn write(&mut self, _val: &String) {}
+}
@@ -0,0 +1,211 @@
+//! Commands from `[hooks]` that run as tgl starts, stops, and switches
+//! timers, e.g. to set a Slack status or log to another system.
+
+use crate::{client_options, config::HooksConfig, icon, is_dry_run, journal::Action};
+use chrono::{DateTime, Utc};
+use std::{
+    io,
+    process::{Command, Stdio},
+};
+use tgl_cli::TimeEntry;
+
+/// A change to the running timer that hooks run for.
+enum Event<'a> {
+    Start(&'a TimeEntry),
+    Stop(&'a TimeEntry),
+    Switch {
+        stopped: &'a TimeEntry,
+        started: &'a TimeEntry,
+    },
+}
+
+/// Finds the timer changes among a command's actions. A stop followed by a
+/// start is a switch.
+fn events(actions: &[Action]) -> Vec<Event<'_>> {
+    let mut events = Vec::new();
+    let mut actions = actions.iter().peekable();
+    while let Some(action) = actions.next() {
+        match action {
+            Action::Stopped { entry: stopped } => match actions.peek() {
+                Some(Action::Started { entry: started }) if started.is_running => {
+                    actions.next();
+                    events.push(Event::Switch { stopped, started });
+                }
+                _ => events.push(Event::Stop(stopped)),
+            },
+            // Entries added after the fact, as by `tgl suggest`, never ran.
+            Action::Started { entry } if entry.is_running => events.push(Event::Start(entry)),
+            _ => {}
+        }
+    }
+
+    events
+}
+
+/// A hook to run: its name, its command, and its environment.
+type Hook<'a> = (&'static str, &'a str, Vec<(String, String)>);
+
+/// The hooks to run for an event. Without an `on_switch` hook, a switch is a
+/// stop and then a start.
+fn hooks_for<'a>(hooks: &'a HooksConfig, event: &Event) -> Vec<Hook<'a>> {
+    let hook = |name: &'static str, command: &'a Option<String>, entries: &[(&str, &TimeEntry)]| {
+        let command = command.as_deref()?;
+        let mut env = vec![(
+            "TGL_EVENT".to_string(),
+            name.trim_start_matches("on_").to_string(),
+        )];
+        for (prefix, entry) in entries {
+            env.extend(entry_env(prefix, entry));
+        }
+
+        Some((name, command, env))
+    };
+
+    match *event {
+        Event::Start(entry) => vec![hook("on_start", &hooks.on_start, &[("TGL_", entry)])],
+        Event::Stop(entry) => vec![hook("on_stop", &hooks.on_stop, &[("TGL_", entry)])],
+        Event::Switch { stopped, started } if hooks.on_switch.is_some() => vec![hook(
+            "on_switch",
+            &hooks.on_switch,
+            &[("TGL_", started), ("TGL_PREVIOUS_", stopped)],
+        )],
+        Event::Switch { stopped, started } => vec![
+            hook("on_stop", &hooks.on_stop, &[("TGL_", stopped)]),
+            hook("on_start", &hooks.on_start, &[("TGL_", started)]),
+        ],
+    }
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// The entry's details as environment variables, each name starting with
+/// `prefix`. Missing details are empty.
+fn entry_env(prefix: &str, entry: &TimeEntry) -> Vec<(String, String)> {
+    let time = |t: Option<DateTime<Utc>>| t.map(|t| t.to_rfc3339()).unwrap_or_default();
+    [
+        ("ID", entry.id.to_string()),
+        ("DESCRIPTION", entry.description.clone().unwrap_or_default()),
+        ("PROJECT", entry.project_name.clone().unwrap_or_default()),
+        ("CLIENT", entry.client_name.clone().unwrap_or_default()),
+        ("TAGS", entry.tags.join(",")),
+        ("BILLABLE", entry.billable.to_string()),
+        ("START", time(entry.start)),
+        ("STOP", time(entry.stop)),
+        ("DURATION", entry.duration.num_seconds().to_string()),
+        ("WORKSPACE_ID", entry.workspace_id.to_string()),
+    ]
+    .into_iter()
+    .map(|(name, value)| (format!("{prefix}{name}"), value))
+    .collect()
+}
+
+/// Runs the hooks for a command's actions, one at a time. A failing hook is
+/// reported without failing the command, since the change already happened.
+pub fn run(actions: &[Action]) {
+    if is_dry_run() {
+        return;
+    }
+    let hooks = &client_options().hooks;
+    for event in events(actions) {
+        for (name, command, env) in hooks_for(hooks, &event) {
+            // Hook output goes to stderr, like git's, to keep stdout for
+            // tgl's own.
+            let status = shell(command)
+                .envs(env)
+                .stdin(Stdio::null())
+                .stdout(io::stderr())
+                .status();
+            match status {
+                Ok(status) if status.success() => {}
+                Ok(status) => eprintln!("{} The {name} hook failed with {status}", icon("⚠️")),
+                Err(err) => eprintln!("{} Failed to run the {name} hook: {err}", icon("⚠️")),
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+#[cfg(not(unix))]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_hooks_for_transitions() {
+        let entry = |id: i64, is_running: bool| -> TimeEntry {
+            serde_json::from_value(serde_json::json!({
+                "billable": false,
+                "client_id": null,
+                "description": "Fix login bug",
+                "duration": 600,
+                "id": id,
+                "is_running": is_running,
+                "project_id": null,
+                "project_name": "Website",
+                "start": "2024-06-03T09:00:00Z",
+                "stop": null,
+                "tags": ["a", "b"],
+                "task_id": null,
+                "workspace_id": 1,
+            }))
+            .unwrap()
+        };
+        let hooks = HooksConfig {
+            on_start: Some("start".to_string()),
+            on_stop: Some("stop".to_string()),
+            on_switch: None,
+        };
+        let run = |actions: &[Action], hooks: &HooksConfig| -> Vec<String> {
+            events(actions)
+                .iter()
+                .flat_map(|event| hooks_for(hooks, event))
+                .map(|(_, command, env)| {
+                    let id = |name| env.iter().find(|(n, _)| n == name).map(|(_, v)| v.clone());
+                    format!(
+                        "{command} {} {}",
+                        id("TGL_ID").unwrap_or_default(),
+                        id("TGL_PREVIOUS_ID").unwrap_or_default()
+                    )
+                    .trim_end()
+                    .to_string()
+                })
+                .collect()
+        };
+        let switch = [
+            Action::Stopped {
+                entry: entry(1, false),
+            },
+            Action::Started {
+                entry: entry(2, true),
+            },
+        ];
+
+        assert_eq!(vec!["stop 1", "start 2"], run(&switch, &hooks));
+        let with_switch = HooksConfig {
+            on_switch: Some("switch".to_string()),
+            ..hooks.clone()
+        };
+        assert_eq!(vec!["switch 2 1"], run(&switch, &with_switch));
+        let added = [Action::Started {
+            entry: entry(3, false),
+        }];
+        assert!(run(&added, &hooks).is_empty());
+        assert_eq!(
+            ("TGL_TAGS".to_string(), "a,b".to_string()),
+            entry_env("TGL_", &entry(1, false))[4]
+        );
+    }
+}
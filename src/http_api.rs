@@ -0,0 +1,258 @@
+//! The daemon's HTTP API on localhost, for editors, browser extensions, and
+//! widgets that would otherwise run tgl for every click.
+//!
+//! - `GET /current` returns the running entry, or `null`.
+//! - `POST /toggle` stops the running entry, or restarts the latest one.
+//! - `POST /start` starts `{"template": "<name>"}` from the config file,
+//!   optionally with its own `"description"`.
+//!
+//! Each returns the running entry afterwards. POSTs must be sent as
+//! `application/json`, which web pages can't do across origins without the
+//! permission this API never grants, and every request must name the
+//! daemon's own address as its `Host`, so that a page can't reach the API
+//! through a domain it rebinds to 127.0.0.1.
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    time::Duration,
+};
+use tgl_cli::TimeEntry;
+
+/// Bodies beyond this are cut off; a template name never comes close.
+const MAX_BODY: usize = 64 * 1024;
+
+/// What a request asks the daemon to do with the timer.
+pub enum Action {
+    Toggle,
+    Start {
+        template: String,
+        description: Option<String>,
+    },
+}
+
+#[derive(Deserialize)]
+struct StartBody {
+    template: String,
+    description: Option<String>,
+}
+
+/// The daemon's result of an action: the running entry after it, or why it
+/// failed.
+type Outcome = Result<Option<TimeEntry>, String>;
+
+/// Answers requests one at a time until the listener fails. `current` gets
+/// the running entry, or `None` before the daemon's first poll, and `call`
+/// has the daemon carry out an action, or returns `None` if it has stopped.
+pub fn serve(
+    listener: TcpListener,
+    current: impl Fn() -> Option<Option<TimeEntry>>,
+    call: impl Fn(Action) -> Option<Outcome>,
+) {
+    let port = listener.local_addr().map_or(0, |addr| addr.port());
+    for stream in listener.incoming().flatten() {
+        let _ = answer(stream, port, &current, &call);
+    }
+}
+
+/// An HTTP request, read just far enough for the daemon's small APIs.
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    /// The headers with lowercase names, in the order they came.
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    /// The value of the first header named `name`, which must be lowercase.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn is_json(&self) -> bool {
+        self.header("content-type")
+            .is_some_and(|value| value.starts_with("application/json"))
+    }
+}
+
+/// Reads a request from `stream`, giving up on clients that stall.
+pub fn read_request(stream: &TcpStream) -> io::Result<Request> {
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+    }
+    let content_length = headers
+        .iter()
+        .find(|(name, _)| name == "content-length")
+        .and_then(|(_, value)| value.parse().ok())
+        .unwrap_or(0usize);
+    let mut body = Vec::new();
+    reader
+        .take(content_length.min(MAX_BODY) as u64)
+        .read_to_end(&mut body)?;
+
+    let mut parts = request_line.split_whitespace();
+    Ok(Request {
+        method: parts.next().unwrap_or("").to_string(),
+        path: parts.next().unwrap_or("").to_string(),
+        headers,
+        body,
+    })
+}
+
+/// Writes a JSON response and closes the connection.
+pub fn respond(mut stream: TcpStream, status: u16, body: &Value) -> io::Result<()> {
+    let body = body.to_string();
+    write!(
+        stream,
+        "HTTP/1.1 {status} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        reason(status),
+        body.len()
+    )
+}
+
+fn answer(
+    stream: TcpStream,
+    port: u16,
+    current: &dyn Fn() -> Option<Option<TimeEntry>>,
+    call: &dyn Fn(Action) -> Option<Outcome>,
+) -> io::Result<()> {
+    let request = read_request(&stream)?;
+    let (status, body) = route(&request, port, current, call);
+
+    respond(stream, status, &body)
+}
+
+/// Whether `host` is this machine's own address for `port`, as opposed to a
+/// domain that merely resolves to it.
+fn is_local_host(host: &str, port: u16) -> bool {
+    [format!("127.0.0.1:{port}"), format!("localhost:{port}")]
+        .iter()
+        .any(|local| host.eq_ignore_ascii_case(local))
+}
+
+fn route(
+    request: &Request,
+    port: u16,
+    current: &dyn Fn() -> Option<Option<TimeEntry>>,
+    call: &dyn Fn(Action) -> Option<Outcome>,
+) -> (u16, Value) {
+    let error = |status, message: &str| (status, json!({ "error": message }));
+    if !request
+        .header("host")
+        .is_some_and(|host| is_local_host(host, port))
+    {
+        return error(403, "Send requests to 127.0.0.1 or localhost");
+    }
+    let action = match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/current") => {
+            return match current() {
+                Some(running) => (200, json!(running)),
+                None => error(503, "The daemon hasn't polled Toggl yet"),
+            };
+        }
+        ("POST", "/toggle" | "/start") if !request.is_json() => {
+            return error(415, "Send requests as application/json");
+        }
+        ("POST", "/toggle") => Action::Toggle,
+        ("POST", "/start") => match serde_json::from_slice::<StartBody>(&request.body) {
+            Ok(StartBody {
+                template,
+                description,
+            }) => Action::Start {
+                template,
+                description,
+            },
+            Err(err) => return error(400, &format!("Invalid request body: {err}")),
+        },
+        (_, "/current" | "/toggle" | "/start") => return error(405, "Method not allowed"),
+        _ => return error(404, "Not found"),
+    };
+
+    match call(action) {
+        Some(Ok(running)) => (200, json!(running)),
+        Some(Err(err)) => error(500, &err),
+        None => error(503, "The daemon is shutting down"),
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        415 => "Unsupported Media Type",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn routes_requests() {
+        let current = || Some(None);
+        let call = |action| match action {
+            Action::Toggle => Some(Ok(None)),
+            Action::Start { template, .. } => Some(Err(format!("No template named '{template}'"))),
+        };
+        let request = |host: &str, method: &str, path: &str, is_json, body: &str| {
+            let mut headers = vec![("host".to_string(), host.to_string())];
+            if is_json {
+                headers.push(("content-type".to_string(), "application/json".to_string()));
+            }
+            let request = Request {
+                method: method.to_string(),
+                path: path.to_string(),
+                headers,
+                body: body.as_bytes().to_vec(),
+            };
+            route(&request, 7878, &current, &call).0
+        };
+        let route =
+            |method, path, is_json, body| request("localhost:7878", method, path, is_json, body);
+
+        assert_eq!(200, route("GET", "/current", false, ""));
+        assert_eq!(200, route("POST", "/toggle", true, ""));
+        assert_eq!(415, route("POST", "/toggle", false, ""));
+        assert_eq!(400, route("POST", "/start", true, "{}"));
+        assert_eq!(500, route("POST", "/start", true, r#"{"template": "x"}"#));
+        assert_eq!(405, route("GET", "/toggle", false, ""));
+        assert_eq!(404, route("GET", "/", false, ""));
+
+        assert_eq!(200, request("127.0.0.1:7878", "GET", "/current", false, ""));
+        assert_eq!(
+            403,
+            request("evil.example:7878", "GET", "/current", false, "")
+        );
+        assert_eq!(
+            403,
+            request("evil.example:7878", "POST", "/toggle", true, "")
+        );
+        assert_eq!(403, request("localhost:80", "GET", "/current", false, ""));
+    }
+}
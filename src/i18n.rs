@@ -0,0 +1,182 @@
+//! Translations of status and report output, kept as Fluent files under
+//! `locales/`.
+
+use chrono::{Locale, NaiveDate};
+use fluent_bundle::{concurrent::FluentBundle, FluentArgs, FluentResource, FluentValue};
+use std::{env, sync::OnceLock};
+use unic_langid::LanguageIdentifier;
+
+/// Bundled translations by language. English comes first, since it's the
+/// fallback for messages other languages lack.
+const TRANSLATIONS: [(&str, &str); 2] = [
+    ("en", include_str!("../locales/en.ftl")),
+    ("de", include_str!("../locales/de.ftl")),
+];
+
+/// Where the language comes from when the config doesn't set one, most
+/// specific first.
+const LOCALE_VARS: [&str; 3] = ["LC_ALL", "LC_MESSAGES", "LANG"];
+
+struct Messages {
+    /// Names days and months in dates.
+    locale: Locale,
+    /// The user's language, then English.
+    bundles: Vec<FluentBundle<FluentResource>>,
+}
+
+static MESSAGES: OnceLock<Messages> = OnceLock::new();
+
+impl Messages {
+    /// Loads the messages for a locale such as `de_DE.UTF-8` or `de-DE`, or
+    /// English without one.
+    fn new(requested: Option<&str>) -> Self {
+        // Drop the encoding and modifier, as in `de_DE.UTF-8@euro`.
+        let name = requested
+            .and_then(|r| r.split(['.', '@']).next())
+            .unwrap_or_default()
+            .replace('-', "_");
+        let language = name.split('_').next().unwrap_or_default();
+        let locale = Locale::try_from(name.as_str())
+            .or_else(|_| {
+                Locale::try_from(format!("{language}_{}", language.to_uppercase()).as_str())
+            })
+            .unwrap_or(Locale::POSIX);
+
+        let bundles = TRANSLATIONS[1..]
+            .iter()
+            .filter(|(lang, _)| *lang == language)
+            .chain(&TRANSLATIONS[..1])
+            .map(|(lang, ftl)| bundle(lang, ftl))
+            .collect();
+
+        Self { locale, bundles }
+    }
+
+    fn tr(&self, id: &str, args: &[(&str, FluentValue)]) -> String {
+        let mut fluent_args = FluentArgs::new();
+        for (name, value) in args {
+            fluent_args.set(*name, value.clone());
+        }
+        for bundle in &self.bundles {
+            if let Some(pattern) = bundle.get_message(id).and_then(|m| m.value()) {
+                let mut errors = Vec::new();
+                return bundle
+                    .format_pattern(pattern, Some(&fluent_args), &mut errors)
+                    .into_owned();
+            }
+        }
+
+        id.to_string()
+    }
+
+    fn fmt_date(&self, date: NaiveDate, format_id: &str) -> String {
+        date.format_localized(&self.tr(format_id, &[]), self.locale)
+            .to_string()
+    }
+}
+
+fn bundle(language: &str, ftl: &str) -> FluentBundle<FluentResource> {
+    let language: LanguageIdentifier = language.parse().expect("bundled languages are valid");
+    let resource = FluentResource::try_new(ftl.to_string()).expect("bundled translations parse");
+    let mut bundle = FluentBundle::new_concurrent(vec![language]);
+    // The Unicode isolation marks around arguments show up in terminals.
+    bundle.set_use_isolating(false);
+    bundle
+        .add_resource(resource)
+        .expect("bundled translations don't repeat messages");
+
+    bundle
+}
+
+fn messages() -> &'static Messages {
+    MESSAGES.get_or_init(|| Messages::new(None))
+}
+
+/// Chooses the language of messages: `configured` if given, or else the one
+/// from `LC_ALL`, `LC_MESSAGES`, or `LANG`. Call it before printing anything.
+pub fn init(configured: Option<&str>) {
+    let requested = configured.map(str::to_string).or_else(|| {
+        LOCALE_VARS
+            .iter()
+            .find_map(|var| env::var(var).ok().filter(|v| !v.is_empty()))
+    });
+    let _ = MESSAGES.set(Messages::new(requested.as_deref()));
+}
+
+/// Gets a message in the chosen language, filled in with `args`.
+pub fn tr(id: &str, args: &[(&str, FluentValue)]) -> String {
+    messages().tr(id, args)
+}
+
+/// Formats a date with the chrono format in the message `format_id`.
+pub fn fmt_date(date: NaiveDate, format_id: &str) -> String {
+    messages().fmt_date(date, format_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn translates_with_english_fallback() {
+        let date = NaiveDate::from_ymd_opt(2024, 6, 3).unwrap();
+        let german = Messages::new(Some("de_DE.UTF-8"));
+        let english = Messages::new(Some("C"));
+
+        assert_eq!(
+            "Heute 1:30:00 erfasst.",
+            german.tr("logged-today", &[("duration", "1:30:00".into())])
+        );
+        assert_eq!(
+            "1:30:00 logged today.",
+            english.tr("logged-today", &[("duration", "1:30:00".into())])
+        );
+        assert_eq!(
+            "Saldo vom a bis b: c (Minusstunden).",
+            german.tr(
+                "balance-total",
+                &[
+                    ("from", "a".into()),
+                    ("to", "b".into()),
+                    ("balance", "c".into()),
+                    ("owing", "true".into())
+                ]
+            )
+        );
+        assert_eq!(
+            "Mo, 03.06.2024",
+            german.fmt_date(date, "day-heading-format")
+        );
+        assert_eq!(
+            "Mon 2024-06-03",
+            english.fmt_date(date, "day-heading-format")
+        );
+        assert_eq!(
+            "3 Einträge",
+            german.tr("status-entry-count", &[("count", 3.into())])
+        );
+        assert_eq!(
+            "1 entry",
+            english.tr("status-entry-count", &[("count", 1.into())])
+        );
+        assert_eq!("missing", german.tr("missing", &[]));
+    }
+
+    #[test]
+    fn translations_have_every_message() {
+        let english = Messages::new(None);
+        let ids: Vec<_> = TRANSLATIONS[0]
+            .1
+            .lines()
+            .filter_map(|line| line.split_once(" ="))
+            .map(|(id, _)| id.trim())
+            .collect();
+        for (language, ftl) in &TRANSLATIONS[1..] {
+            let bundle = bundle(language, ftl);
+            for id in &ids {
+                assert!(bundle.has_message(id), "{language} is missing {id}");
+            }
+        }
+        assert!(ids.iter().all(|id| english.bundles[0].has_message(id)));
+    }
+}
@@ -0,0 +1,289 @@
+//! Idle detection for `tgl daemon`. When you come back to the computer after
+//! `[idle] after` without input while a timer ran, the daemon offers to take
+//! that time out of the running entry, which `tgl idle` then does.
+//!
+//! Idle time comes from `xprintidle` on X11, GNOME's idle monitor on
+//! Wayland, and IOKit through `ioreg` on macOS, so that no native libraries
+//! are needed. Like the daemon, this isn't available on Windows.
+
+use crate::{
+    clear_prompt_cache,
+    config::{Config, IdleConfig},
+    daemon, decorate, ensure_can_prompt, get_client, hooks,
+    journal::{self, Action},
+};
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Duration, Local, Utc};
+use clap::Args;
+use serde::{Deserialize, Serialize};
+use std::process::{Command, Stdio};
+use tgl_cli::{Client, TimeEntry, TimeEntryUpdate};
+
+#[derive(Args)]
+pub struct IdleArgs {
+    /// Take the time you were away out of the running entry, which splits it
+    /// in two
+    #[arg(long, conflicts_with_all = ["stop", "keep"])]
+    discard: bool,
+    /// Stop the running entry when you left
+    #[arg(long, conflicts_with = "keep")]
+    stop: bool,
+    /// Keep the time you were away in the running entry
+    #[arg(long)]
+    keep: bool,
+}
+
+/// A time you were away from the computer while an entry ran.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Away {
+    /// The entry that ran the whole time.
+    pub entry_id: i64,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+}
+
+/// Follows the idle time from poll to poll to notice when you come back.
+pub struct Watcher {
+    after: Duration,
+    /// When the last input before the current absence was.
+    away_since: Option<DateTime<Utc>>,
+}
+
+impl Watcher {
+    /// Creates a watcher for the configured idle time, or `None` if there
+    /// isn't one.
+    pub fn new(config: &IdleConfig) -> Option<Self> {
+        Some(Self {
+            after: config.after?.duration(),
+            away_since: None,
+        })
+    }
+
+    /// Takes the idle time at `now`, returning when you were away once
+    /// you're back.
+    pub fn sample(
+        &mut self,
+        idle: Duration,
+        now: DateTime<Utc>,
+    ) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        let last_input = now - idle;
+        if idle < self.after {
+            return Some((self.away_since.take()?, last_input));
+        }
+        // An absence that began later than the one being followed means you
+        // were back in between, at a time no poll saw. Only the new one can
+        // be told apart from time you worked.
+        if self
+            .away_since
+            .is_none_or(|since| last_input - since > Duration::seconds(5))
+        {
+            self.away_since = Some(last_input);
+        }
+
+        None
+    }
+}
+
+/// How long there's been no keyboard or mouse input, or `None` if this
+/// system can't tell.
+pub fn idle_time() -> Option<Duration> {
+    if cfg!(target_os = "macos") {
+        return parse_ioreg(&output("ioreg", &["-c", "IOHIDSystem", "-d", "4"])?);
+    }
+
+    output("xprintidle", &[])
+        .and_then(|ms| ms.trim().parse().ok())
+        .map(Duration::milliseconds)
+        .or_else(|| {
+            parse_gdbus(&output(
+                "gdbus",
+                &[
+                    "call",
+                    "--session",
+                    "--dest",
+                    "org.gnome.Mutter.IdleMonitor",
+                    "--object-path",
+                    "/org/gnome/Mutter/IdleMonitor/Core",
+                    "--method",
+                    "org.gnome.Mutter.IdleMonitor.GetIdletime",
+                ],
+            )?)
+        })
+}
+
+/// Runs a program that reports on the desktop and returns what it printed,
+/// or `None` if it isn't installed or failed.
+pub fn output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+
+    String::from_utf8(output.stdout).ok()
+}
+
+/// Reads the idle time from `ioreg`, which has it in nanoseconds as
+/// `"HIDIdleTime" = 1234`.
+fn parse_ioreg(output: &str) -> Option<Duration> {
+    let (_, rest) = output.split_once("\"HIDIdleTime\" = ")?;
+    let ns: i64 = rest
+        .split(|c: char| !c.is_ascii_digit())
+        .next()?
+        .parse()
+        .ok()?;
+
+    Some(Duration::nanoseconds(ns))
+}
+
+/// Reads the idle time from GNOME's idle monitor, which has it in
+/// milliseconds as `(uint64 1234,)`.
+fn parse_gdbus(output: &str) -> Option<Duration> {
+    let ms = output.trim().strip_prefix("(uint64 ")?.strip_suffix(",)")?;
+
+    Some(Duration::milliseconds(ms.parse().ok()?))
+}
+
+/// What to do with the time you were away.
+enum Choice {
+    Discard,
+    Stop,
+    Keep,
+}
+
+fn fmt_time(config: &Config, time: DateTime<Utc>) -> String {
+    DateTime::<Local>::from(time)
+        .format(config.time_format.pattern())
+        .to_string()
+}
+
+/// Asks what to do with the time you were away, from `from` to `to`.
+fn ask(config: &Config, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Choice> {
+    ensure_can_prompt(
+        "what to do with the time you were away",
+        Some("Pass --discard, --stop, or --keep"),
+    )?;
+    let (from, to) = (fmt_time(config, from), fmt_time(config, to));
+    let items = [
+        format!("Take {from}-{to} out of the running entry"),
+        format!("Stop the running entry at {from}"),
+        "Keep the time".to_string(),
+    ];
+    let choice = dialoguer::Select::with_theme(&dialoguer::theme::ColorfulTheme::default())
+        .with_prompt(format!("You were away from {from} to {to}"))
+        .items(&items)
+        .default(0)
+        .interact_on_opt(&dialoguer::console::Term::stderr())
+        .context("Failed to read selection")?;
+
+    Ok(match choice {
+        Some(0) => Choice::Discard,
+        Some(1) => Choice::Stop,
+        _ => Choice::Keep,
+    })
+}
+
+pub fn run_idle(config: &Config, args: &IdleArgs) -> Result<()> {
+    let Some(away) = daemon::read_away() else {
+        println!(
+            "{}",
+            decorate("🤷", "tgl daemon hasn't seen you away with a timer running")
+        );
+        return Ok(());
+    };
+    let choice = match (args.discard, args.stop, args.keep) {
+        (true, _, _) => Choice::Discard,
+        (_, true, _) => Choice::Stop,
+        (_, _, true) => Choice::Keep,
+        _ => ask(config, away.from, away.to)?,
+    };
+    if let Choice::Keep = choice {
+        daemon::forget_away();
+        return Ok(());
+    }
+
+    let client = get_client()?;
+    let entry = client
+        .get_current_entry()
+        .context("Failed to retrieve current time entry")?
+        .filter(|entry| entry.id == away.entry_id)
+        .ok_or_else(|| anyhow!("The entry that ran while you were away has stopped since"))?;
+    take_away(config, &client, &entry, away.from, away.to, choice)?;
+    daemon::forget_away();
+
+    Ok(())
+}
+
+/// Takes the time you were away out of `entry`, which splits it in two, or
+/// stops it when you left.
+fn take_away(
+    config: &Config,
+    client: &Client,
+    entry: &TimeEntry,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    choice: Choice,
+) -> Result<()> {
+    if let Choice::Keep = choice {
+        return Ok(());
+    }
+    let stopped = client
+        .update_time_entry(entry, &TimeEntryUpdate::new().stop(from))
+        .context("Failed to stop time entry")?;
+    let mut actions = vec![Action::Stopped { entry: stopped }];
+    let message = match choice {
+        Choice::Discard => {
+            let resumed = client
+                .copy_time_entry(entry, &TimeEntryUpdate::new().start(to))
+                .context("Failed to start time entry")?;
+            actions.push(Action::Started { entry: resumed });
+            format!(
+                "Took {}-{} out of the running entry",
+                fmt_time(config, from),
+                fmt_time(config, to)
+            )
+        }
+        _ => format!("Stopped the running entry at {}", fmt_time(config, from)),
+    };
+    hooks::run(&actions);
+    journal::record(actions);
+    clear_prompt_cache();
+    println!("{}", decorate("✅", &message));
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn notices_coming_back() {
+        let at = |m| Utc.with_ymd_and_hms(2024, 6, 3, 10, m, 0).unwrap();
+        let mut watcher = Watcher::new(&IdleConfig {
+            after: Some("10m".parse().unwrap()),
+        })
+        .unwrap();
+
+        assert_eq!(None, watcher.sample(Duration::minutes(5), at(5)));
+        assert_eq!(None, watcher.sample(Duration::minutes(12), at(12)));
+        assert_eq!(None, watcher.sample(Duration::minutes(20), at(20)));
+        assert_eq!(
+            Some((at(0), at(24))),
+            watcher.sample(Duration::minutes(1), at(25))
+        );
+        assert_eq!(None, watcher.sample(Duration::minutes(1), at(26)));
+
+        assert_eq!(
+            Some(Duration::milliseconds(1500)),
+            parse_gdbus("(uint64 1500,)\n")
+        );
+        assert_eq!(
+            Some(Duration::seconds(2)),
+            parse_ioreg("    | |   \"HIDIdleTime\" = 2000000000\n")
+        );
+    }
+}
@@ -0,0 +1,608 @@
+//! Importing time entries exported from other time trackers.
+
+use crate::{
+    bulk::fmt_dated_entry,
+    config::{Config, WorkHours},
+    decorate, ensure_can_prompt, find_workspaces, get_client, journal,
+};
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use clap::{Args, Subcommand};
+use serde::{
+    de::{DeserializeOwned, IgnoredAny},
+    Deserialize,
+};
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+};
+use tgl_cli::{Client, TimeEntryUpdate};
+
+/// Time formats tried in order, covering 12- and 24-hour clocks.
+const TIME_FORMATS: &[&str] = &["%I:%M:%S %p", "%I:%M %p", "%H:%M:%S", "%H:%M"];
+
+#[derive(Subcommand)]
+pub enum ImportCommand {
+    /// Import a Clockify detailed report exported as CSV
+    Clockify(ClockifyArgs),
+    /// Import a Harvest detailed time report exported as CSV
+    Harvest(HarvestArgs),
+    /// Import Watson frames, from `watson log --json` or its frames file
+    Watson(JsonImportArgs),
+    /// Import timewarrior intervals from `timew export`
+    Timew(JsonImportArgs),
+}
+
+#[derive(Args)]
+pub struct ClockifyArgs {
+    /// CSV file exported from Clockify's detailed report
+    file: PathBuf,
+    /// Format of the dates in the file, as set in Clockify's preferences
+    #[arg(long, default_value = "%m/%d/%Y")]
+    date_format: String,
+    #[command(flatten)]
+    options: ImportOptions,
+}
+
+#[derive(Args)]
+pub struct HarvestArgs {
+    /// CSV file exported from Harvest's detailed time report
+    file: PathBuf,
+    /// TOML file that renames projects and clients, with `"Harvest name" =
+    /// "Toggl name"` pairs under `[projects]` and `[clients]`
+    #[arg(long)]
+    mapping: Option<PathBuf>,
+    #[command(flatten)]
+    options: ImportOptions,
+}
+
+#[derive(Args)]
+pub struct JsonImportArgs {
+    /// JSON file to import, or - to read standard input
+    file: PathBuf,
+    #[command(flatten)]
+    options: ImportOptions,
+}
+
+#[derive(Args)]
+struct ImportOptions {
+    /// Name of the workspace to import into, if you have several
+    #[arg(long)]
+    workspace: Option<String>,
+    /// Create missing projects without asking
+    #[arg(short, long)]
+    yes: bool,
+}
+
+/// A time entry read from another tool's export.
+#[derive(Debug, PartialEq)]
+struct ImportedEntry {
+    billable: Option<bool>,
+    client: Option<String>,
+    description: Option<String>,
+    project: Option<String>,
+    start: DateTime<Utc>,
+    stop: DateTime<Utc>,
+    tags: Vec<String>,
+}
+
+/// A row of Clockify's detailed report. Other columns are ignored.
+#[derive(Deserialize)]
+struct ClockifyRow {
+    #[serde(rename = "Project", default)]
+    project: String,
+    #[serde(rename = "Client", default)]
+    client: String,
+    #[serde(rename = "Description", default)]
+    description: String,
+    #[serde(rename = "Tags", default)]
+    tags: String,
+    #[serde(rename = "Billable", default)]
+    billable: String,
+    #[serde(rename = "Start Date")]
+    start_date: String,
+    #[serde(rename = "Start Time")]
+    start_time: String,
+    #[serde(rename = "End Date")]
+    end_date: String,
+    #[serde(rename = "End Time")]
+    end_time: String,
+}
+
+impl ClockifyRow {
+    fn into_entry(self, date_format: &str) -> Result<ImportedEntry> {
+        Ok(ImportedEntry {
+            billable: match self.billable.as_str() {
+                "Yes" => Some(true),
+                "No" => Some(false),
+                _ => None,
+            },
+            client: non_empty(self.client),
+            description: non_empty(self.description),
+            project: non_empty(self.project),
+            start: parse_local(&self.start_date, &self.start_time, date_format)?,
+            stop: parse_local(&self.end_date, &self.end_time, date_format)?,
+            tags: self
+                .tags
+                .split(',')
+                .map(str::trim)
+                .filter(|t| !t.is_empty())
+                .map(str::to_string)
+                .collect(),
+        })
+    }
+}
+
+/// A row of Harvest's detailed time report. Other columns are ignored.
+#[derive(Deserialize)]
+struct HarvestRow {
+    #[serde(rename = "Date")]
+    date: NaiveDate,
+    #[serde(rename = "Client", default)]
+    client: String,
+    #[serde(rename = "Project", default)]
+    project: String,
+    #[serde(rename = "Task", default)]
+    task: String,
+    #[serde(rename = "Notes", default)]
+    notes: String,
+    #[serde(rename = "Hours")]
+    hours: f64,
+    #[serde(rename = "Billable?", default)]
+    billable: String,
+}
+
+/// Renames Harvest projects and clients to their Toggl names.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct NameMapping {
+    projects: BTreeMap<String, String>,
+    clients: BTreeMap<String, String>,
+}
+
+/// A Watson frame, either as listed by `watson log --json` or as stored in
+/// Watson's frames file.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum WatsonFrame {
+    Log {
+        project: String,
+        start: DateTime<Utc>,
+        stop: DateTime<Utc>,
+        #[serde(default)]
+        tags: Vec<String>,
+    },
+    /// Start and stop timestamps, project, ID, tags, and update timestamp.
+    Stored(i64, i64, String, IgnoredAny, Vec<String>, IgnoredAny),
+}
+
+impl WatsonFrame {
+    fn into_entry(self) -> Result<ImportedEntry> {
+        let (project, start, stop, tags) = match self {
+            WatsonFrame::Log {
+                project,
+                start,
+                stop,
+                tags,
+            } => (project, start, stop, tags),
+            WatsonFrame::Stored(start, stop, project, _, tags, _) => {
+                let timestamp = |secs| {
+                    DateTime::from_timestamp(secs, 0)
+                        .ok_or_else(|| anyhow!("Invalid timestamp {secs}"))
+                };
+                (project, timestamp(start)?, timestamp(stop)?, tags)
+            }
+        };
+
+        Ok(ImportedEntry {
+            billable: None,
+            client: None,
+            description: None,
+            project: non_empty(project),
+            start,
+            stop,
+            tags,
+        })
+    }
+}
+
+/// An interval from `timew export`. Intervals without an end are still
+/// being tracked.
+#[derive(Deserialize)]
+struct TimewInterval {
+    start: String,
+    end: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    annotation: Option<String>,
+}
+
+impl TimewInterval {
+    fn into_entry(self) -> Result<Option<ImportedEntry>> {
+        let Some(end) = self.end else {
+            return Ok(None);
+        };
+        // timewarrior has no projects, so everything is kept as tags.
+        Ok(Some(ImportedEntry {
+            billable: None,
+            client: None,
+            description: self.annotation.and_then(non_empty),
+            project: None,
+            start: parse_timew(&self.start)?,
+            stop: parse_timew(&end)?,
+            tags: self.tags,
+        }))
+    }
+}
+
+/// Parses timewarrior's compact UTC timestamps, e.g. `20240603T090000Z`.
+fn parse_timew(value: &str) -> Result<DateTime<Utc>> {
+    let time = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%SZ")
+        .with_context(|| format!("Invalid timestamp '{value}'"))?;
+
+    Ok(time.and_utc())
+}
+
+/// Looks up a name in one of the [`NameMapping`] tables, keeping it if it
+/// isn't there.
+fn rename(names: &BTreeMap<String, String>, name: String) -> Option<String> {
+    let name = non_empty(name)?;
+    Some(names.get(&name).cloned().unwrap_or(name))
+}
+
+/// Converts Harvest rows to entries. Harvest only records hours, so each
+/// day's entries are laid out back to back from the start of the work day,
+/// in file order.
+fn harvest_entries(
+    rows: Vec<HarvestRow>,
+    mapping: &NameMapping,
+    work_hours: &WorkHours,
+) -> Result<Vec<ImportedEntry>> {
+    let mut day_ends: BTreeMap<NaiveDate, DateTime<Utc>> = BTreeMap::new();
+    let mut entries = Vec::new();
+    for row in rows {
+        let start = match day_ends.get(&row.date) {
+            Some(end) => *end,
+            None => local_to_utc(row.date.and_time(work_hours.start))?,
+        };
+        let stop = start + Duration::seconds((row.hours * 3600.0).round() as i64);
+        day_ends.insert(row.date, stop);
+        entries.push(ImportedEntry {
+            billable: match row.billable.as_str() {
+                "Yes" => Some(true),
+                "No" => Some(false),
+                _ => None,
+            },
+            client: rename(&mapping.clients, row.client),
+            description: non_empty(row.notes),
+            project: rename(&mapping.projects, row.project),
+            start,
+            stop,
+            tags: non_empty(row.task).into_iter().collect(),
+        });
+    }
+
+    Ok(entries)
+}
+
+fn non_empty(value: String) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// Parses a date and time in the local time zone, trying each of
+/// [`TIME_FORMATS`] for the time.
+fn parse_local(date: &str, time: &str, date_format: &str) -> Result<DateTime<Utc>> {
+    let date = NaiveDate::parse_from_str(date.trim(), date_format)
+        .with_context(|| format!("Invalid date '{date}', expected {date_format}"))?;
+    let time = TIME_FORMATS
+        .iter()
+        .find_map(|f| NaiveTime::parse_from_str(time.trim(), f).ok())
+        .ok_or_else(|| anyhow!("Invalid time '{time}'"))?;
+
+    local_to_utc(date.and_time(time))
+}
+
+fn local_to_utc(local: NaiveDateTime) -> Result<DateTime<Utc>> {
+    let local = Local
+        .from_local_datetime(&local)
+        .earliest()
+        .ok_or_else(|| anyhow!("{local} doesn't exist in the local time zone"))?;
+
+    Ok(local.with_timezone(&Utc))
+}
+
+/// Reads the rows of a CSV file with a header line.
+fn read_rows<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>> {
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+
+    csv::Reader::from_reader(file)
+        .deserialize()
+        .enumerate()
+        // Line 1 is the header.
+        .map(|(i, row)| row.with_context(|| format!("Failed to read line {}", i + 2)))
+        .collect()
+}
+
+/// Reads a JSON array from a file, or from standard input if the path is
+/// `-`.
+fn read_json<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>> {
+    let contents = if path == Path::new("-") {
+        io::read_to_string(io::stdin()).context("Failed to read standard input")?
+    } else {
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?
+    };
+
+    serde_json::from_str(&contents).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+pub fn run_import(config: &Config, command: &ImportCommand) -> Result<()> {
+    match command {
+        ImportCommand::Clockify(args) => {
+            let entries = read_rows::<ClockifyRow>(&args.file)?
+                .into_iter()
+                .map(|r| r.into_entry(&args.date_format))
+                .collect::<Result<_>>()?;
+
+            import_entries(&args.options, entries)
+        }
+        ImportCommand::Harvest(args) => {
+            let mapping = match &args.mapping {
+                Some(path) => {
+                    let contents = fs::read_to_string(path)
+                        .with_context(|| format!("Failed to read {}", path.display()))?;
+                    toml::from_str(&contents)
+                        .with_context(|| format!("Failed to parse {}", path.display()))?
+                }
+                None => NameMapping::default(),
+            };
+            let rows = read_rows(&args.file)?;
+            let entries = harvest_entries(rows, &mapping, &config.work_hours)?;
+
+            import_entries(&args.options, entries)
+        }
+        ImportCommand::Watson(args) => {
+            let entries = read_json::<WatsonFrame>(&args.file)?
+                .into_iter()
+                .map(WatsonFrame::into_entry)
+                .collect::<Result<_>>()?;
+
+            import_entries(&args.options, entries)
+        }
+        ImportCommand::Timew(args) => {
+            let mut entries = Vec::new();
+            for interval in read_json::<TimewInterval>(&args.file)? {
+                entries.extend(interval.into_entry()?);
+            }
+
+            import_entries(&args.options, entries)
+        }
+    }
+}
+
+fn import_entries(options: &ImportOptions, mut entries: Vec<ImportedEntry>) -> Result<()> {
+    let client = get_client()?;
+    let workspace = match find_workspaces(&client, options.workspace.as_deref())?.as_slice() {
+        [workspace] => workspace.id,
+        _ => bail!("You have several workspaces, pass --workspace"),
+    };
+
+    entries.sort_by_key(|e| e.start);
+
+    // Resolve each project once. `None` means the project was missing and
+    // not created, so its entries are skipped.
+    let mut project_ids: BTreeMap<&str, Option<i64>> = BTreeMap::new();
+    for entry in &entries {
+        let Some(name) = entry.project.as_deref() else {
+            continue;
+        };
+        if !project_ids.contains_key(name) {
+            let id = find_or_create_project(&client, workspace, name, entry, options.yes)?;
+            project_ids.insert(name, id);
+        }
+    }
+
+    let mut imported = 0;
+    for entry in &entries {
+        let project_id = match entry.project.as_deref() {
+            Some(name) => match project_ids[name] {
+                Some(id) => Some(id),
+                None => continue,
+            },
+            None => None,
+        };
+        let mut details = TimeEntryUpdate::new()
+            .project_id(project_id)
+            .tags(entry.tags.clone());
+        if let Some(description) = &entry.description {
+            details = details.description(description);
+        }
+        if let Some(billable) = entry.billable {
+            details = details.billable(billable);
+        }
+
+        let created = client
+            .add_detailed_time_entry(workspace, entry.start, entry.stop, &details)
+            .context("Failed to create time entry")?;
+        println!("{}", fmt_dated_entry(&created));
+        imported += 1;
+    }
+    journal::forget();
+
+    let skipped = entries.len() - imported;
+    if skipped > 0 {
+        println!("{}", decorate("✅", &format!("Imported {imported} entries, skipped {skipped} in projects that weren't created")));
+    } else {
+        println!(
+            "{}",
+            decorate("✅", &format!("Imported {imported} entries"))
+        );
+    }
+
+    Ok(())
+}
+
+/// Finds the project with the given name, or creates it after asking,
+/// linked to the entry's client if the workspace has one with that name.
+fn find_or_create_project(
+    client: &Client,
+    workspace_id: i64,
+    name: &str,
+    entry: &ImportedEntry,
+    yes: bool,
+) -> Result<Option<i64>> {
+    if let Some(project) = client
+        .find_project_by_name(workspace_id, name)
+        .context("Failed to get projects")?
+    {
+        return Ok(Some(project.id));
+    }
+
+    if !yes {
+        ensure_can_prompt(
+            &format!("confirmation to create project '{name}'"),
+            Some("Pass --yes to create missing projects"),
+        )?;
+        let confirmed = dialoguer::Confirm::new()
+            .with_prompt(format!("Project '{name}' doesn't exist. Create it?"))
+            .default(true)
+            .interact()
+            .context("Failed to read confirmation")?;
+        if !confirmed {
+            return Ok(None);
+        }
+    }
+
+    let client_id = match &entry.client {
+        Some(client_name) => client
+            .get_clients(workspace_id)
+            .context("Failed to get clients")?
+            .into_iter()
+            .find(|c| c.name.eq_ignore_ascii_case(client_name))
+            .map(|c| c.id),
+        None => None,
+    };
+    let project = client
+        .create_project(workspace_id, name, client_id)
+        .context("Failed to create project")?;
+    println!(
+        "{}",
+        decorate("📁", &format!("Created project {}", project.name))
+    );
+
+    Ok(Some(project.id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_watson_and_timew_json() {
+        let frames: Vec<WatsonFrame> = serde_json::from_str(
+            r#"[
+                [1717405200, 1717408800, "website", "abc", ["bug"], 1717408800],
+                {"id": "def", "project": "", "start": "2024-06-03T11:00:00+02:00",
+                 "stop": "2024-06-03T11:30:00+02:00", "tags": []}
+            ]"#,
+        )
+        .unwrap();
+        let entries: Vec<_> = frames
+            .into_iter()
+            .map(|f| f.into_entry().unwrap())
+            .collect();
+        assert_eq!(Some("website"), entries[0].project.as_deref());
+        assert_eq!(vec!["bug"], entries[0].tags);
+        assert_eq!(Duration::hours(1), entries[0].stop - entries[0].start);
+        assert_eq!(None, entries[1].project);
+        assert_eq!(Duration::minutes(30), entries[1].stop - entries[1].start);
+
+        let intervals: Vec<TimewInterval> = serde_json::from_str(
+            r#"[
+                {"id": 2, "start": "20240603T090000Z", "end": "20240603T094500Z",
+                 "tags": ["client", "review"], "annotation": "PR 12"},
+                {"id": 1, "start": "20240603T100000Z", "tags": ["running"]}
+            ]"#,
+        )
+        .unwrap();
+        let entries: Vec<_> = intervals
+            .into_iter()
+            .filter_map(|i| i.into_entry().unwrap())
+            .collect();
+        assert_eq!(1, entries.len());
+        assert_eq!(Some("PR 12"), entries[0].description.as_deref());
+        assert_eq!(vec!["client", "review"], entries[0].tags);
+        assert_eq!(Duration::minutes(45), entries[0].stop - entries[0].start);
+    }
+
+    #[test]
+    fn reads_harvest_rows() {
+        let csv = "\
+Date,Client,Project,Project Code,Task,Notes,Hours,Hours Rounded,Billable?
+2024-06-03,Acme Inc,Site,,Development,Login form,1.5,1.5,Yes
+2024-06-03,,Admin,,,,0.25,0.25,No
+";
+        let rows = csv::Reader::from_reader(csv.as_bytes())
+            .deserialize()
+            .collect::<Result<Vec<HarvestRow>, _>>()
+            .unwrap();
+        let mapping: NameMapping = toml::from_str(
+            r#"
+            [projects]
+            Site = "Website"
+            [clients]
+            "Acme Inc" = "Acme"
+            "#,
+        )
+        .unwrap();
+        let entries = harvest_entries(rows, &mapping, &WorkHours::default()).unwrap();
+
+        assert_eq!(Some("Website"), entries[0].project.as_deref());
+        assert_eq!(Some("Acme"), entries[0].client.as_deref());
+        assert_eq!(Some("Login form"), entries[0].description.as_deref());
+        assert_eq!(vec!["Development"], entries[0].tags);
+        assert_eq!(Some(true), entries[0].billable);
+        assert_eq!(
+            "09:00",
+            entries[0]
+                .start
+                .with_timezone(&Local)
+                .format("%H:%M")
+                .to_string()
+        );
+        assert_eq!(entries[0].stop, entries[1].start);
+        assert_eq!(Some("Admin"), entries[1].project.as_deref());
+        assert_eq!(Duration::minutes(15), entries[1].stop - entries[1].start);
+    }
+
+    #[test]
+    fn reads_clockify_rows() {
+        let csv = "\
+Project,Client,Description,Task,User,Tags,Billable,Start Date,Start Time,End Date,End Time,Duration (h)
+Website,Acme,\"Fix login, again\",,Sam,\"bug, urgent\",Yes,06/03/2024,09:00:00 AM,06/03/2024,01:30:00 PM,04:30:00
+,,Email,,Sam,,No,06/04/2024,16:00,06/04/2024,16:15,00:15:00
+";
+        let entries: Vec<_> = csv::Reader::from_reader(csv.as_bytes())
+            .deserialize::<ClockifyRow>()
+            .map(|r| r.unwrap().into_entry("%m/%d/%Y").unwrap())
+            .collect();
+
+        assert_eq!(Some("Website"), entries[0].project.as_deref());
+        assert_eq!(Some("Acme"), entries[0].client.as_deref());
+        assert_eq!(Some("Fix login, again"), entries[0].description.as_deref());
+        assert_eq!(vec!["bug", "urgent"], entries[0].tags);
+        assert_eq!(Some(true), entries[0].billable);
+        assert_eq!(
+            chrono::Duration::minutes(270),
+            entries[0].stop - entries[0].start
+        );
+        assert_eq!(None, entries[1].project);
+        assert_eq!(Some(false), entries[1].billable);
+        assert_eq!(
+            chrono::Duration::minutes(15),
+            entries[1].stop - entries[1].start
+        );
+    }
+}
@@ -0,0 +1,148 @@
+//! `tgl status --interactive`: today's entries as a list that single keys act
+//! on, short of a full-screen interface.
+
+use crate::{
+    config::Config,
+    decorate, ensure_can_prompt,
+    entry::{delete_entry, edit_entry},
+    fmt_entry_line, get_client, get_todays_entries, name_clients, report_change, restart_entry,
+    segments::{self, Row},
+    stop_timer, StatusArgs,
+};
+use anyhow::{Context, Result};
+use dialoguer::console::{style, Key, Term};
+
+const HELP: &str = "↑/↓ select · s stop · r restart · e edit · d delete · q quit";
+
+/// Finds the row showing the entry with `id`, or the latest row.
+fn reselect(rows: &[Row], id: Option<i64>) -> usize {
+    id.and_then(|id| rows.iter().position(|row| row.entry.id == id))
+        .unwrap_or(rows.len().saturating_sub(1))
+}
+
+pub fn run_interactive(config: &Config, args: &StatusArgs) -> Result<()> {
+    ensure_can_prompt("a key", Some("Drop --interactive to only list entries"))?;
+    let client = get_client()?;
+    let term = Term::stderr();
+    let mut selected_id = None;
+    let mut message = None;
+    loop {
+        let mut entries = get_todays_entries(&client)?;
+        name_clients(config, &client, &mut entries)?;
+        let mut rows = segments::rows(&entries);
+        rows.retain(|row| args.matches(&row.entry));
+        let mut selected = reselect(&rows, selected_id);
+
+        // Moving the selection only redraws the same rows, until an action
+        // changes them.
+        let (key, selected) = loop {
+            let mut lines: Vec<_> = rows
+                .iter()
+                .enumerate()
+                .map(|(i, row)| {
+                    let line = fmt_entry_line(config, Some(row.index), &row.entry, false);
+                    match i == selected {
+                        true => format!("{} {}", style(">").bold(), style(line).bold()),
+                        false => format!("  {line}"),
+                    }
+                })
+                .collect();
+            if rows.is_empty() {
+                lines.push(decorate("🤷", "No entries today"));
+            }
+            lines.push(String::new());
+            lines.push(message.take().unwrap_or_else(|| HELP.to_string()));
+            for line in &lines {
+                term.write_line(line)?;
+            }
+            let key = term.read_key().context("Failed to read key")?;
+            term.clear_last_lines(lines.len())?;
+            match key {
+                Key::ArrowUp | Key::Char('k') => selected = selected.saturating_sub(1),
+                Key::ArrowDown | Key::Char('j') => {
+                    selected = (selected + 1).min(rows.len().saturating_sub(1))
+                }
+                Key::Char('s' | 'r' | 'e' | 'd' | 'q') | Key::Escape => {
+                    break (key, rows.get(selected))
+                }
+                _ => {}
+            }
+        };
+        let Some(Row { entry, .. }) = selected else {
+            match key {
+                Key::Char('q') | Key::Escape => break,
+                _ => continue,
+            }
+        };
+        selected_id = Some(entry.id);
+
+        let done = |message: &str| Some(decorate("✅", message));
+        let result = match key {
+            Key::Char('q') | Key::Escape => break,
+            Key::Char('s') if !entry.is_running => {
+                Ok(Some(decorate("🤷", "That entry isn't running")))
+            }
+            Key::Char('s') => stop_timer(&client).map(|_| done("Stopped")),
+            Key::Char('r') => restart_entry(&client, entry).map(|started| {
+                selected_id = Some(started.id);
+                done("Started")
+            }),
+            Key::Char('e') => edit_entry(&client, entry).map(|updated| match updated {
+                Some(_) => done("Updated"),
+                None => Some(decorate("🤷", "Nothing to change")),
+            }),
+            Key::Char('d') => confirm_delete(&term).and_then(|confirmed| match confirmed {
+                true => delete_entry(&client, entry).map(|_| done("Deleted")),
+                false => Ok(None),
+            }),
+            _ => Ok(None),
+        };
+        // Failed actions, such as edits in a locked week, leave the list open
+        // for the next one.
+        message = result.unwrap_or_else(|err| Some(format!("{err:#}")));
+    }
+
+    report_change(config, &client, None)
+}
+
+fn confirm_delete(term: &Term) -> Result<bool> {
+    dialoguer::Confirm::new()
+        .with_prompt("Delete this entry?")
+        .default(false)
+        .report(false)
+        .interact_on(term)
+        .context("Failed to read confirmation")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_selected_entry() {
+        let entry = |id: i64| -> tgl_cli::TimeEntry {
+            serde_json::from_value(serde_json::json!({
+                "billable": false,
+                "client_id": null,
+                "description": null,
+                "duration": 600,
+                "id": id,
+                "is_running": false,
+                "project_id": null,
+                "project_name": null,
+                "start": "2024-06-03T09:00:00Z",
+                "stop": null,
+                "tags": [],
+                "task_id": null,
+                "workspace_id": 1,
+            }))
+            .unwrap()
+        };
+        let rows = segments::rows(&[entry(1), entry(2), entry(3)]);
+
+        assert_eq!(1, reselect(&rows, Some(2)));
+        assert_eq!(2, reselect(&rows, Some(4)));
+        assert_eq!(2, reselect(&rows, None));
+        assert_eq!(0, reselect(&[], None));
+    }
+}
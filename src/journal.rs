@@ -0,0 +1,119 @@
+//! A record of the last change tgl made to your time entries, so that
+//! `tgl undo` can reverse it.
+
+use crate::{
+    cache_path, clear_prompt_cache, config::Config, decorate, get_client, is_dry_run, is_quiet,
+    report_change,
+};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use tgl_cli::{Client, TimeEntry, TimeEntryUpdate};
+
+const JOURNAL_FILE: &str = "journal.json";
+
+/// A change that `tgl undo` knows how to reverse.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Action {
+    /// A new entry was started.
+    Started { entry: TimeEntry },
+    /// A running entry was stopped.
+    Stopped { entry: TimeEntry },
+    /// An entry was deleted.
+    Deleted { entry: TimeEntry },
+}
+
+impl Action {
+    fn undo(&self, client: &Client) -> Result<()> {
+        match self {
+            Action::Started { entry } => client
+                .delete_time_entry(entry)
+                .context("Failed to delete time entry")?,
+            Action::Stopped { entry } => {
+                client
+                    .resume_time_entry(entry)
+                    .context("Failed to restart time entry")?;
+            }
+            Action::Deleted { entry } => {
+                client
+                    .copy_time_entry(entry, &TimeEntryUpdate::new())
+                    .context("Failed to recreate time entry")?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn describe(&self) -> String {
+        let (verb, entry) = match self {
+            Action::Started { entry } => ("Deleted the entry started for", entry),
+            Action::Stopped { entry } => ("Restarted", entry),
+            Action::Deleted { entry } => ("Recreated", entry),
+        };
+
+        format!(
+            "{verb} {} [{}]",
+            entry.description.as_deref().unwrap_or("(no description)"),
+            entry.project_name.as_deref().unwrap_or_default()
+        )
+    }
+}
+
+/// Replaces the journal with the actions of the latest command, in the
+/// order they happened.
+pub fn record(actions: Vec<Action>) {
+    // Losing the journal only means `tgl undo` can't help, so don't fail
+    // the command over it.
+    if is_dry_run() {
+        return;
+    }
+    if let Some(path) = cache_path(JOURNAL_FILE) {
+        if let Some(dir) = path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        if let Ok(contents) = serde_json::to_vec(&actions) {
+            let _ = fs::write(path, contents);
+        }
+    }
+}
+
+/// Empties the journal after a change that can't be undone, so that `tgl
+/// undo` doesn't reverse an older one instead.
+pub fn forget() {
+    if is_dry_run() {
+        return;
+    }
+    if let Some(path) = cache_path(JOURNAL_FILE) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+fn read() -> Option<Vec<Action>> {
+    let contents = fs::read(cache_path(JOURNAL_FILE)?).ok()?;
+    serde_json::from_slice(&contents).ok()
+}
+
+pub fn run_undo(config: &Config) -> Result<()> {
+    let client = get_client()?;
+    let Some(actions) = read().filter(|a| !a.is_empty()) else {
+        if is_quiet() {
+            println!("Nothing to undo");
+        } else {
+            println!("{}", decorate("🤷", "Nothing to undo\n"));
+        }
+        return report_change(config, &client, None);
+    };
+
+    for action in actions.iter().rev() {
+        action.undo(&client)?;
+        println!("{}", decorate("↩️ ", &action.describe()));
+    }
+    forget();
+    clear_prompt_cache();
+    if !is_quiet() {
+        println!();
+    }
+
+    report_change(config, &client, None)
+}
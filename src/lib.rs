@@ -1,2 +1,47 @@
-pub mod api;
-pub mod svc;
+//! Library behind the `tgl` command line client for [Toggl](https://toggl.com/track/).
+//!
+//! Everything needed to talk to Toggl is re-exported from the crate root.
+//! The modules that implement it are private so that they can be
+//! reorganized freely.
+//!
+//! The `cli` feature, on by default, only builds the `tgl` binary. Turn off
+//! default features to embed the client without its terminal and keyring
+//! dependencies:
+//!
+//! ```toml
+//! tgl-cli = { version = "0.19", default-features = false }
+//! ```
+//!
+//! The `mirror` feature, which `cli` turns on, adds `Client::with_store`
+//! for reading from a local SQLite copy of Toggl when it can't be reached.
+//! It builds SQLite from source, so leave it off unless you need it.
+//!
+//! # Stability
+//!
+//! This crate follows semantic versioning for the items re-exported here.
+//! Structs and enums that mirror Toggl data are `#[non_exhaustive]`, so new
+//! Toggl fields and new error variants can be added in minor releases.
+//! Match on [`Error`] with a wildcard arm and read model fields rather than
+//! constructing models yourself.
+
+pub(crate) mod api;
+pub(crate) mod cache;
+pub(crate) mod cassette;
+pub(crate) mod clockify;
+pub(crate) mod demo;
+pub(crate) mod harvest;
+#[cfg(feature = "mirror")]
+pub(crate) mod store;
+pub(crate) mod svc;
+pub(crate) mod tracker;
+
+pub use api::{Connection, PlannedRequest, Progress, Timeouts};
+pub use cassette::Cassette;
+pub use clockify::ClockifyClient;
+pub use demo::DemoTracker;
+pub use harvest::HarvestClient;
+pub use svc::{
+    BulkUpdate, BulkUpdateResult, Client, Error, Favorite, Project, StartEntry, TimeEntry,
+    TimeEntryUpdate, WebhookSubscription, Workspace, WorkspaceClient,
+};
+pub use tracker::TimeTracker;
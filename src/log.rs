@@ -0,0 +1,139 @@
+//! A chronological listing of time entries, the counterpart to the totals of
+//! `tgl report`.
+
+use crate::{
+    config::Config, eprintln_stale_banner, fmt_entry_line, get_client, i18n, name_clients,
+    render_row, report::get_range_entries, template::Template, when::parse_date, TemplateRow,
+};
+use anyhow::{bail, Result};
+use chrono::{DateTime, Days, Local, NaiveDate};
+use clap::Args;
+use tgl_cli::TimeEntry;
+
+/// How far back the log goes without --since.
+const LOG_DAYS: u64 = 7;
+
+#[derive(Args)]
+pub struct LogArgs {
+    /// First day to list, e.g. 2024-06-03 or "last monday" [default: a week
+    /// ago]
+    #[arg(long, value_parser = parse_date)]
+    since: Option<NaiveDate>,
+    /// Last day to list, e.g. yesterday [default: today]
+    #[arg(long, value_parser = parse_date)]
+    until: Option<NaiveDate>,
+    /// Only list entries in this project, ignoring case
+    #[arg(long, value_name = "NAME")]
+    project: Option<String>,
+    /// Print each entry using a template instead of the default layout,
+    /// without day headings, e.g. "{start} {duration} {project}: {description}"
+    #[arg(long, value_name = "TEMPLATE")]
+    format: Option<Template>,
+}
+
+/// Groups entries by the local day they started on, newest day and entry
+/// first.
+fn by_day(entries: &[TimeEntry]) -> Vec<(NaiveDate, Vec<&TimeEntry>)> {
+    let mut sorted: Vec<_> = entries.iter().filter(|e| e.start.is_some()).collect();
+    sorted.sort_by_key(|e| std::cmp::Reverse(e.start));
+
+    let mut days: Vec<(NaiveDate, Vec<&TimeEntry>)> = Vec::new();
+    for entry in sorted {
+        let date = entry
+            .start
+            .map(|s| DateTime::<Local>::from(s).date_naive())
+            .unwrap_or_default();
+        match days.last_mut() {
+            Some((day, entries)) if *day == date => entries.push(entry),
+            _ => days.push((date, vec![entry])),
+        }
+    }
+
+    days
+}
+
+pub fn run_log(config: &Config, args: &LogArgs) -> Result<()> {
+    let today = Local::now().date_naive();
+    let until = args.until.unwrap_or(today);
+    let since = args.since.unwrap_or(today - Days::new(LOG_DAYS));
+    if since > until {
+        bail!("--since must not be after --until");
+    }
+
+    let client = get_client()?;
+    let mut entries = get_range_entries(&client, since, until)?;
+    eprintln_stale_banner(&client);
+    if let Some(project) = &args.project {
+        entries.retain(|e| {
+            e.project_name
+                .as_ref()
+                .is_some_and(|name| name.eq_ignore_ascii_case(project))
+        });
+    }
+    name_clients(config, &client, &mut entries)?;
+
+    for (i, (date, entries)) in by_day(&entries).into_iter().enumerate() {
+        if let Some(template) = &args.format {
+            for entry in entries {
+                let row = TemplateRow::from_entry(entry);
+                println!("{}", render_row(config, &client, template, &row)?);
+            }
+            continue;
+        }
+
+        if i > 0 {
+            println!();
+        }
+        println!("{}", i18n::fmt_date(date, "day-heading-format"));
+        for entry in entries {
+            println!("{}", fmt_entry_line(config, None, entry, false));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn groups_newest_first() {
+        let entry = |id: i64, day: u32, hour: u32| -> TimeEntry {
+            let start = Local
+                .with_ymd_and_hms(2024, 6, day, hour, 0, 0)
+                .unwrap()
+                .with_timezone(&Utc);
+            serde_json::from_value(serde_json::json!({
+                "billable": false,
+                "client_id": null,
+                "description": null,
+                "duration": 600,
+                "id": id,
+                "is_running": false,
+                "project_id": null,
+                "project_name": null,
+                "start": start,
+                "stop": null,
+                "tags": [],
+                "task_id": null,
+                "workspace_id": 1,
+            }))
+            .unwrap()
+        };
+        let entries = [entry(1, 3, 9), entry(2, 4, 9), entry(3, 3, 14)];
+
+        let days: Vec<_> = by_day(&entries)
+            .into_iter()
+            .map(|(date, entries)| (date.to_string(), entries.iter().map(|e| e.id).collect()))
+            .collect();
+        assert_eq!(
+            vec![
+                ("2024-06-04".to_string(), vec![2]),
+                ("2024-06-03".to_string(), vec![3, 1]),
+            ],
+            days
+        );
+    }
+}
@@ -1,186 +1,1506 @@
+mod auth;
+mod backend;
+mod budget;
+mod bulk;
+mod chart;
+mod check;
+mod clients;
+mod config;
+mod daemon;
+mod duration;
+mod entry;
+mod history;
+mod hooks;
+mod http_api;
+mod i18n;
+mod idle;
+mod import;
+mod interactive;
+mod journal;
+mod log;
+mod mqtt;
+mod nudge;
+mod pager;
+mod project;
+mod report;
+mod restore;
+mod review;
+mod rounding;
+mod search;
+mod segments;
+mod service;
+mod spinner;
+mod suggest;
+mod tag;
+mod targets;
+mod template;
+mod theme;
+mod webhook;
+mod when;
+mod window;
+
 use anyhow::{anyhow, bail, Context, Result};
-use chrono::{DateTime, Datelike, Days, Duration, Local, TimeZone, Utc};
-use clap::{Parser, Subcommand};
+use auth::{run_auth, AuthCommand};
+use backend::get_tracker;
+use budget::{budget_warning, run_budget};
+use bulk::{run_bulk, run_merge, run_move, BulkArgs, MergeArgs, MoveArgs};
+use chart::{run_heatmap, run_week, HeatmapArgs, WeekArgs};
+use check::{run_check, CheckCommand};
+use chrono::{DateTime, Datelike, Days, Duration, Local, NaiveDate, TimeZone, Utc};
+use clap::{ArgAction, Args, Parser, Subcommand};
+use clients::{run_client, ClientCommand};
+use config::{Backend, Config, DurationFormat, EntryTemplate, HooksConfig};
+use daemon::{run_daemon, DaemonArgs};
 use dialoguer::theme::Theme;
-use std::env;
-use tgl_cli::svc::{Client, TimeEntry};
+use duration::{fmt_duration, fmt_duration_decimal};
+use entry::{
+    run_adjust, run_delete, run_edit, run_rename, run_split, AdjustArgs, DeleteArgs, EditArgs,
+    EntryArgs, RenameArgs, SplitArgs,
+};
+use history::RecentDescriptions;
+use idle::{run_idle, IdleArgs};
+use import::{run_import, ImportCommand};
+use interactive::run_interactive;
+use journal::{run_undo, Action};
+use log::{run_log, LogArgs};
+use nudge::run_nudge;
+use project::{run_project, ProjectCommand};
+use report::{
+    get_range_entries, run_balance, run_export, run_overtime, run_report, BalanceArgs, ExportArgs,
+    ReportArgs,
+};
+use restore::{run_restore, RestoreArgs};
+use review::run_yesterday;
+use rounding::Interval;
+use search::{run_search, SearchArgs};
+use segments::Row;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    env, fs,
+    io::{self, IsTerminal},
+    path::PathBuf,
+    process::{self, Stdio},
+    sync::OnceLock,
+};
+use suggest::{run_suggest, SuggestArgs};
+use tag::{run_tag, TagCommand};
+use targets::{run_targets, week_forecast};
+use template::{Field, Template};
+use tgl_cli::{
+    Cassette, Client, Connection, Favorite, TimeEntry, TimeTracker, Timeouts, Workspace,
+};
+use theme::{ColorChoice, Icons};
+use tracing_subscriber::EnvFilter;
+use when::parse_date;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
     #[command(subcommand)]
     command: Option<Command>,
+    /// How to render durations [default: from config, or clock]
+    #[arg(long, global = true, value_enum)]
+    duration_format: Option<DurationFormat>,
+    /// When to use colors [default: from config, or auto]
+    #[arg(long, global = true, value_enum)]
+    color: Option<ColorChoice>,
+    /// Print the changes a command would make in Toggl instead of making them
+    #[arg(long, global = true)]
+    dry_run: bool,
+    /// Refetch workspaces and projects instead of using the cached ones
+    #[arg(long, global = true)]
+    refresh: bool,
+    /// Log API calls to stderr; repeat (-vv) for more detail [default: from TGL_LOG, or off]
+    #[arg(short, long, global = true, action = ArgAction::Count)]
+    verbose: u8,
+    /// Print only essential results, without emoji, prompt summaries, or the
+    /// status that follows changes
+    #[arg(short, long, global = true)]
+    quiet: bool,
+    /// Fail instead of prompting when information is missing, for editors,
+    /// hooks, and cron jobs
+    #[arg(long, global = true)]
+    no_input: bool,
+    /// Print long listings directly instead of through $PAGER
+    #[arg(long, global = true)]
+    no_pager: bool,
+    /// How long to wait on each request to Toggl, e.g. 10s [default: from config, or 30s]
+    #[arg(long, global = true, value_name = "INTERVAL")]
+    timeout: Option<Interval>,
+    /// Keep the API token in the keyring under this profile, apart from other
+    /// accounts' [default: from TGL_PROFILE, or default]
+    #[arg(long, global = true, value_name = "NAME")]
+    profile: Option<String>,
+    /// Use made-up sample data instead of a real account, without a token or
+    /// network access. Works with status, current, start, stop, and prompt,
+    /// and changes are forgotten when the command ends
+    #[arg(long, global = true)]
+    demo: bool,
 }
 
 #[derive(Subcommand)]
 enum Command {
     /// Get the current status of Toggl timers for today
-    Status,
+    Status(StatusArgs),
+    /// Review yesterday's entries against your daily target
+    Yesterday,
     /// Start a new time entry
-    Start,
+    Start(StartArgs),
     /// Stop the current time entry
     Stop,
-    /// Restart the latest time entry
-    Restart,
-    /// Delete the Toggl API token saved in the keyring/keychain
+    /// Stop the current time entry and start a new one in its place
+    Switch(StartArgs),
+    /// Restart the latest time entry, or the given one
+    Restart(RestartArgs),
+    /// Edit a time entry
+    Edit(EditArgs),
+    /// Split a time entry in two at a point in time
+    Split(SplitArgs),
+    /// Move the running time entry's start, e.g. when the timer was started
+    /// late
+    Adjust(AdjustArgs),
+    /// Change the running time entry's description or project, keeping its
+    /// start
+    Rename(RenameArgs),
+    /// Tag the running time entry, or the given one
+    Tag {
+        #[command(subcommand)]
+        command: TagCommand,
+    },
+    /// Delete a time entry
+    Delete(DeleteArgs),
+    /// Change all time entries matching a filter at once
+    Bulk(BulkArgs),
+    /// Move the time entries in a date range from one project to another
+    Move(MoveArgs),
+    /// Merge consecutive entries with the same project and description
+    Merge(MergeArgs),
+    /// Pick any recent time entry and start it again
+    Continue(ContinueArgs),
+    /// Undo the last start, stop, switch, restart, or delete
+    Undo,
+    /// Delete the Toggl API token saved in the keyring/keychain for the
+    /// profile
     DeleteApiToken,
+    /// Save an API token without prompting, e.g. in CI or setup scripts
+    Auth {
+        #[command(subcommand)]
+        command: AuthCommand,
+    },
+    /// Work with the favorites saved in Toggl
+    Favorites {
+        #[command(subcommand)]
+        command: FavoritesCommand,
+    },
+    /// Manage your Toggl projects
+    Project {
+        #[command(subcommand)]
+        command: ProjectCommand,
+    },
+    /// Manage your Toggl clients (customers)
+    Client {
+        #[command(subcommand)]
+        command: ClientCommand,
+    },
+    /// Print a compact one-line summary of the running timer for shell prompts
+    Prompt(PromptArgs),
+    /// Show only the running time entry
+    Current(CurrentArgs),
+
+    /// Summarize time logged per project and description over a date range
+    Report(ReportArgs),
+    /// List time entries newest first, under a heading for each day
+    Log(LogArgs),
+    /// Find past time entries whose descriptions match a pattern
+    Search(SearchArgs),
+    /// Compare tracked time with your working days' target since a date
+    Balance(BalanceArgs),
+    /// Show each day's time over or under the daily target, and the running
+    /// total, for banking extra hours. Time on days off is all overtime
+    Overtime(BalanceArgs),
+    /// Export the time entries in a date range
+    Export(ExportArgs),
+    /// Recreate time entries from a backup file, skipping ones that exist
+    Restore(RestoreArgs),
+    /// Import time entries exported from another time tracker
+    Import {
+        #[command(subcommand)]
+        command: ImportCommand,
+    },
+    /// Offer to add entries for today's meetings from a calendar
+    Suggest(SuggestArgs),
+    /// Show how much time you tracked each day this week
+    Week(WeekArgs),
+    /// Show a calendar of how much time you tracked each day
+    Heatmap(HeatmapArgs),
+    /// Show how much of their estimates your projects have used
+    Budget,
+    /// Show this week's progress toward each project's target from
+    /// `[targets]`
+    Targets,
+    /// Remind you to start the timer, take a break, or stop for the day, as
+    /// set up under `[nudge]`; meant to run every few minutes from cron
+    Nudge,
+    /// Poll Toggl in the background, so that `tgl prompt` and `tgl current`
+    /// answer without the network, and give `[nudge]` reminders as they
+    /// come up
+    Daemon(DaemonArgs),
+    /// Take the time you were away from the computer, as noticed by `tgl
+    /// daemon` under `[idle]`, out of the running entry, or stop it when you
+    /// left
+    Idle(IdleArgs),
+    /// Look for problems in your time entries
+    Check {
+        #[command(subcommand)]
+        command: CheckCommand,
+    },
+}
+
+impl Command {
+    /// Whether the command can print more than fits on a screen, and so
+    /// goes through the pager.
+    fn is_listing(&self) -> bool {
+        match self {
+            Command::Report(_) | Command::Log(_) | Command::Search(_) => true,
+            Command::Status(args) => args.from.is_some(),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum FavoritesCommand {
+    /// List your Toggl favorites
+    List,
+}
+
+#[derive(Args)]
+struct StartArgs {
+    /// Name of a template from the config file to start
+    #[arg(conflicts_with_all = ["favorite", "template"])]
+    name: Option<String>,
+    /// Start from a template in the config file
+    #[arg(long, conflicts_with = "favorite")]
+    template: Option<String>,
+    /// Start from one of your Toggl favorites, including its tags and
+    /// billable flag
+    #[arg(long)]
+    favorite: bool,
+}
+
+#[derive(Args)]
+struct PromptArgs {
+    /// Fetch the running timer into the cache without printing it, which
+    /// `tgl prompt` runs in the background when its cache is out of date
+    #[arg(long, hide = true)]
+    refresh: bool,
+}
+
+#[derive(Args)]
+struct CurrentArgs {
+    /// Print the entry using a template instead of the default layout, e.g.
+    /// "{duration} {project}: {description}"
+    #[arg(long, value_name = "TEMPLATE")]
+    format: Option<Template>,
+}
+
+#[derive(Args, Default)]
+struct StatusArgs {
+    /// Print each entry using a template instead of the default layout,
+    /// e.g. "{duration} {project}: {description}"
+    #[arg(long, value_name = "TEMPLATE")]
+    format: Option<Template>,
+    /// Show each entry's Toggl ID after its index
+    #[arg(long)]
+    ids: bool,
+    /// Show one line per project, with its total, how many entries it has,
+    /// and its latest description
+    #[arg(long, conflicts_with_all = ["format", "ids"])]
+    group: bool,
+    /// Merge consecutive entries with the same project and description into
+    /// one row, marked with how many there are (e.g. ×3)
+    #[arg(long, conflicts_with_all = ["format", "group"])]
+    collapse: bool,
+    /// Show this day instead of today, e.g. 2024-06-03 or yesterday
+    #[arg(long, value_parser = parse_date, conflicts_with_all = ["from", "to"])]
+    date: Option<NaiveDate>,
+    /// Show the days from this one on instead of today, e.g. "last monday"
+    #[arg(long, value_parser = parse_date)]
+    from: Option<NaiveDate>,
+    /// Last day to show with --from [default: today]
+    #[arg(long, value_parser = parse_date, requires = "from")]
+    to: Option<NaiveDate>,
+    /// Only show entries in this project, ignoring case
+    #[arg(long, value_name = "NAME")]
+    project: Option<String>,
+    /// Only show entries with this tag, ignoring case
+    #[arg(long)]
+    tag: Option<String>,
+    /// Pick one of today's entries with the arrow keys and act on it: s to
+    /// stop, r to restart, e to edit, d to delete, q to quit
+    #[arg(long, conflicts_with_all = ["format", "group", "collapse", "date", "from"])]
+    interactive: bool,
+}
+
+impl StatusArgs {
+    fn is_filtered(&self) -> bool {
+        self.project.is_some() || self.tag.is_some()
+    }
+
+    fn matches(&self, entry: &TimeEntry) -> bool {
+        let project_matches = self.project.as_ref().is_none_or(|project| {
+            entry
+                .project_name
+                .as_ref()
+                .is_some_and(|name| name.eq_ignore_ascii_case(project))
+        });
+        let tag_matches = self
+            .tag
+            .as_ref()
+            .is_none_or(|tag| entry.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)));
+
+        project_matches && tag_matches
+    }
+}
+
+#[derive(Args)]
+struct RestartArgs {
+    #[command(flatten)]
+    entry: EntryArgs,
+    /// Choose from the last few distinct entries instead of the latest one
+    #[arg(long, conflicts_with_all = ["index", "id"])]
+    select: bool,
+    /// How many days of history to search [default: from config, or Toggl's default]
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..=3650))]
+    days: Option<u32>,
+    /// The most entries to search, newest first [default: from config, or no limit]
+    #[arg(long)]
+    limit: Option<usize>,
+}
+
+#[derive(Args)]
+struct ContinueArgs {
+    /// How many days of history to search [default: from config, or 14]
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..=3650))]
+    days: Option<u32>,
+    /// The most entries to search, newest first [default: from config, or no limit]
+    #[arg(long)]
+    limit: Option<usize>,
 }
 
+/// How many days of history `tgl continue` searches by default.
+const CONTINUE_DAYS: u32 = 14;
+
+/// How many distinct entries `tgl restart --select` offers.
+const RESTART_SELECT_COUNT: usize = 10;
+
+/// How long `tgl prompt` trusts its cached view of the running timer.
+const PROMPT_CACHE_TTL_SECS: i64 = 30;
+
+/// How long `tgl prompt` waits before asking Toggl again after it failed to.
+const PROMPT_RETRY_SECS: i64 = 60;
+
+/// How long `tgl prompt` waits on Toggl when the cache is out of date.
+const PROMPT_TIMEOUT: Duration = Duration::seconds(2);
+
+/// How long a background refresh of the prompt cache may hold its lock
+/// before other prompts assume it died and start their own.
+const PROMPT_LOCK_SECS: u64 = 10;
+
+/// Cache file for workspaces and projects, under [`cache_path`].
+const PROJECT_CACHE_FILE: &str = "projects.json";
+
+/// Lock file held by a background refresh of the prompt cache, under
+/// [`cache_path`].
+const PROMPT_LOCK_FILE: &str = "prompt.lock";
+
+/// SQLite mirror of recent data for offline reads, under [`cache_path`].
+const STORE_FILE: &str = "store.sqlite3";
+
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    init_logging(cli.verbose);
+    ctrlc::set_handler(|| abort()).context("Failed to install Ctrl-C handler")?;
+    let mut config = config::load()?;
+    if let Some(duration_format) = cli.duration_format {
+        config.duration_format = duration_format;
+    }
+    if let Some(color) = cli.color {
+        config.color = color;
+    }
+    if cli.demo {
+        config.backend = Backend::Demo;
+    }
+    config.color.apply();
+    i18n::init(config.locale.as_deref());
+    // Decided before the pager takes over stdout.
+    let stdout_is_terminal = io::stdout().is_terminal();
+    let pager = cli
+        .command
+        .as_ref()
+        .is_some_and(Command::is_listing)
+        .then(|| pager::start(&config, cli.no_pager))
+        .flatten();
+    let _ = CLIENT_OPTIONS.set(ClientOptions {
+        cache_ttl: if cli.refresh {
+            Duration::zero()
+        } else {
+            config.cache.ttl.duration()
+        },
+        dry_run: cli.dry_run,
+        store: config.store.enabled,
+        // Keep the spinner out of output that's piped or meant for other
+        // programs.
+        spinner: stdout_is_terminal
+            && io::stderr().is_terminal()
+            && !cli.quiet
+            && pager.is_none()
+            && !matches!(cli.command, Some(Command::Prompt(_)))
+            && !matches!(&cli.command, Some(Command::Export(args)) if args.is_json()),
+        quiet: cli.quiet,
+        // Prompts draw on stderr and read keys from stdin, so piping stdout
+        // alone, as in `tgl status | grep`, doesn't rule them out.
+        no_input: cli.no_input || !(io::stdin().is_terminal() && io::stderr().is_terminal()),
+        plain: !stdout_is_terminal,
+        icons: config.icons,
+        latest_days: config.history.days,
+        latest_limit: config.history.limit,
+        hooks: config.hooks.clone(),
+        connection: connection(&config, cli.timeout)?,
+        cassette: cassette()?,
+        profile: cli
+            .profile
+            .clone()
+            .or_else(|| env::var("TGL_PROFILE").ok())
+            .filter(|profile| !profile.is_empty()),
+    });
 
-    match &cli.command {
-        Some(Command::Status) => run_status(),
-        Some(Command::Start) => run_start(),
-        Some(Command::Stop) => run_stop(),
-        Some(Command::Restart) => run_restart(),
+    let result = match &cli.command {
+        Some(Command::Status(args)) => run_status(&config, args),
+        Some(Command::Start(args)) => run_start(&config, args),
+        Some(Command::Stop) => run_stop(&config),
+        Some(Command::Prompt(args)) => run_prompt(&config, args),
+        Some(Command::Current(args)) => run_current(&config, args),
+        None => run_status(&config, &StatusArgs::default()),
+        _ if config.backend != Backend::Toggl => Err(anyhow!(
+            "This command needs Toggl, but the backend is {}",
+            config.backend.name()
+        )),
+        Some(Command::Yesterday) => run_yesterday(&config),
+        Some(Command::Switch(args)) => run_switch(&config, args),
+        Some(Command::Restart(args)) => run_restart(&config, args),
+        Some(Command::Continue(args)) => run_continue(&config, args),
+        Some(Command::Edit(args)) => run_edit(&config, args),
+        Some(Command::Split(args)) => run_split(&config, args),
+        Some(Command::Adjust(args)) => run_adjust(&config, args),
+        Some(Command::Rename(args)) => run_rename(&config, args),
+        Some(Command::Tag { command }) => run_tag(&config, command),
+        Some(Command::Delete(args)) => run_delete(&config, args),
+        Some(Command::Bulk(args)) => run_bulk(args),
+        Some(Command::Move(args)) => run_move(args),
+        Some(Command::Merge(args)) => run_merge(args),
+        Some(Command::Undo) => run_undo(&config),
         Some(Command::DeleteApiToken) => run_delete_api_token(),
-        None => run_status(),
+        Some(Command::Auth { command }) => run_auth(command),
+        Some(Command::Favorites {
+            command: FavoritesCommand::List,
+        }) => run_favorites_list(),
+        Some(Command::Project { command }) => run_project(command),
+        Some(Command::Client { command }) => run_client(&config, command),
+        Some(Command::Report(args)) => run_report(&config, args),
+        Some(Command::Log(args)) => run_log(&config, args),
+        Some(Command::Search(args)) => run_search(&config, args),
+        Some(Command::Balance(args)) => run_balance(&config, args),
+        Some(Command::Overtime(args)) => run_overtime(&config, args),
+        Some(Command::Export(args)) => run_export(args),
+        Some(Command::Restore(args)) => run_restore(args),
+        Some(Command::Import { command }) => run_import(&config, command),
+        Some(Command::Suggest(args)) => run_suggest(&config, args),
+        Some(Command::Week(args)) => run_week(&config, args),
+        Some(Command::Heatmap(args)) => run_heatmap(&config, args),
+        Some(Command::Budget) => run_budget(&config),
+        Some(Command::Targets) => run_targets(&config),
+        Some(Command::Nudge) => run_nudge(&config),
+        Some(Command::Daemon(args)) => run_daemon(&config, args),
+        Some(Command::Idle(args)) => run_idle(&config, args),
+        Some(Command::Check { command }) => run_check(&config, command),
+    };
+    // Wait for the pager before anything below exits the process.
+    drop(pager);
+    // Prompts read keys in raw mode, where Ctrl-C arrives as an interrupted
+    // read instead of a signal.
+    let result = result.map_err(|err| match is_interrupted(&err) {
+        true => tgl_cli::Error::UserAbort.into(),
+        false => err,
+    });
+    if let Err(err) = &result {
+        if let Some(ExitCode(code)) = err.downcast_ref() {
+            process::exit(*code);
+        }
+        if err.chain().any(|cause| cause.is::<InputRequired>()) {
+            eprintln!("Error: {err:?}");
+            process::exit(INPUT_REQUIRED_EXIT_CODE);
+        }
+        let cause = err
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<tgl_cli::Error>());
+        let code = match cause {
+            Some(tgl_cli::Error::UserAbort) => abort(),
+            Some(tgl_cli::Error::Auth) => {
+                eprintln!("Error: {err:?}");
+                eprintln!(
+                    "\n{} Toggl rejected the API token. Run `tgl delete-api-token` to enter a new one.",
+                    icon("🔑")
+                );
+                UNAUTHORIZED_EXIT_CODE
+            }
+            Some(tgl_cli::Error::Network(_)) => {
+                eprintln!("Error: {err:?}");
+                eprintln!(
+                    "\n{} Couldn't reach Toggl. Check your connection, or allow more time with --timeout.",
+                    icon("📴")
+                );
+                NETWORK_EXIT_CODE
+            }
+            Some(tgl_cli::Error::Api { status, message }) => {
+                eprintln!("Error: {err:?}");
+                eprintln!(
+                    "\n{} Toggl refused the request ({status}): {message}",
+                    icon("⚠️")
+                );
+                API_EXIT_CODE
+            }
+            Some(tgl_cli::Error::Keyring(_)) => {
+                eprintln!("Error: {err:?}");
+                eprintln!(
+                    "\n{} Set TOGGL_API_TOKEN to use tgl without the keyring.",
+                    icon("🔑")
+                );
+                KEYRING_EXIT_CODE
+            }
+            Some(tgl_cli::Error::Io(_)) => {
+                eprintln!("Error: {err:?}");
+                IO_EXIT_CODE
+            }
+            _ => return result,
+        };
+        process::exit(code);
     }
+
+    result
+}
+
+/// Exit code of `tgl current` and `tgl status --quiet` when no timer is
+/// running.
+const IDLE_EXIT_CODE: i32 = 3;
+
+/// Exit code when Toggl rejects the API token.
+const UNAUTHORIZED_EXIT_CODE: i32 = 4;
+
+/// Exit code when Toggl can't be reached.
+const NETWORK_EXIT_CODE: i32 = 5;
+
+/// Exit code when the API token can't be read from or saved to the keyring.
+const KEYRING_EXIT_CODE: i32 = 6;
+
+/// Exit code when a prompt was needed but `--no-input` ruled it out.
+const INPUT_REQUIRED_EXIT_CODE: i32 = 7;
+
+/// Exit code when Toggl answered a request with an error of its own.
+const API_EXIT_CODE: i32 = 8;
+
+/// Exit code when a file couldn't be read or written.
+const IO_EXIT_CODE: i32 = 9;
+
+/// Exit code after Ctrl-C, following the shell convention of 128 + SIGINT.
+const ABORTED_EXIT_CODE: i32 = 130;
+
+/// Ends a command with an exit code that carries meaning on its own, so
+/// nothing is printed for it.
+#[derive(Debug, thiserror::Error)]
+#[error("exit code {0}")]
+struct ExitCode(i32);
+
+/// Information a command would have prompted for, had `--no-input` not
+/// ruled it out, and how to provide it instead.
+#[derive(Debug, thiserror::Error)]
+#[error("Can't ask for {what} with --no-input or without a terminal{}", hint.map(|h| format!(". {h}")).unwrap_or_default())]
+struct InputRequired {
+    what: String,
+    hint: Option<&'static str>,
+}
+
+/// Restores the cursor that prompts and spinners hide, then exits.
+fn abort() -> ! {
+    let _ = dialoguer::console::Term::stderr().show_cursor();
+    eprintln!("\n{} Aborted", icon("🛑"));
+    process::exit(ABORTED_EXIT_CODE);
+}
+
+fn is_interrupted(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        cause
+            .downcast_ref::<io::Error>()
+            .is_some_and(|e| e.kind() == io::ErrorKind::Interrupted)
+    })
+}
+
+/// Sends logs to stderr. `-v` flags take precedence over `TGL_LOG`, which
+/// accepts `tracing` filter directives such as `tgl_cli=debug`.
+fn init_logging(verbose: u8) {
+    let filter = match (verbose, env::var("TGL_LOG")) {
+        (0, Ok(directives)) => EnvFilter::new(directives),
+        (0, Err(_)) => EnvFilter::new("off"),
+        (1, _) => EnvFilter::new("tgl=debug,tgl_cli=debug"),
+        _ => EnvFilter::new("trace"),
+    };
+
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(io::stderr)
+        .with_ansi(io::stderr().is_terminal())
+        .init();
+}
+
+/// How [`get_client`] sets up clients, decided once from the command line
+/// and config so that every command picks it up.
+#[derive(Default)]
+struct ClientOptions {
+    /// How long cached workspaces and projects stay fresh.
+    cache_ttl: Duration,
+    /// Whether `--dry-run` was passed.
+    dry_run: bool,
+    /// Whether to mirror data to the local store for offline reads.
+    store: bool,
+    /// Whether to show a spinner during requests.
+    spinner: bool,
+    /// Whether `--quiet` was passed.
+    quiet: bool,
+    /// Whether `--no-input` was passed, or stdin or stderr isn't a terminal.
+    no_input: bool,
+    /// Whether stdout isn't a terminal, so output is meant for other
+    /// programs and leaves out emoji.
+    plain: bool,
+    /// How messages are marked, from `icons`.
+    icons: Icons,
+    /// How many days of recent entries to fetch, from `[history]`.
+    latest_days: Option<u32>,
+    /// The most recent entries to fetch, from `[history]`.
+    latest_limit: Option<usize>,
+    /// Commands to run as timers start and stop, from `[hooks]`.
+    hooks: HooksConfig,
+    /// How to reach Toggl, from `[network]` and `--timeout`.
+    connection: Connection,
+    /// Which profile's API token to use, from `--profile` or `TGL_PROFILE`.
+    profile: Option<String>,
+    /// Where to record or replay responses, from `TGL_HTTP_RECORD` or
+    /// `TGL_HTTP_REPLAY`.
+    cassette: Option<Cassette>,
+}
+
+static CLIENT_OPTIONS: OnceLock<ClientOptions> = OnceLock::new();
+
+fn client_options() -> &'static ClientOptions {
+    CLIENT_OPTIONS.get_or_init(ClientOptions::default)
+}
+
+fn is_dry_run() -> bool {
+    client_options().dry_run
+}
+
+fn is_quiet() -> bool {
+    client_options().quiet
+}
+
+/// Fails with [`InputRequired`] under `--no-input`, in place of prompting
+/// for `what`.
+fn ensure_can_prompt(what: &str, hint: Option<&'static str>) -> Result<()> {
+    if client_options().no_input {
+        return Err(InputRequired {
+            what: what.to_string(),
+            hint,
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+fn is_plain() -> bool {
+    client_options().plain
+}
+
+/// Gets an emoji, or its ASCII stand-in when `icons = "ascii"`.
+fn icon(emoji: &str) -> &str {
+    client_options().icons.get(emoji)
+}
+
+/// Gets an emoji, or nothing, for a column where emoji take two cells and
+/// their stand-ins are padded to match.
+fn icon_column(emoji: Option<&str>) -> String {
+    match emoji.map(|emoji| (emoji, icon(emoji))) {
+        None => "  ".to_string(),
+        Some((emoji, marker)) if marker == emoji => marker.to_string(),
+        Some((_, marker)) => format!("{marker:<2}"),
+    }
+}
+
+/// Prefixes a message with an emoji, unless `--quiet` or a stdout that isn't
+/// a terminal asks for plain output.
+fn decorate(emoji: &str, message: &str) -> String {
+    if is_quiet() || is_plain() {
+        message.to_string()
+    } else {
+        format!("{} {message}", icon(emoji))
+    }
+}
+
+/// Stands in for the stop time of a running entry.
+fn running_stop() -> &'static str {
+    if is_plain() {
+        "--:--"
+    } else {
+        icon("⏳:⏳")
+    }
+}
+
+/// Follows a change with today's status, or with `--quiet`, with just the
+/// entry that changed.
+fn report_change(
+    config: &Config,
+    tracker: &dyn TimeTracker,
+    entry: Option<&TimeEntry>,
+) -> Result<()> {
+    if !is_quiet() {
+        return print_status(config, tracker, &StatusArgs::default());
+    }
+    if let Some(entry) = entry {
+        println_entry(config, None, entry, false);
+    }
+
+    Ok(())
 }
 
 fn get_client() -> Result<Client> {
-    let token = get_api_token()?;
+    let options = client_options();
+    let token = match &options.cassette {
+        // Replayed responses don't need a real token.
+        Some(cassette) if cassette.is_replay() => "replay".to_string(),
+        _ => get_api_token()?,
+    };
+    let mut client = Client::new(token, Utc::now)
+        .context("Failed to create Toggle API client")?
+        .with_latest_window(options.latest_days, options.latest_limit)
+        .with_connection(&options.connection)
+        .context("Failed to create Toggle API client")?;
+    // Cached data would keep requests out of the cassette, or answer them
+    // with something other than what it holds.
+    let use_cache = options.cassette.is_none();
+    if let Some(cassette) = &options.cassette {
+        client = client.with_cassette(cassette.clone());
+    }
+    if let Some(path) = cache_path(PROJECT_CACHE_FILE).filter(|_| use_cache) {
+        client = client.with_disk_cache(path, options.cache_ttl);
+    }
+    if let Some(path) = cache_path(STORE_FILE).filter(|_| options.store && use_cache) {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).context("Failed to create cache directory")?;
+        }
+        client = client
+            .with_store(&path)
+            .context("Failed to open local store")?;
+    }
+    if options.spinner {
+        client = client.with_progress(spinner::spinner());
+    }
+    if options.dry_run {
+        client = client.with_dry_run(|request| {
+            let body = request
+                .body
+                .as_ref()
+                .map(|b| format!(" {b}"))
+                .unwrap_or_default();
+            eprintln!(
+                "{} Would {} {}{body}",
+                icon("🧪"),
+                request.method,
+                request.url
+            );
+        });
+    }
+
+    Ok(client)
+}
+
+/// Sets up recording to the cassette file in `TGL_HTTP_RECORD`, or replaying
+/// from the one in `TGL_HTTP_REPLAY`.
+fn cassette() -> Result<Option<Cassette>> {
+    let var = |name| env::var_os(name).filter(|path| !path.is_empty());
+    let (path, cassette) = match (var("TGL_HTTP_REPLAY"), var("TGL_HTTP_RECORD")) {
+        (Some(path), _) => (path.clone(), Cassette::replay(path)),
+        (None, Some(path)) => (path.clone(), Cassette::record(path)),
+        (None, None) => return Ok(None),
+    };
+
+    cassette
+        .map(Some)
+        .map_err(tgl_cli::Error::Io)
+        .with_context(|| format!("Failed to read cassette {}", path.to_string_lossy()))
+}
+
+/// Reads the `[network]` settings, with `--timeout` taking precedence.
+fn connection(config: &Config, timeout: Option<Interval>) -> Result<Connection> {
+    let network = &config.network;
+    let mut connection = Connection::new().timeouts(Timeouts::new(
+        network.connect_timeout.duration().to_std()?,
+        timeout.unwrap_or(network.timeout).duration().to_std()?,
+    ));
+    if let Some(proxy) = &network.proxy {
+        connection = connection.proxy(proxy);
+    }
+    if let Some(path) = &network.ca_cert {
+        let pem = fs::read(path)
+            .with_context(|| format!("Failed to read CA certificate {}", path.display()))?;
+        connection = connection.root_certificate(pem);
+    }
 
-    Client::new(token, Utc::now).context("Failed to create Toggle API client")
+    Ok(connection)
+}
+
+const KEYRING_SERVICE: &str = "github.com/blachniet/tgl";
+
+/// The profile used when none is given.
+const DEFAULT_PROFILE: &str = "default";
+
+fn profile() -> &'static str {
+    client_options()
+        .profile
+        .as_deref()
+        .unwrap_or(DEFAULT_PROFILE)
 }
 
 fn keyring_entry() -> keyring::Entry {
-    keyring::Entry::new("github.com/blachniet/tgl", "api_token")
+    keyring::Entry::new(KEYRING_SERVICE, &format!("api_token:{}", profile()))
+}
+
+fn keyring_error(err: keyring::Error) -> tgl_cli::Error {
+    tgl_cli::Error::Keyring(Box::new(err))
+}
+
+/// Moves a token saved before there were profiles to the default profile.
+fn migrate_legacy_token() -> Result<Option<String>> {
+    let legacy = keyring::Entry::new(KEYRING_SERVICE, "api_token");
+    let token = match legacy.get_password() {
+        Ok(token) => token,
+        Err(keyring::Error::NoEntry) => return Ok(None),
+        Err(err) => {
+            return Err(keyring_error(err)).context("Failed to read from your keyring/keychain")
+        }
+    };
+    keyring_entry()
+        .set_password(&token)
+        .map_err(keyring_error)
+        .context("Failed to save the API token to the keyring/keychain")?;
+    legacy
+        .delete_password()
+        .map_err(keyring_error)
+        .context("Failed to delete the old API token from the keyring/keychain")?;
+
+    Ok(Some(token))
 }
 
 fn get_api_token() -> Result<String> {
+    if let Some(token) = find_api_token()? {
+        return Ok(token);
+    }
+
+    ensure_can_prompt(
+        "an API token",
+        Some("Set TOGGL_API_TOKEN or run `tgl auth login`"),
+    )?;
+    let token = dialoguer::Password::new()
+        .with_prompt("Enter your API token from https://track.toggl.com/profile")
+        .with_confirmation("Confirm token", "Tokens don't match")
+        .interact()
+        .context("Failed to read API token from keyring/keychain")?;
+
+    keyring_entry()
+        .set_password(&token)
+        .map_err(keyring_error)
+        .context("Failed to save the API token to the keyring/keychain")?;
+
+    Ok(token)
+}
+
+/// Looks up the API token without ever prompting the user.
+fn find_api_token() -> Result<Option<String>> {
     // Look for the token in an environment variable.
     let token = env::var("TOGGL_API_TOKEN");
     if let Ok(token) = token {
         if !token.is_empty() {
-            return Ok(token);
+            return Ok(Some(token));
         }
     }
 
     // Look for the token in the keyring.
-    let entry = keyring_entry();
-    let result = entry.get_password();
-    let token = match result {
-        Ok(token) => Ok(token),
-        Err(ref err) => match err {
-            keyring::Error::NoEntry => {
-                let token = dialoguer::Password::new()
-                    .with_prompt("Enter your API token from https://track.toggl.com/profile")
-                    .with_confirmation("Confirm token", "Tokens don't match")
-                    .interact()
-                    .context("Failed to read API token from keyring/keychain")?;
-
-                entry
-                    .set_password(&token)
-                    .context("Failed to save the API token to the keyring/keychain")?;
-
-                Ok(token)
-            }
-            _ => result.context("Failed to read from your keyring/keychain"),
-        },
-    }?;
+    match keyring_entry().get_password() {
+        Ok(token) => Ok(Some(token)),
+        Err(keyring::Error::NoEntry) if profile() == DEFAULT_PROFILE => migrate_legacy_token(),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(err) => Err(keyring_error(err)).context("Failed to read from your keyring/keychain"),
+    }
+}
 
-    Ok(token)
+fn cache_path(file_name: &str) -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("tgl").join(file_name))
+}
+
+/// Warns that Toggl couldn't be reached and the output comes from the local
+/// store.
+fn eprintln_stale_banner(client: &Client) {
+    if let Some(synced_at) = client.stale_since() {
+        eprintln!(
+            "{} Toggl is unreachable. Showing data saved at {}\n",
+            icon("📴"),
+            synced_at.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+        );
+    }
+}
+
+fn println_entry(config: &Config, index: Option<usize>, entry: &TimeEntry, show_id: bool) {
+    println!("{}", fmt_entry_line(config, index, entry, show_id));
 }
 
-fn println_entry(entry: &TimeEntry) {
-    println!(
-        "{} ({}) [{}] {}",
-        fmt_duration(entry.duration),
-        fmt_start_stop(entry),
-        entry.project_name.as_ref().unwrap_or(&"".to_string()),
+fn fmt_entry_line(
+    config: &Config,
+    index: Option<usize>,
+    entry: &TimeEntry,
+    show_id: bool,
+) -> String {
+    let mut prefix = String::new();
+    if let Some(index) = index {
+        prefix.push_str(&format!("{index:>2} "));
+    }
+    if show_id {
+        prefix.push_str(&format!("{} ", entry.id));
+    }
+    format!(
+        "{prefix}{} ({}) [{}] {}",
+        fmt_duration(entry.duration, config.duration_format),
+        fmt_start_stop(config, entry),
+        config.theme.paint_project(
+            project_label(
+                config,
+                entry.client_name.as_deref(),
+                entry.project_name.as_deref().unwrap_or_default()
+            ),
+            entry.project_color.as_deref()
+        ),
         entry.description.as_ref().unwrap_or(&"".to_string()),
-    );
+    )
+}
+
+/// Names a project, after its client when `show_clients` is on and the client
+/// is known.
+fn project_label(config: &Config, client_name: Option<&str>, project: &str) -> String {
+    match client_name {
+        Some(client_name) if config.show_clients && !project.is_empty() => {
+            format!("{client_name} / {project}")
+        }
+        _ => project.to_string(),
+    }
 }
 
-fn fmt_duration(dur: Duration) -> String {
-    let (hours, minutes, seconds) = get_duration_parts(dur);
-    format!("{hours}:{minutes:02}:{seconds:02}")
+/// Fills in the clients Toggl didn't name on entries, from the cached
+/// clients, when `show_clients` is on.
+fn name_clients(config: &Config, client: &Client, entries: &mut [TimeEntry]) -> Result<()> {
+    if !config.show_clients {
+        return Ok(());
+    }
+    for entry in entries.iter_mut().filter(|e| e.client_name.is_none()) {
+        if let Some(client_id) = entry.client_id {
+            entry.client_name = client
+                .get_client(entry.workspace_id, client_id)
+                .context("Failed to retrieve clients")?
+                .map(|c| c.name.to_string());
+        }
+    }
+
+    Ok(())
+}
+
+/// Marks a row that merges several entries with how many, such as ` ×3`.
+fn fmt_segments(segments: usize) -> String {
+    match segments {
+        1 => String::new(),
+        segments => format!(" ×{segments}"),
+    }
+}
+
+/// Prints one line per project with its total time, how many entries it
+/// has, and its latest description, the most time first.
+fn println_project_groups(config: &Config, entries: &[TimeEntry]) {
+    let mut groups: HashMap<Option<&str>, (Duration, usize, &TimeEntry)> = HashMap::new();
+    for entry in entries {
+        let group =
+            groups
+                .entry(entry.project_name.as_deref())
+                .or_insert((Duration::zero(), 0, entry));
+        group.0 += entry.duration;
+        group.1 += 1;
+        if entry.start > group.2.start {
+            group.2 = entry;
+        }
+    }
+    let mut groups: Vec<_> = groups.into_iter().collect();
+    groups.sort_by_key(|(project, (total, _, _))| (std::cmp::Reverse(*total), *project));
+
+    for (project, (total, count, latest)) in groups {
+        let count = i18n::tr("status-entry-count", &[("count", count.into())]);
+        let total = fmt_duration(total, config.duration_format);
+        let total = if latest.is_running {
+            config.theme.running.paint(total).to_string()
+        } else {
+            total
+        };
+        println!(
+            "{total} ({count}) [{}] {}",
+            config.theme.paint_project(
+                project_label(
+                    config,
+                    latest.client_name.as_deref(),
+                    project.unwrap_or_default()
+                ),
+                latest.project_color.as_deref()
+            ),
+            latest.description.as_deref().unwrap_or_default(),
+        );
+    }
 }
 
-fn fmt_start_stop(entry: &TimeEntry) -> String {
+fn fmt_start_stop(config: &Config, entry: &TimeEntry) -> String {
     if let Some(start) = entry.start {
         let start: DateTime<Local> = DateTime::from(start);
         if let Some(stop) = entry.stop {
             let stop: DateTime<Local> = DateTime::from(stop);
             format!(
                 "{} - {}",
-                start.time().format("%H:%M"),
-                stop.time().format("%H:%M")
+                start.time().format(config.time_format.pattern()),
+                stop.time().format(config.time_format.pattern())
             )
         } else {
-            format!("{} - ⏳:⏳", start.time().format("%H:%M"))
+            format!(
+                "{} - {}",
+                start.time().format(config.time_format.pattern()),
+                config.theme.running.paint(running_stop())
+            )
         }
     } else {
         String::new()
     }
 }
 
-fn get_duration_parts(dur: Duration) -> (i64, i64, i64) {
-    let minutes = (dur - Duration::hours(dur.num_hours())).num_minutes();
-    let seconds = (dur - Duration::minutes(dur.num_minutes())).num_seconds();
+/// The values available to `--format` templates for one line of output.
+struct TemplateRow<'a> {
+    client_id: Option<i64>,
+    client_name: Option<&'a str>,
+    description: Option<&'a str>,
+    duration: Duration,
+    id: Option<i64>,
+    index: Option<usize>,
+    project_name: Option<&'a str>,
+    start: String,
+    stop: String,
+    workspace_id: i64,
+}
+
+impl<'a> TemplateRow<'a> {
+    fn from_entry(entry: &'a TimeEntry) -> Self {
+        Self {
+            client_id: entry.client_id,
+            client_name: entry.client_name.as_deref(),
+            description: entry.description.as_deref(),
+            duration: entry.duration,
+            id: Some(entry.id),
+            index: None,
+            project_name: entry.project_name.as_deref(),
+            start: fmt_template_time(entry.start),
+            stop: fmt_template_time(entry.stop),
+            workspace_id: entry.workspace_id,
+        }
+    }
 
-    (dur.num_hours(), minutes, seconds)
+    /// A row from the first entry's start to the last one's stop, which is
+    /// empty while an entry is running. It has no entry's details.
+    fn span(entries: &[TimeEntry]) -> Self {
+        let stop = match entries.iter().any(|e| e.is_running) {
+            true => None,
+            false => entries.iter().filter_map(|e| e.stop).max(),
+        };
+
+        Self {
+            client_id: None,
+            client_name: None,
+            description: None,
+            duration: entries.iter().map(|e| e.duration).sum(),
+            id: None,
+            index: None,
+            project_name: None,
+            start: fmt_template_time(entries.iter().filter_map(|e| e.start).min()),
+            stop: fmt_template_time(stop),
+            workspace_id: entries.first().map_or(0, |e| e.workspace_id),
+        }
+    }
 }
 
-fn run_status() -> Result<()> {
-    let client = get_client()?;
-    let now = Local::now();
-    let today = Local
-        .with_ymd_and_hms(now.year(), now.month(), now.day(), 0, 0, 0)
-        .unwrap();
-    let tomorrow = today.checked_add_days(Days::new(1)).unwrap();
+fn fmt_template_time(t: Option<DateTime<Utc>>) -> String {
+    t.map(|t| DateTime::<Local>::from(t).format("%H:%M").to_string())
+        .unwrap_or_default()
+}
+
+fn render_row(
+    config: &Config,
+    tracker: &dyn TimeTracker,
+    template: &Template,
+    row: &TemplateRow,
+) -> Result<String> {
+    // Clients are only fetched when the template actually asks for them and
+    // Toggl didn't name them already.
+    let client_name = match (row.client_name, row.client_id, tracker.toggl()) {
+        (Some(name), _, _) => Some(name.to_string()),
+        (None, Some(client_id), Some(client)) if template.uses(Field::Client) => client
+            .get_client(row.workspace_id, client_id)
+            .context("Failed to retrieve clients")?
+            .map(|c| c.name.to_string()),
+        _ => None,
+    };
+
+    Ok(render_fields(config, template, row, client_name))
+}
+
+/// Whether rendering `row` needs its client's name from Toggl.
+fn needs_client(template: &Template, row: &TemplateRow) -> bool {
+    template.uses(Field::Client) && row.client_name.is_none() && row.client_id.is_some()
+}
+
+fn render_fields(
+    config: &Config,
+    template: &Template,
+    row: &TemplateRow,
+    client_name: Option<String>,
+) -> String {
+    template.render(|field| match field {
+        Field::Client => client_name.clone().unwrap_or_default(),
+        Field::Description => row.description.unwrap_or_default().to_string(),
+        Field::Duration => fmt_duration(row.duration, config.duration_format),
+        Field::DurationDecimal => fmt_duration_decimal(row.duration),
+        Field::Id => row.id.map(|i| i.to_string()).unwrap_or_default(),
+        Field::Index => row.index.map(|i| i.to_string()).unwrap_or_default(),
+        Field::Project => row.project_name.unwrap_or_default().to_string(),
+        Field::Start => row.start.clone(),
+        Field::Stop => row.stop.clone(),
+    })
+}
+
+fn local_midnight(date: NaiveDate) -> DateTime<Local> {
+    Local
+        .with_ymd_and_hms(date.year(), date.month(), date.day(), 0, 0, 0)
+        .earliest()
+        .expect("local midnight exists")
+}
+
+/// Gets today's entries in the order `tgl status` lists them.
+fn get_todays_entries(client: &Client) -> Result<Vec<TimeEntry>> {
+    let today = local_midnight(Local::now().date_naive());
+    let tomorrow = local_midnight(today.date_naive() + Days::new(1));
     let mut latest_entries = client
         .get_latest_entries()
         .context("Failed to retrieve time entries")?;
     latest_entries.sort_unstable_by_key(|e| e.start);
+    latest_entries.retain(|e| e.touches(today.into(), tomorrow.into()));
 
-    let mut is_running = false;
-    let mut dur_today = Duration::zero();
-    for entry in latest_entries.iter().filter(|e| {
-        if let Some(start) = e.start {
-            if start >= today && start < tomorrow {
-                return true;
-            }
+    Ok(latest_entries)
+}
+
+fn run_status(config: &Config, args: &StatusArgs) -> Result<()> {
+    if args.interactive {
+        if config.backend != Backend::Toggl {
+            bail!("--interactive only works with Toggl");
         }
+        return run_interactive(config, args);
+    }
 
-        if let Some(stop) = e.stop {
-            if stop >= today && stop < tomorrow {
-                return true;
-            }
+    print_status(config, get_tracker(config)?.as_ref(), args)
+}
+
+fn print_status(config: &Config, tracker: &dyn TimeTracker, args: &StatusArgs) -> Result<()> {
+    // Quiet status is for scripts, which only need the exit code.
+    if is_quiet() {
+        return match tracker
+            .current_entry()
+            .context("Failed to retrieve current time entry")?
+        {
+            Some(_) => Ok(()),
+            None => Err(ExitCode(IDLE_EXIT_CODE).into()),
+        };
+    }
+    let today = Local::now().date_naive();
+    let (from, to) = match (args.date, args.from) {
+        (Some(date), _) => (date, date),
+        (None, Some(from)) => (from, args.to.unwrap_or(today)),
+        (None, None) => (today, today),
+    };
+    if from > to {
+        bail!("--from must not be after --to");
+    }
+    let is_today = from == today && to == today;
+    let mut entries = match tracker.toggl() {
+        Some(client) => {
+            let mut entries = if is_today {
+                get_todays_entries(client)?
+            } else {
+                client
+                    .get_entries_touching(
+                        local_midnight(from).into(),
+                        local_midnight(to + Days::new(1)).into(),
+                    )
+                    .context("Failed to retrieve time entries")?
+            };
+            eprintln_stale_banner(client);
+            name_clients(config, client, &mut entries)?;
+            entries
         }
+        None => get_range_entries(tracker, from, to)?,
+    };
 
-        false
-    }) {
-        println_entry(entry);
+    let mut is_running = false;
+    let mut dur_today = Duration::zero();
+    let mut last_date = None;
+    let mut rows = if args.format.is_none() && (args.collapse || config.status.collapse) {
+        segments::collapse(&entries)
+    } else {
+        segments::rows(&entries)
+    };
+    // Filtering after numbering keeps the indices other commands take.
+    rows.retain(|row| args.matches(&row.entry));
+    entries.retain(|e| args.matches(e));
+    for Row {
+        index,
+        entry,
+        segments,
+    } in &rows
+    {
         dur_today += entry.duration;
         is_running = is_running || entry.is_running;
+        if args.group {
+            continue;
+        }
+        match &args.format {
+            Some(template) => {
+                let row = TemplateRow {
+                    index: Some(*index),
+                    ..TemplateRow::from_entry(entry)
+                };
+                println!("{}", render_row(config, tracker, template, &row)?)
+            }
+            None => {
+                let date = entry.start.map(|s| s.with_timezone(&Local).date_naive());
+                if from != to && date != last_date {
+                    if last_date.is_some() {
+                        println!();
+                    }
+                    if let Some(date) = date {
+                        println!("{}", i18n::fmt_date(date, "day-heading-format"));
+                    }
+                    last_date = date;
+                }
+                // Other commands only take indices of today's entries.
+                let index = is_today.then_some(*index);
+                let line = match &config.status.line {
+                    Some(template) => {
+                        let row = TemplateRow {
+                            index,
+                            ..TemplateRow::from_entry(entry)
+                        };
+                        render_row(config, tracker, template, &row)?
+                    }
+                    None => fmt_entry_line(config, index, entry, args.ids),
+                };
+                println!("{line}{}", fmt_segments(*segments));
+            }
+        }
+    }
+    if args.group {
+        println_project_groups(config, &entries);
+    }
+
+    if args.format.is_some() {
+        return Ok(());
+    }
+    if let Some(footer) = &config.status.footer {
+        println!();
+        println!(
+            "{}",
+            render_row(config, tracker, footer, &TemplateRow::span(&entries))?
+        );
+        return Ok(());
+    }
+
+    let logged = config
+        .theme
+        .total
+        .paint(fmt_duration(dur_today, config.duration_format))
+        .to_string();
+    if !is_today {
+        println!();
+        let summary = if from == to {
+            i18n::tr(
+                "logged-on",
+                &[
+                    ("duration", logged.into()),
+                    ("date", i18n::fmt_date(from, "date-format").into()),
+                ],
+            )
+        } else {
+            i18n::tr(
+                "logged-between",
+                &[
+                    ("duration", logged.into()),
+                    ("from", i18n::fmt_date(from, "date-format").into()),
+                    ("to", i18n::fmt_date(to, "date-format").into()),
+                ],
+            )
+        };
+        println!("{}", decorate("⏱ ", &summary));
+        return Ok(());
+    }
+
+    if let (Some(client), Some(running)) = (tracker.toggl(), entries.iter().find(|e| e.is_running))
+    {
+        if let Some(warning) = budget_warning(client, running)? {
+            println!();
+            println!("{warning}");
+        }
     }
 
     println!();
-    print!("⏱  {} logged today.", fmt_duration(dur_today));
+    print!(
+        "{}",
+        decorate(
+            "⏱ ",
+            &i18n::tr("logged-today", &[("duration", logged.into())])
+        )
+    );
 
-    if is_running {
-        let target_dur = Duration::hours(8);
+    // The target is for all of the day's time, not just the entries shown.
+    if is_running && !args.is_filtered() {
+        let target_dur = config.work_hours.daily_target.duration();
         let dur_remaining = target_dur - dur_today;
         let target_time = (Local::now() + dur_remaining).time();
         println!(
-            " You'll reach {} logged at {}.",
-            fmt_duration(target_dur),
-            target_time.format("%H:%M")
+            " {}",
+            i18n::tr(
+                "target-eta",
+                &[
+                    (
+                        "target",
+                        fmt_duration(target_dur, config.duration_format).into()
+                    ),
+                    (
+                        "time",
+                        target_time
+                            .format(config.time_format.pattern())
+                            .to_string()
+                            .into()
+                    ),
+                ]
+            )
         );
     } else {
         println!();
     }
+    if config.weekly_target().is_some() && !args.is_filtered() {
+        let monday = today - Days::new(today.weekday().num_days_from_monday().into());
+        let logged_this_week = get_range_entries(tracker, monday, today)?
+            .iter()
+            .map(|e| e.duration)
+            .sum();
+        if let Some(forecast) = week_forecast(config, logged_this_week) {
+            println!("{forecast}");
+        }
+    }
 
     Ok(())
 }
 
-fn run_start() -> Result<()> {
+/// The details of a new time entry, gathered from the user.
+enum NewEntry {
+    Manual {
+        workspace_id: i64,
+        project_id: Option<i64>,
+        description: String,
+        tags: Vec<String>,
+        billable: Option<bool>,
+    },
+    Favorite(Favorite),
+}
+
+impl NewEntry {
+    fn start_at(&self, client: &Client, start: DateTime<Utc>) -> Result<TimeEntry> {
+        let entry = match self {
+            NewEntry::Manual {
+                workspace_id,
+                project_id,
+                description,
+                tags,
+                billable,
+            } => {
+                let mut entry = client
+                    .start(*workspace_id)
+                    .description(description)
+                    .tags(tags.clone())
+                    .start_at(start);
+                if let Some(project_id) = project_id {
+                    entry = entry.project(*project_id);
+                }
+                if let Some(billable) = billable {
+                    entry = entry.billable(*billable);
+                }
+                entry.send()
+            }
+            NewEntry::Favorite(favorite) => client.start_favorite_at(favorite, start),
+        }
+        .context("Failed to start time entry")?;
+
+        Ok(entry)
+    }
+}
+
+fn prompt_new_entry(client: &Client, config: &Config, args: &StartArgs) -> Result<NewEntry> {
+    if args.favorite {
+        return Ok(NewEntry::Favorite(select_favorite(client)?));
+    }
+    if let Some(name) = args.name.as_ref().or(args.template.as_ref()) {
+        return resolve_template(client, config, name);
+    }
+
+    let (workspace_id, project_id, description) = prompt_entry_details(client)?;
+
+    Ok(NewEntry::Manual {
+        workspace_id,
+        project_id,
+        description,
+        tags: Vec::new(),
+        billable: None,
+    })
+}
+
+/// Asks for a workspace, project, and description, returning them as
+/// `(workspace_id, project_id, description)`.
+fn prompt_entry_details(client: &Client) -> Result<(i64, Option<i64>, String)> {
     let theme = dialoguer::theme::ColorfulTheme::default();
     let term = dialoguer::console::Term::stderr();
-    let client = get_client()?;
     let workspaces = client
         .get_workspaces()
         .context("Failed to retrieve workspaces")?;
@@ -194,17 +1514,23 @@ fn run_start() -> Result<()> {
                 "Using only workspace",
                 &workspace_names[0],
             )?;
-            term.write_line(&buf)?;
+            if !is_quiet() {
+                term.write_line(&buf)?;
+            }
 
             Ok(0)
         }
-        _ => dialoguer::FuzzySelect::with_theme(&theme)
-            .with_prompt("Select a workspace")
-            .items(&workspace_names)
-            .default(0)
-            .interact_on_opt(&term)
-            .context("Failed to read workspace input")?
-            .ok_or_else(|| anyhow!("You must select a workspace")),
+        _ => {
+            ensure_can_prompt("a workspace", None)?;
+            dialoguer::FuzzySelect::with_theme(&theme)
+                .with_prompt("Select a workspace")
+                .items(&workspace_names)
+                .default(0)
+                .report(!is_quiet())
+                .interact_on_opt(&term)
+                .context("Failed to read workspace input")?
+                .ok_or_else(|| anyhow!("You must select a workspace"))
+        }
     }?;
 
     let workspace = &workspaces[workspace_idx];
@@ -213,61 +1539,691 @@ fn run_start() -> Result<()> {
         .context("Failed to get projects")?;
     let projects: Vec<_> = projects.iter().filter(|p| p.active).collect();
     let project_names: Vec<_> = projects.iter().map(|p| p.name.to_string()).collect();
+    ensure_can_prompt("a project", Some("Start from a template instead"))?;
     let project_idx = dialoguer::FuzzySelect::with_theme(&theme)
         .with_prompt("Select a project or press 'Esc' to skip")
         .items(&project_names)
+        .report(!is_quiet())
         .interact_on_opt(&term)
         .context("Failed to read project selection")?;
 
     let project_id = project_idx.map(|i| projects[i].id);
+    let recent = RecentDescriptions::new(client);
+    let mut history = recent.history();
     let description: String = dialoguer::Input::new()
-        .with_prompt("Enter a description (optional)")
+        .with_prompt("Enter a description (optional, ↑/Tab for recent ones)")
         .allow_empty(true)
+        .completion_with(&recent)
+        .history_with(&mut history)
+        .report(!is_quiet())
         .interact_text()
         .context("Failed to read description input")?;
 
-    client
-        .start_time_entry(workspace.id, project_id, Some(&description))
-        .context("Failed to start time entry")?;
+    Ok((workspace.id, project_id, description))
+}
+
+fn resolve_template(client: &Client, config: &Config, name: &str) -> Result<NewEntry> {
+    let template = find_template(config, name)?;
+    let (workspace_id, project_id) = template_target(client, name, template)?;
 
-    run_status()
+    Ok(NewEntry::Manual {
+        workspace_id,
+        project_id,
+        description: template.description.clone().unwrap_or_default(),
+        tags: template.tags.clone(),
+        billable: template.billable,
+    })
 }
 
-fn run_stop() -> Result<()> {
+fn find_template<'a>(config: &'a Config, name: &str) -> Result<&'a EntryTemplate> {
+    config
+        .templates
+        .get(name)
+        .ok_or_else(|| anyhow!("No template named '{name}' in the config file"))
+}
+
+/// Finds the workspace and project that the template called `name` starts
+/// entries in.
+fn template_target(
+    tracker: &dyn TimeTracker,
+    name: &str,
+    template: &EntryTemplate,
+) -> Result<(i64, Option<i64>)> {
+    let workspaces = tracker
+        .workspaces()
+        .context("Failed to retrieve workspaces")?;
+    let workspaces: Vec<_> = match &template.workspace {
+        Some(workspace) => workspaces
+            .iter()
+            .filter(|w| w.name.eq_ignore_ascii_case(workspace))
+            .collect(),
+        None => workspaces.iter().collect(),
+    };
+    if workspaces.is_empty() {
+        bail!("Workspace for template '{name}' not found");
+    }
+
+    Ok(match &template.project {
+        Some(project) => {
+            let mut found = Vec::new();
+            for w in &workspaces {
+                let projects = tracker.projects(w.id).context("Failed to get projects")?;
+                if let Some(p) = projects
+                    .iter()
+                    .find(|p| p.name.eq_ignore_ascii_case(project))
+                {
+                    found.push((w.id, Some(p.id)));
+                }
+            }
+            match found[..] {
+                [] => bail!("Project '{project}' from template '{name}' not found"),
+                [found] => found,
+                _ => bail!("Project '{project}' exists in several workspaces, set 'workspace' in template '{name}'"),
+            }
+        }
+        None => match workspaces[..] {
+            [w] => (w.id, None),
+            _ => bail!("You have several workspaces, set 'workspace' in template '{name}'"),
+        },
+    })
+}
+
+/// Gets your workspaces, or only the one called `name` (ignoring case).
+fn find_workspaces(client: &Client, name: Option<&str>) -> Result<Vec<Workspace>> {
+    let workspaces: Vec<_> = client
+        .get_workspaces()
+        .context("Failed to retrieve workspaces")?
+        .into_iter()
+        .filter(|w| name.is_none_or(|name| w.name.eq_ignore_ascii_case(name)))
+        .collect();
+    match name {
+        Some(name) if workspaces.is_empty() => bail!("Workspace '{name}' not found"),
+        _ => Ok(workspaces),
+    }
+}
+
+/// Fails if `entries` include one that starts before its workspace's lock
+/// date, which Toggl would refuse to change.
+fn ensure_unlocked<'a>(
+    client: &Client,
+    entries: impl IntoIterator<Item = &'a TimeEntry>,
+) -> Result<()> {
+    let workspaces = client
+        .get_workspaces()
+        .context("Failed to retrieve workspaces")?;
+    for entry in entries {
+        let Some(start) = entry.start else {
+            continue;
+        };
+        ensure_unlocked_at(&workspaces, entry.workspace_id, start)?;
+    }
+
+    Ok(())
+}
+
+/// Fails if `time` is before the workspace's lock date.
+fn ensure_unlocked_at(
+    workspaces: &[Workspace],
+    workspace_id: i64,
+    time: DateTime<Utc>,
+) -> Result<()> {
+    let Some(workspace) = workspaces.iter().find(|w| w.id == workspace_id) else {
+        return Ok(());
+    };
+    if let Some(lock_date) = workspace.lock_date {
+        if time < local_midnight(lock_date) {
+            bail!(
+                "{} Entries before {lock_date} are locked in workspace '{}'. Ask a workspace admin to unlock them.",
+                icon("🔒"),
+                workspace.name
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn select_favorite(client: &Client) -> Result<Favorite> {
+    let mut favorites = client
+        .get_favorites()
+        .context("Failed to retrieve favorites")?;
+    if favorites.is_empty() {
+        bail!(
+            "{} No favorites found. Add some in the Toggl web or desktop app.",
+            icon("🤷")
+        );
+    }
+
+    ensure_can_prompt("a favorite", None)?;
+    let items: Vec<_> = favorites.iter().map(fmt_favorite).collect();
+    let idx = dialoguer::FuzzySelect::with_theme(&dialoguer::theme::ColorfulTheme::default())
+        .with_prompt("Select a favorite")
+        .items(&items)
+        .default(0)
+        .report(!is_quiet())
+        .interact_on_opt(&dialoguer::console::Term::stderr())
+        .context("Failed to read favorite selection")?
+        .ok_or_else(|| anyhow!("You must select a favorite"))?;
+
+    Ok(favorites.swap_remove(idx))
+}
+
+fn fmt_favorite(favorite: &Favorite) -> String {
+    let mut s = format!(
+        "{} [{}]",
+        favorite
+            .description
+            .as_deref()
+            .unwrap_or("(no description)"),
+        favorite.project_name.as_deref().unwrap_or_default()
+    );
+    for tag in &favorite.tags {
+        s.push_str(&format!(" #{tag}"));
+    }
+    if favorite.billable {
+        s.push_str(" $");
+    }
+
+    s
+}
+
+fn run_start(config: &Config, args: &StartArgs) -> Result<()> {
+    let tracker = get_tracker(config)?;
+    let started = match tracker.toggl() {
+        Some(client) => prompt_new_entry(client, config, args)?.start_at(client, Utc::now())?,
+        None => start_template(config, tracker.as_ref(), args)?,
+    };
+    record_changes(
+        tracker.as_ref(),
+        vec![Action::Started {
+            entry: started.clone(),
+        }],
+    );
+    clear_prompt_cache();
+
+    report_change(config, tracker.as_ref(), Some(&started))
+}
+
+/// Starts an entry from a template with a backend other than Toggl, which
+/// leaves out its tags and billable flag.
+fn start_template(
+    config: &Config,
+    tracker: &dyn TimeTracker,
+    args: &StartArgs,
+) -> Result<TimeEntry> {
+    if args.favorite {
+        bail!("Favorites only work with Toggl");
+    }
+    let Some(name) = args.name.as_ref().or(args.template.as_ref()) else {
+        bail!("Give the name of a template to start");
+    };
+    let template = find_template(config, name)?;
+    let (workspace_id, project_id) = template_target(tracker, name, template)?;
+
+    tracker
+        .start_entry(
+            workspace_id,
+            project_id,
+            template.description.as_deref(),
+            Utc::now(),
+        )
+        .context("Failed to start time entry")
+}
+
+fn run_switch(config: &Config, args: &StartArgs) -> Result<()> {
     let client = get_client()?;
-    if client
+    // Ask for everything up front so that cancelling a prompt leaves the
+    // running timer untouched.
+    let entry = prompt_new_entry(&client, config, args)?;
+    let stopped = client
         .stop_current_time_entry()
-        .context("Failed to stop current time entry")?
-        .is_none()
-    {
-        println!("🤷 No timers running\n");
+        .context("Failed to stop current time entry")?;
+
+    // Start exactly where the previous entry stopped so there's no gap.
+    let start = stopped
+        .as_ref()
+        .and_then(|e| e.stop)
+        .unwrap_or_else(Utc::now);
+    let started = entry.start_at(&client, start)?;
+    let mut actions: Vec<_> = stopped
+        .map(|entry| Action::Stopped { entry })
+        .into_iter()
+        .collect();
+    actions.push(Action::Started {
+        entry: started.clone(),
+    });
+    hooks::run(&actions);
+    journal::record(actions);
+    clear_prompt_cache();
+
+    report_change(config, &client, Some(&started))
+}
+
+fn run_stop(config: &Config) -> Result<()> {
+    let tracker = get_tracker(config)?;
+    let stopped = stop_timer(tracker.as_ref())?;
+    match &stopped {
+        Some(_) => {}
+        None if is_quiet() => println!("No timers running"),
+        None => println!("{}", decorate("🤷", "No timers running\n")),
     }
 
-    run_status()
+    report_change(config, tracker.as_ref(), stopped.as_ref())
 }
 
-fn run_restart() -> Result<()> {
-    let client = get_client()?;
-    let recent_entries = client
+fn stop_timer(tracker: &dyn TimeTracker) -> Result<Option<TimeEntry>> {
+    let stopped = tracker
+        .stop_current_entry()
+        .context("Failed to stop current time entry")?;
+    if let Some(entry) = &stopped {
+        record_changes(
+            tracker,
+            vec![Action::Stopped {
+                entry: entry.clone(),
+            }],
+        );
+    }
+    clear_prompt_cache();
+
+    Ok(stopped)
+}
+
+/// Runs the hooks for changes to the timer, and journals them for `tgl undo`
+/// when they were made in Toggl, the only backend it can undo them in.
+fn record_changes(tracker: &dyn TimeTracker, actions: Vec<Action>) {
+    hooks::run(&actions);
+    if tracker.toggl().is_some() {
+        journal::record(actions);
+    }
+}
+
+fn run_restart(config: &Config, args: &RestartArgs) -> Result<()> {
+    let client = get_client()?.with_latest_window(
+        args.days.or(config.history.days),
+        args.limit.or(config.history.limit),
+    );
+    if let Some(entry) = args.entry.resolve(&client)? {
+        return start_again(config, &client, &entry);
+    }
+
+    let mut entries = client
         .get_latest_entries()
         .context("Failed to retrieve latest time entries")?;
-    if let Some(last_entry) = recent_entries.first() {
-        client
-            .start_time_entry(
-                last_entry.workspace_id,
-                last_entry.project_id,
-                last_entry.description.as_deref(),
-            )
-            .context("Failed to start time entry")?;
+    // Don't trust the API's ordering, and restarting the entry that's
+    // already running would only duplicate it.
+    entries.retain(|e| !e.is_running);
+    sort_newest_first(&mut entries);
+
+    let entry = if args.select {
+        dedup_entries(&mut entries);
+        entries.truncate(RESTART_SELECT_COUNT);
+        select_entry(&entries, "Select an entry to restart")?
     } else {
-        bail!("🤷 No recent entries to restart");
+        entries
+            .first()
+            .ok_or_else(|| anyhow!("{} No recent entries to restart", icon("🤷")))?
+    };
+
+    start_again(config, &client, entry)
+}
+
+fn run_continue(config: &Config, args: &ContinueArgs) -> Result<()> {
+    let client = get_client()?;
+    let now = Utc::now();
+    let days = args.days.or(config.history.days).unwrap_or(CONTINUE_DAYS);
+    let since = now
+        .checked_sub_signed(Duration::days(days.into()))
+        .ok_or_else(|| anyhow!("Can't search {days} days of history"))?;
+    let mut entries = client
+        .get_indexed_entries(since, now)
+        .context("Failed to retrieve recent time entries")?;
+    sort_newest_first(&mut entries);
+    if let Some(limit) = args.limit.or(config.history.limit) {
+        entries.truncate(limit);
     }
+    dedup_entries(&mut entries);
+    let entry = select_entry(&entries, "Select an entry to continue")?;
 
-    run_status()
+    start_again(config, &client, entry)
+}
+
+fn sort_newest_first(entries: &mut [TimeEntry]) {
+    entries.sort_by_key(|e| std::cmp::Reverse(e.start));
+}
+
+/// Keeps only the first entry for each project and description combination.
+fn dedup_entries(entries: &mut Vec<TimeEntry>) {
+    let mut seen = std::collections::HashSet::new();
+    entries.retain(|e| seen.insert((e.workspace_id, e.project_id, e.description.clone())));
+}
+
+fn select_entry<'a>(entries: &'a [TimeEntry], prompt: &str) -> Result<&'a TimeEntry> {
+    if entries.is_empty() {
+        bail!("{} No recent entries to choose from", icon("🤷"));
+    }
+    ensure_can_prompt("an entry", None)?;
+
+    let items: Vec<_> = entries
+        .iter()
+        .map(|e| {
+            format!(
+                "{} [{}]",
+                e.description.as_deref().unwrap_or("(no description)"),
+                e.project_name.as_deref().unwrap_or_default()
+            )
+        })
+        .collect();
+    let idx = dialoguer::FuzzySelect::with_theme(&dialoguer::theme::ColorfulTheme::default())
+        .with_prompt(prompt)
+        .items(&items)
+        .default(0)
+        .report(!is_quiet())
+        .interact_on_opt(&dialoguer::console::Term::stderr())
+        .context("Failed to read entry selection")?
+        .ok_or_else(|| anyhow!("You must select an entry"))?;
+
+    Ok(&entries[idx])
+}
+
+/// Starts a new entry with the same project and description as `entry`.
+fn start_again(config: &Config, client: &Client, entry: &TimeEntry) -> Result<()> {
+    let started = restart_entry(client, entry)?;
+
+    report_change(config, client, Some(&started))
+}
+
+fn restart_entry(client: &Client, entry: &TimeEntry) -> Result<TimeEntry> {
+    let started = client
+        .start_time_entry(
+            entry.workspace_id,
+            entry.project_id,
+            entry.description.as_deref(),
+        )
+        .context("Failed to start time entry")?;
+    let actions = vec![Action::Started {
+        entry: started.clone(),
+    }];
+    hooks::run(&actions);
+    journal::record(actions);
+    clear_prompt_cache();
+
+    Ok(started)
+}
+
+fn run_favorites_list() -> Result<()> {
+    let client = get_client()?;
+    let favorites = client
+        .get_favorites()
+        .context("Failed to retrieve favorites")?;
+    if favorites.is_empty() {
+        println!("{}", decorate("🤷", "No favorites found"));
+    }
+    for favorite in &favorites {
+        println!("{}", fmt_favorite(favorite));
+    }
+
+    Ok(())
 }
 
 fn run_delete_api_token() -> Result<()> {
+    // An entry from before profiles counts as the default profile's.
+    if profile() == DEFAULT_PROFILE {
+        migrate_legacy_token()?;
+    }
     keyring_entry()
         .delete_password()
-        .context("Failed to delete API token from keyring/keychain")
+        .map_err(keyring_error)
+        .with_context(|| {
+            format!(
+                "Failed to delete API token for profile '{}' from keyring/keychain",
+                profile()
+            )
+        })
+}
+
+#[derive(Serialize, Deserialize)]
+struct PromptCache {
+    fetched_at: DateTime<Utc>,
+    running: Option<PromptEntry>,
+    /// Whether the last fetch failed, which leaves `running` as it was.
+    #[serde(default)]
+    failed: bool,
+}
+
+impl PromptCache {
+    /// Whether the cache is recent enough to use as it is. After a failure
+    /// that's for longer, so that an unreachable Toggl isn't asked again on
+    /// every prompt.
+    fn is_fresh(&self, now: DateTime<Utc>) -> bool {
+        let ttl = match self.failed {
+            true => PROMPT_RETRY_SECS,
+            false => PROMPT_CACHE_TTL_SECS,
+        };
+        now - self.fetched_at < Duration::seconds(ttl)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct PromptEntry {
+    start: DateTime<Utc>,
+    label: String,
+}
+
+/// Prints the running entry without fetching the rest of the day's.
+fn run_current(config: &Config, args: &CurrentArgs) -> Result<()> {
+    let mut tracker = None;
+    let current = match read_daemon_current(config) {
+        Some(current) => current,
+        None => tracker
+            .insert(get_tracker(config)?)
+            .current_entry()
+            .context("Failed to retrieve current time entry")?,
+    };
+    match current {
+        Some(entry) => {
+            match &args.format {
+                Some(template) => {
+                    let row = TemplateRow::from_entry(&entry);
+                    // The daemon's answer is only worth having if Toggl
+                    // isn't asked for the client anyway.
+                    let line = match tracker {
+                        Some(tracker) => render_row(config, tracker.as_ref(), template, &row)?,
+                        None if needs_client(template, &row) => {
+                            render_row(config, get_tracker(config)?.as_ref(), template, &row)?
+                        }
+                        None => {
+                            let client_name = row.client_name.map(str::to_string);
+                            render_fields(config, template, &row, client_name)
+                        }
+                    };
+                    println!("{line}");
+                }
+                None => println_entry(config, None, &entry, false),
+            }
+            Ok(())
+        }
+        None => {
+            println!("{}", decorate("🤷", "No timer running"));
+            Err(ExitCode(IDLE_EXIT_CODE).into())
+        }
+    }
+}
+
+fn run_prompt(config: &Config, args: &PromptArgs) -> Result<()> {
+    // Shell prompts call this constantly, so it must never block on user
+    // input and should stay quiet when anything goes wrong.
+    let now = Utc::now();
+    if args.refresh {
+        refresh_prompt_cache(config, read_prompt_cache(), now);
+        if let Some(lock) = cache_path(PROMPT_LOCK_FILE) {
+            let _ = fs::remove_file(lock);
+        }
+        return Ok(());
+    }
+    let cache = match (read_daemon_current(config), read_prompt_cache()) {
+        (Some(running), _) => PromptCache {
+            fetched_at: now,
+            running: running.and_then(prompt_entry),
+            failed: false,
+        },
+        // An out-of-date timer is still closer than none, and the shell
+        // shouldn't wait on Toggl for a better one.
+        (None, Some(cache)) => {
+            if !cache.is_fresh(now) {
+                spawn_prompt_refresh();
+            }
+            cache
+        }
+        // Without a cache, as right after tgl changed the timer, there's
+        // nothing to show until Toggl answers.
+        (None, None) => refresh_prompt_cache(config, None, now),
+    };
+
+    if let Some(entry) = cache.running {
+        // Seconds would only change when the cache does, so clock time
+        // leaves them out.
+        let format = match config.duration_format {
+            DurationFormat::Clock => DurationFormat::Compact,
+            format => format,
+        };
+        let line = format!(
+            "{} {} {}",
+            icon("▶"),
+            fmt_duration(now - entry.start, format),
+            entry.label
+        );
+        println!("{}", line.trim_end());
+    }
+
+    Ok(())
+}
+
+/// Fetches the running timer into the cache. On failure, the cache keeps
+/// what it had, marked so that Toggl isn't asked again right away.
+fn refresh_prompt_cache(
+    config: &Config,
+    previous: Option<PromptCache>,
+    now: DateTime<Utc>,
+) -> PromptCache {
+    let cache = fetch_prompt_cache(config, now).unwrap_or_else(|_| PromptCache {
+        fetched_at: now,
+        running: previous.and_then(|cache| cache.running),
+        failed: true,
+    });
+    write_prompt_cache(&cache);
+
+    cache
+}
+
+/// Runs `tgl prompt --refresh` in a process of its own that outlives this
+/// one, unless one is running already.
+fn spawn_prompt_refresh() {
+    let Some(lock) = cache_path(PROMPT_LOCK_FILE) else {
+        return;
+    };
+    if let Some(dir) = lock.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    let take_lock = || {
+        fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&lock)
+            .is_ok()
+    };
+    if !take_lock() {
+        let is_abandoned = fs::metadata(&lock)
+            .and_then(|metadata| metadata.modified())
+            .is_ok_and(|modified| {
+                modified
+                    .elapsed()
+                    .is_ok_and(|age| age.as_secs() >= PROMPT_LOCK_SECS)
+            });
+        if !is_abandoned || fs::remove_file(&lock).is_err() || !take_lock() {
+            return;
+        }
+    }
+    let Ok(exe) = env::current_exe() else {
+        let _ = fs::remove_file(&lock);
+        return;
+    };
+    let spawned = process::Command::new(exe)
+        .args(env::args_os().skip(1))
+        .arg("--refresh")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    if spawned.is_err() {
+        let _ = fs::remove_file(&lock);
+    }
+}
+
+fn fetch_prompt_cache(config: &Config, now: DateTime<Utc>) -> Result<PromptCache> {
+    if config.backend != Backend::Toggl {
+        return Ok(PromptCache {
+            fetched_at: now,
+            running: get_tracker(config)?.current_entry()?.and_then(prompt_entry),
+            failed: false,
+        });
+    }
+    let token = find_api_token()?.ok_or_else(|| anyhow!("No API token available"))?;
+    // Use the configured proxy and certificates, but give up well before
+    // other commands would, since the shell waits on the prompt.
+    let connection = &client_options().connection;
+    let timeout = PROMPT_TIMEOUT.to_std()?;
+    let connection = connection.clone().timeouts(Timeouts::new(
+        connection.timeouts.connect.min(timeout),
+        connection.timeouts.request.min(timeout),
+    ));
+    let client = Client::new(token, Utc::now)?.with_connection(&connection)?;
+    let running = client.get_current_entry()?.and_then(prompt_entry);
+
+    Ok(PromptCache {
+        fetched_at: now,
+        running,
+        failed: false,
+    })
+}
+
+/// The running entry from `tgl daemon`, which only polls Toggl.
+fn read_daemon_current(config: &Config) -> Option<Option<TimeEntry>> {
+    daemon::read_current().filter(|_| config.backend == Backend::Toggl)
+}
+
+fn prompt_entry(entry: TimeEntry) -> Option<PromptEntry> {
+    Some(PromptEntry {
+        start: entry.start?,
+        label: entry.project_name.or(entry.description).unwrap_or_default(),
+    })
+}
+
+fn read_prompt_cache() -> Option<PromptCache> {
+    let contents = fs::read(cache_path("prompt.json")?).ok()?;
+    serde_json::from_slice(&contents).ok()
+}
+
+fn write_prompt_cache(cache: &PromptCache) {
+    if let Some(path) = cache_path("prompt.json") {
+        if let Some(dir) = path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        if let Ok(contents) = serde_json::to_vec(cache) {
+            let _ = fs::write(path, contents);
+        }
+    }
+}
+
+/// Forces the next `tgl prompt` to refetch after the running timer changes,
+/// and `tgl daemon` to poll again.
+fn clear_prompt_cache() {
+    daemon::refresh();
+    forget_prompt_cache();
+}
+
+/// Removes the cache that `tgl prompt` falls back to without a daemon.
+fn forget_prompt_cache() {
+    if let Some(path) = cache_path("prompt.json") {
+        let _ = fs::remove_file(path);
+    }
 }
@@ -0,0 +1,205 @@
+//! Publishing the timer's state from `tgl daemon` to an MQTT broker, so that
+//! home automation can react to it, e.g. with a light that turns red while a
+//! timer runs.
+//!
+//! Only what that takes is implemented: MQTT 3.1.1 without TLS, sending one
+//! retained message at QoS 0 over a fresh connection each time the state
+//! changes, which is rare enough not to keep a connection open.
+
+use crate::config::MqttConfig;
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+use std::{
+    io::{Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+use tgl_cli::TimeEntry;
+
+const DEFAULT_PORT: u16 = 1883;
+
+/// How long connecting to, writing to, or waiting on the broker may take
+/// before the daemon gives up until the next poll.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+pub struct Publisher<'a> {
+    config: &'a MqttConfig,
+    broker: String,
+    /// The last state the broker accepted, so that it's only sent again
+    /// once it changes.
+    published: Option<Value>,
+}
+
+impl<'a> Publisher<'a> {
+    /// Creates a publisher for the configured broker, or `None` if there
+    /// isn't one.
+    pub fn new(config: &'a MqttConfig) -> Option<Self> {
+        let broker = config.broker.as_ref()?;
+        let has_port = broker
+            .rsplit_once(':')
+            .is_some_and(|(_, port)| port.parse::<u16>().is_ok());
+
+        Some(Self {
+            config,
+            broker: match has_port {
+                true => broker.clone(),
+                false => format!("{broker}:{DEFAULT_PORT}"),
+            },
+            published: None,
+        })
+    }
+
+    /// Publishes the timer's state if it changed since the last time. A
+    /// state that fails to publish is tried again on the next call.
+    pub fn update(&mut self, running: Option<&TimeEntry>, now: DateTime<Utc>) -> Result<()> {
+        let current = state(running);
+        if self.published.as_ref() == Some(&current) {
+            return Ok(());
+        }
+        let mut message = current.clone();
+        if let (Some(fields), Some(start)) =
+            (message.as_object_mut(), running.and_then(|e| e.start))
+        {
+            fields.insert("duration".to_string(), json!((now - start).num_seconds()));
+        }
+        self.publish(message.to_string().as_bytes())
+            .with_context(|| format!("Failed to publish to MQTT broker {}", self.broker))?;
+        self.published = Some(current);
+
+        Ok(())
+    }
+
+    fn publish(&self, message: &[u8]) -> Result<()> {
+        let mut stream = self.connect()?;
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        stream.set_write_timeout(Some(TIMEOUT))?;
+
+        stream.write_all(&connect_packet(
+            &format!("tgl-{}", std::process::id()),
+            self.config.username.as_deref(),
+            self.config.password.as_deref(),
+        ))?;
+        let mut connack = [0; 4];
+        stream.read_exact(&mut connack)?;
+        match connack {
+            [0x20, 0x02, _, 0] => {}
+            [0x20, 0x02, _, 4 | 5] => bail!("The broker rejected the username or password"),
+            [0x20, 0x02, _, code] => bail!("The broker refused the connection with code {code}"),
+            _ => bail!("The broker sent an unexpected response"),
+        }
+        stream.write_all(&publish_packet(&self.config.topic, message))?;
+        stream.write_all(&packet(0xe0, &[]))?;
+
+        Ok(())
+    }
+
+    /// Connects to the first of the broker's addresses that answers in time.
+    fn connect(&self) -> Result<TcpStream> {
+        let mut error = anyhow!("The broker's address didn't resolve");
+        for addr in self.broker.to_socket_addrs()? {
+            match TcpStream::connect_timeout(&addr, TIMEOUT) {
+                Ok(stream) => return Ok(stream),
+                Err(err) => error = err.into(),
+            }
+        }
+
+        Err(error)
+    }
+}
+
+/// The timer's state as published, apart from the running entry's duration,
+/// which changes on every poll.
+fn state(running: Option<&TimeEntry>) -> Value {
+    match running {
+        Some(entry) => json!({
+            "running": true,
+            "description": entry.description,
+            "project": entry.project_name,
+            "client": entry.client_name,
+            "start": entry.start,
+        }),
+        None => json!({ "running": false }),
+    }
+}
+
+/// Frames a control packet: its type and flags, then the body's length.
+fn packet(header: u8, body: &[u8]) -> Vec<u8> {
+    let mut packet = vec![header];
+    let mut len = body.len();
+    loop {
+        let mut byte = (len % 128) as u8;
+        len /= 128;
+        if len > 0 {
+            byte |= 0x80;
+        }
+        packet.push(byte);
+        if len == 0 {
+            break;
+        }
+    }
+    packet.extend_from_slice(body);
+
+    packet
+}
+
+fn push_str(body: &mut Vec<u8>, s: &str) {
+    body.extend_from_slice(&(s.len() as u16).to_be_bytes());
+    body.extend_from_slice(s.as_bytes());
+}
+
+fn connect_packet(client_id: &str, username: Option<&str>, password: Option<&str>) -> Vec<u8> {
+    let mut body = Vec::new();
+    push_str(&mut body, "MQTT");
+    body.push(4); // Protocol level 3.1.1
+    let mut flags = 0x02; // Clean session
+    if username.is_some() {
+        flags |= 0x80;
+        // A password is only allowed with a username.
+        if password.is_some() {
+            flags |= 0x40;
+        }
+    }
+    body.push(flags);
+    body.extend_from_slice(&60u16.to_be_bytes()); // Keep alive, in seconds
+    push_str(&mut body, client_id);
+    if let Some(username) = username {
+        push_str(&mut body, username);
+        if let Some(password) = password {
+            push_str(&mut body, password);
+        }
+    }
+
+    packet(0x10, &body)
+}
+
+/// A retained message at QoS 0, which needs no acknowledgement.
+fn publish_packet(topic: &str, message: &[u8]) -> Vec<u8> {
+    let mut body = Vec::new();
+    push_str(&mut body, topic);
+    body.extend_from_slice(message);
+
+    packet(0x31, &body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_packets() {
+        assert_eq!(
+            vec![0x31, 7, 0, 3, b'a', b'/', b'b', b'h', b'i'],
+            publish_packet("a/b", b"hi")
+        );
+        assert_eq!(
+            vec![
+                0x10, 19, 0, 4, b'M', b'Q', b'T', b'T', 4, 0xc2, 0, 60, 0, 1, b'c', 0, 1, b'u', 0,
+                1, b'p'
+            ],
+            connect_packet("c", Some("u"), Some("p"))
+        );
+        // Lengths past 127 take more than one byte.
+        assert_eq!([0xe0, 0xc8, 0x01], packet(0xe0, &[0; 200])[..3]);
+    }
+}
@@ -0,0 +1,139 @@
+//! Reminders about the timer, checked once per run of `tgl nudge` so that
+//! cron or a status bar can ask every few minutes, or on each poll of `tgl
+//! daemon`.
+
+use crate::{
+    config::{Config, NudgeConfig, WorkHours},
+    decorate,
+    duration::fmt_duration,
+    get_client, get_todays_entries,
+    targets::target_work_hours,
+};
+use anyhow::Result;
+use chrono::{Duration, Local, NaiveDateTime};
+use tgl_cli::TimeEntry;
+
+#[derive(Debug, PartialEq)]
+pub enum Nudge {
+    NoTimer,
+    LongEntry(Duration),
+    PastTarget(Duration),
+}
+
+/// Finds the reminders that apply at `now`, given the day's entries.
+pub fn nudges(
+    nudge: &NudgeConfig,
+    work_hours: &WorkHours,
+    now: NaiveDateTime,
+    entries: &[TimeEntry],
+) -> Vec<Nudge> {
+    let mut nudges = Vec::new();
+    let is_work_day = work_hours.is_work_day(now.date());
+    let running = entries.iter().find(|e| e.is_running);
+    let in_work_hours = (work_hours.start..work_hours.end).contains(&now.time());
+    if nudge.no_timer && is_work_day && in_work_hours && running.is_none() {
+        nudges.push(Nudge::NoTimer);
+    }
+    if let Some(running) = running.filter(|_| nudge.long_entry) {
+        if running.duration > nudge.long_entry_after.duration() {
+            nudges.push(Nudge::LongEntry(running.duration));
+        }
+    }
+    let logged: Duration = entries.iter().map(|e| e.duration).sum();
+    if nudge.past_target && is_work_day && logged > work_hours.daily_target.duration() {
+        nudges.push(Nudge::PastTarget(logged));
+    }
+
+    nudges
+}
+
+pub fn run_nudge(config: &Config) -> Result<()> {
+    let client = get_client()?;
+    let entries = get_todays_entries(&client)?;
+    let work_hours = target_work_hours(config);
+    for nudge in nudges(
+        &config.nudge,
+        &work_hours,
+        Local::now().naive_local(),
+        &entries,
+    ) {
+        println!("{}", fmt_nudge(config, &work_hours, &nudge));
+    }
+
+    Ok(())
+}
+
+pub fn fmt_nudge(config: &Config, work_hours: &WorkHours, nudge: &Nudge) -> String {
+    let fmt = |dur| fmt_duration(dur, config.duration_format);
+    match nudge {
+        Nudge::NoTimer => decorate("⏰", "No timer is running during work hours"),
+        Nudge::LongEntry(dur) => decorate(
+            "⚠️",
+            &format!("The running entry has gone on for {}", fmt(*dur)),
+        ),
+        Nudge::PastTarget(logged) => decorate(
+            "🎯",
+            &format!(
+                "{} logged today, past the target of {}",
+                fmt(*logged),
+                fmt(work_hours.daily_target.duration())
+            ),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applies_each_rule() {
+        let entry = |hours: i64, is_running: bool| -> TimeEntry {
+            serde_json::from_value(serde_json::json!({
+                "billable": false,
+                "client_id": null,
+                "description": null,
+                "duration": hours * 3600,
+                "id": 1,
+                "is_running": is_running,
+                "project_id": null,
+                "project_name": null,
+                "start": "2024-06-03T08:00:00Z",
+                "stop": null,
+                "tags": [],
+                "task_id": null,
+                "workspace_id": 1,
+            }))
+            .unwrap()
+        };
+        let work_hours = WorkHours::default();
+        let monday = |time: &str| format!("2024-06-03T{time}").parse().unwrap();
+        let nudge = NudgeConfig::default();
+
+        assert_eq!(
+            vec![Nudge::NoTimer],
+            nudges(&nudge, &work_hours, monday("10:00:00"), &[entry(1, false)])
+        );
+        assert!(nudges(&nudge, &work_hours, monday("18:00:00"), &[]).is_empty());
+        assert_eq!(
+            vec![
+                Nudge::LongEntry(Duration::hours(5)),
+                Nudge::PastTarget(Duration::hours(9))
+            ],
+            nudges(
+                &nudge,
+                &work_hours,
+                monday("17:30:00"),
+                &[entry(4, false), entry(5, true)]
+            )
+        );
+        let nudge = NudgeConfig {
+            no_timer: false,
+            ..NudgeConfig::default()
+        };
+        assert!(nudges(&nudge, &work_hours, monday("10:00:00"), &[]).is_empty());
+        // Saturdays have no work hours or target.
+        let saturday = "2024-06-08T10:00:00".parse().unwrap();
+        assert!(nudges(&nudge, &work_hours, saturday, &[entry(9, false)]).is_empty());
+    }
+}
@@ -0,0 +1,102 @@
+//! Pages long listings through `$PAGER`, the way git does.
+
+use crate::config::Config;
+use dialoguer::console;
+use std::{
+    env,
+    io::{self, IsTerminal, Write},
+    process::{Child, Command, Stdio},
+};
+use tracing::debug;
+
+/// A running pager that stdout is redirected to. Dropping it restores
+/// stdout and waits for the user to quit the pager.
+pub struct Pager {
+    child: Child,
+    #[cfg(unix)]
+    saved_stdout: std::os::fd::OwnedFd,
+}
+
+/// Starts the pager if stdout is a terminal and neither `--no-pager` nor the
+/// config turn it off. Without a pager, output goes straight to stdout.
+pub fn start(config: &Config, no_pager: bool) -> Option<Pager> {
+    if no_pager || !config.pager.enabled || !io::stdout().is_terminal() {
+        return None;
+    }
+    let command = env::var("PAGER").unwrap_or_else(|_| "less".to_string());
+    if command.trim().is_empty() || command.trim() == "cat" {
+        return None;
+    }
+
+    // Decide on colors while stdout is still the terminal; they'd be turned
+    // off once it's a pipe.
+    let colors = console::colors_enabled();
+    match spawn(&command) {
+        Ok(pager) => {
+            console::set_colors_enabled(colors);
+            Some(pager)
+        }
+        Err(err) => {
+            debug!(%command, %err, "not paging");
+            None
+        }
+    }
+}
+
+#[cfg(unix)]
+fn spawn(command: &str) -> io::Result<Pager> {
+    use std::os::fd::{AsRawFd, BorrowedFd};
+
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        // Like git: quit if everything fits on one screen, pass colors
+        // through, and leave the output on screen after quitting.
+        .env(
+            "LESS",
+            env::var("LESS").unwrap_or_else(|_| "FRX".to_string()),
+        )
+        .env("LV", env::var("LV").unwrap_or_else(|_| "-c".to_string()))
+        .stdin(Stdio::piped())
+        .spawn()?;
+    let stdin = child.stdin.take().expect("stdin is piped");
+    io::stdout().flush()?;
+    // SAFETY: stdout stays open for the life of the process.
+    let saved_stdout =
+        unsafe { BorrowedFd::borrow_raw(libc::STDOUT_FILENO) }.try_clone_to_owned()?;
+    // SAFETY: both descriptors are open; dup2 replaces stdout atomically.
+    if unsafe { libc::dup2(stdin.as_raw_fd(), libc::STDOUT_FILENO) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // Exit quietly instead of panicking if the user quits the pager before
+    // all output is written.
+    // SAFETY: restoring the default disposition of a signal is always sound.
+    unsafe { libc::signal(libc::SIGPIPE, libc::SIG_DFL) };
+
+    Ok(Pager {
+        child,
+        saved_stdout,
+    })
+}
+
+#[cfg(not(unix))]
+fn spawn(_command: &str) -> io::Result<Pager> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "paging is only supported on Unix",
+    ))
+}
+
+impl Drop for Pager {
+    fn drop(&mut self) {
+        let _ = io::stdout().flush();
+        // Closing our end of the pipe lets the pager see the end of input.
+        #[cfg(unix)]
+        {
+            use std::os::fd::AsRawFd;
+            // SAFETY: both descriptors are open.
+            unsafe { libc::dup2(self.saved_stdout.as_raw_fd(), libc::STDOUT_FILENO) };
+        }
+        let _ = self.child.wait();
+    }
+}
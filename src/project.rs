@@ -0,0 +1,77 @@
+//! Commands that manage Toggl projects.
+
+use crate::{decorate, find_workspaces, get_client};
+use anyhow::{bail, Context, Result};
+use clap::{Args, Subcommand};
+use tgl_cli::{Client, Project};
+
+#[derive(Subcommand)]
+pub enum ProjectCommand {
+    /// Archive a project so it's no longer offered when starting entries
+    Archive(ProjectArgs),
+    /// Restore an archived project
+    Unarchive(ProjectArgs),
+}
+
+#[derive(Args)]
+pub struct ProjectArgs {
+    /// Name of the project, ignoring case
+    name: String,
+    /// Name of the project's workspace, if the name exists in several
+    #[arg(long)]
+    workspace: Option<String>,
+}
+
+pub fn run_project(command: &ProjectCommand) -> Result<()> {
+    let (args, active) = match command {
+        ProjectCommand::Archive(args) => (args, false),
+        ProjectCommand::Unarchive(args) => (args, true),
+    };
+    let client = get_client()?;
+    let project = find_project(&client, &args.name, args.workspace.as_deref())?;
+    if project.active == active {
+        println!(
+            "{}",
+            decorate(
+                "🤷",
+                &format!(
+                    "{} is already {}",
+                    project.name,
+                    if active { "active" } else { "archived" }
+                )
+            )
+        );
+        return Ok(());
+    }
+
+    let project = client
+        .set_project_active(&project, active)
+        .context("Failed to update project")?;
+    if project.active {
+        println!("{}", decorate("📂", &format!("Restored {}", project.name)));
+    } else {
+        println!("{}", decorate("🗄️ ", &format!("Archived {}", project.name)));
+    }
+
+    Ok(())
+}
+
+/// Finds a project by name across workspaces, optionally limited to the
+/// workspace with the given name.
+fn find_project(client: &Client, name: &str, workspace: Option<&str>) -> Result<Project> {
+    let mut found = Vec::new();
+    for w in find_workspaces(client, workspace)? {
+        if let Some(p) = client
+            .find_project_by_name(w.id, name)
+            .context("Failed to get projects")?
+        {
+            found.push(p.clone());
+        }
+    }
+
+    match found.len() {
+        0 => bail!("Project '{name}' not found"),
+        1 => Ok(found.remove(0)),
+        _ => bail!("Project '{name}' exists in several workspaces, pass --workspace"),
+    }
+}
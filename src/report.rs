@@ -0,0 +1,1020 @@
+//! Commands that summarize or export time entries over a date range.
+
+use crate::{
+    chart::daily_totals,
+    config::{Config, WorkHours},
+    decorate,
+    duration::{fmt_duration, fmt_duration_decimal, fmt_signed},
+    eprintln_stale_banner, get_client, i18n, local_midnight, name_clients, project_label,
+    render_row,
+    rounding::{RoundPer, RoundPolicy, RoundTo, Rounding},
+    targets::target_work_hours,
+    template::Template,
+    when::{parse_date, parse_month},
+    TemplateRow,
+};
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{
+    DateTime, Datelike, Days, Duration, Local, Months, NaiveDate, Timelike, Utc, Weekday,
+};
+use clap::{Args, ValueEnum};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use tgl_cli::{Client, TimeEntry, TimeTracker, Workspace};
+
+#[derive(Args)]
+pub struct RangeArgs {
+    /// First day to include, e.g. 2024-06-03, yesterday, or "last monday"
+    /// [default: Monday of this week]
+    #[arg(long, value_parser = parse_date)]
+    from: Option<NaiveDate>,
+    /// Last day to include, e.g. 2024-06-09 or "3 days ago" [default: today]
+    #[arg(long, value_parser = parse_date)]
+    to: Option<NaiveDate>,
+    /// Include a whole month instead, e.g. 2024-06, this, last, or june
+    #[arg(long, value_parser = parse_month, conflicts_with_all = ["from", "to"])]
+    month: Option<NaiveDate>,
+}
+
+impl RangeArgs {
+    /// Returns the inclusive `(from, to)` dates, applying defaults.
+    pub fn resolve(&self) -> Result<(NaiveDate, NaiveDate)> {
+        if let Some(month) = self.month {
+            let next = month
+                .checked_add_months(Months::new(1))
+                .ok_or_else(|| anyhow!("--month is out of range"))?;
+            return Ok((month, next - Days::new(1)));
+        }
+        let today = Local::now().date_naive();
+        let from = self
+            .from
+            .unwrap_or_else(|| today - Days::new(today.weekday().num_days_from_monday().into()));
+        let to = self.to.unwrap_or(today);
+        if from > to {
+            bail!("--from must not be after --to");
+        }
+
+        Ok((from, to))
+    }
+}
+
+#[derive(Args)]
+pub struct RoundArgs {
+    /// Round durations to an interval such as 6m, 15m, or 1h, or to the
+    /// rounding set up in Toggl with "workspace"
+    #[arg(long, value_name = "INTERVAL")]
+    round: Option<RoundTo>,
+    /// Which way to round when --round is an interval
+    #[arg(long, value_enum, default_value_t, requires = "round")]
+    round_policy: RoundPolicy,
+}
+
+impl RoundArgs {
+    fn roundings(&self, client: &Client) -> Result<Roundings> {
+        let roundings = match self.round {
+            None => Roundings::default(),
+            Some(RoundTo::Interval(interval)) => Roundings {
+                fixed: Some(Rounding {
+                    interval,
+                    policy: self.round_policy,
+                }),
+                ..Default::default()
+            },
+            Some(RoundTo::Workspace) => Roundings {
+                per_workspace: client
+                    .get_workspaces()
+                    .context("Failed to retrieve workspaces")?
+                    .iter()
+                    .filter_map(|w| {
+                        Some((w.id, Rounding::from_toggl(w.rounding, w.rounding_minutes)?))
+                    })
+                    .collect(),
+                ..Default::default()
+            },
+        };
+
+        Ok(roundings)
+    }
+}
+
+/// How durations are rounded, either the same everywhere or per workspace.
+#[derive(Default)]
+struct Roundings {
+    fixed: Option<Rounding>,
+    per_workspace: BTreeMap<i64, Rounding>,
+}
+
+impl Roundings {
+    fn get(&self, workspace_id: i64) -> Option<Rounding> {
+        self.fixed
+            .or_else(|| self.per_workspace.get(&workspace_id).copied())
+    }
+
+    /// Rounds an entry's duration the way its workspace calls for.
+    fn apply(&self, entry: &TimeEntry) -> Duration {
+        self.get(entry.workspace_id)
+            .map_or(entry.duration, |r| r.apply(entry.duration))
+    }
+}
+
+#[derive(Args)]
+pub struct ReportArgs {
+    #[command(flatten)]
+    range: RangeArgs,
+    #[command(flatten)]
+    round: RoundArgs,
+    /// Whether --round applies to each entry or to each row's total
+    #[arg(long, value_enum, default_value_t, requires = "round")]
+    round_per: RoundPer,
+    /// Print each project/description row using a template instead of the
+    /// default layout, e.g. "{project},{description},{duration_decimal}"
+    #[arg(long, value_name = "TEMPLATE")]
+    format: Option<Template>,
+    /// Show what billable time earned, using project and workspace rates
+    #[arg(long, conflicts_with = "format")]
+    earnings: bool,
+}
+
+#[derive(Args)]
+pub struct BalanceArgs {
+    /// First day to include, e.g. 2024-06-03 or "2 weeks ago"
+    #[arg(long, value_parser = parse_date)]
+    from: NaiveDate,
+    /// Last day to include, e.g. 2024-06-09 or yesterday [default: today]
+    #[arg(long, value_parser = parse_date)]
+    to: Option<NaiveDate>,
+}
+
+#[derive(Args)]
+pub struct ExportArgs {
+    #[command(flatten)]
+    range: RangeArgs,
+    #[command(flatten)]
+    round: RoundArgs,
+    /// Output format
+    #[arg(long, value_enum, default_value_t)]
+    format: ExportFormat,
+}
+
+impl ExportArgs {
+    /// Whether the export is meant for other programs to read.
+    pub fn is_json(&self) -> bool {
+        matches!(self.format, ExportFormat::Json)
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Default)]
+enum ExportFormat {
+    /// Comma-separated values with a header row
+    #[default]
+    Csv,
+    /// A JSON array of entries
+    Json,
+    /// Timewarrior intervals as JSON, as `timew export` writes them, ready
+    /// for `timew import`
+    Timew,
+    /// hledger timeclock check-ins and check-outs
+    Timeclock,
+    /// Org-mode CLOCK lines under project and description headings
+    Org,
+    /// hledger timedot postings of daily hours per project
+    Hledger,
+}
+
+pub fn get_range_entries(
+    tracker: &dyn TimeTracker,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<TimeEntry>> {
+    let mut entries = tracker
+        .time_entries(
+            local_midnight(from).into(),
+            local_midnight(to + Days::new(1)).into(),
+        )
+        .context("Failed to retrieve time entries")?;
+    entries.sort_unstable_by_key(|e| e.start);
+
+    Ok(entries)
+}
+
+struct ReportGroup<'a> {
+    first: &'a TimeEntry,
+    duration: Duration,
+    billable: Duration,
+    start: Option<DateTime<Utc>>,
+    stop: Option<DateTime<Utc>>,
+}
+
+pub fn run_report(config: &Config, args: &ReportArgs) -> Result<()> {
+    let client = get_client()?;
+    let (from, to) = args.range.resolve()?;
+    let mut entries = get_range_entries(&client, from, to)?;
+    eprintln_stale_banner(&client);
+    name_clients(config, &client, &mut entries)?;
+    let roundings = args.round.roundings(&client)?;
+
+    // Group by project, then by description within each project.
+    let mut groups: BTreeMap<(Option<&str>, Option<&str>), ReportGroup> = BTreeMap::new();
+    for entry in &entries {
+        let key = (entry.project_name.as_deref(), entry.description.as_deref());
+        let group = groups.entry(key).or_insert(ReportGroup {
+            first: entry,
+            duration: Duration::zero(),
+            billable: Duration::zero(),
+            start: entry.start,
+            stop: entry.stop,
+        });
+        let duration = match args.round_per {
+            RoundPer::Entry => roundings.apply(entry),
+            RoundPer::Group => entry.duration,
+        };
+        group.duration += duration;
+        if entry.billable {
+            group.billable += duration;
+        }
+        group.start = group.start.min(entry.start);
+        group.stop = group.stop.max(entry.stop);
+    }
+    if args.round_per == RoundPer::Group {
+        for group in groups.values_mut() {
+            if let Some(r) = roundings.get(group.first.workspace_id) {
+                group.duration = r.apply(group.duration);
+                group.billable = r.apply(group.billable);
+            }
+        }
+    }
+
+    if let Some(template) = &args.format {
+        let fmt_time = |t: Option<DateTime<Utc>>| {
+            t.map(|t| {
+                DateTime::<Local>::from(t)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            })
+            .unwrap_or_default()
+        };
+        for (i, group) in groups.values().enumerate() {
+            let row = TemplateRow {
+                start: fmt_time(group.start),
+                stop: fmt_time(group.stop),
+                duration: group.duration,
+                id: None,
+                index: Some(i + 1),
+                ..TemplateRow::from_entry(group.first)
+            };
+            println!("{}", render_row(config, &client, template, &row)?);
+        }
+
+        return Ok(());
+    }
+
+    let rates = if args.earnings {
+        Some(Rates::new(&client)?)
+    } else {
+        None
+    };
+    let mut total = Duration::zero();
+    let mut earned: BTreeMap<String, f64> = BTreeMap::new();
+    let mut current_project = None;
+    for ((project, description), group) in &groups {
+        if current_project != Some(project) {
+            current_project = Some(project);
+            let project_groups = groups.iter().filter(|((p, _), _)| p == project);
+            let project_total = project_groups
+                .clone()
+                .fold(Duration::zero(), |acc, (_, g)| acc + g.duration);
+            let mut line = format!(
+                "{} [{}]",
+                fmt_duration(project_total, config.duration_format),
+                config.theme.paint_project(
+                    match project {
+                        Some(project) => {
+                            project_label(config, group.first.client_name.as_deref(), project)
+                        }
+                        None => i18n::tr("no-project", &[]),
+                    },
+                    group.first.project_color.as_deref()
+                )
+            );
+            if let Some(rates) = &rates {
+                let billable =
+                    project_groups.fold(Duration::zero(), |acc, (_, g)| acc + g.billable);
+                match rates.get(&client, group.first)? {
+                    _ if billable.is_zero() => {}
+                    Some((rate, currency)) => {
+                        let amount = earnings(billable, rate);
+                        line.push_str(&format!(
+                            " {}",
+                            decorate("💰", &format!("{amount:.2} {currency}"))
+                        ));
+                        *earned.entry(currency).or_default() += amount;
+                    }
+                    None => {
+                        line.push_str(&format!(" {}", decorate("💰", &i18n::tr("no-rate", &[]))))
+                    }
+                }
+            }
+            println!("{line}");
+        }
+        println!(
+            "    {} {}",
+            fmt_duration(group.duration, config.duration_format),
+            description.unwrap_or_default()
+        );
+        total += group.duration;
+    }
+
+    println!();
+    println!(
+        "{}",
+        decorate(
+            "⏱ ",
+            &i18n::tr(
+                "logged-between",
+                &[
+                    (
+                        "duration",
+                        config
+                            .theme
+                            .total
+                            .paint(fmt_duration(total, config.duration_format))
+                            .to_string()
+                            .into()
+                    ),
+                    ("from", i18n::fmt_date(from, "date-format").into()),
+                    ("to", i18n::fmt_date(to, "date-format").into()),
+                ]
+            )
+        )
+    );
+    if rates.is_some() {
+        if earned.is_empty() {
+            println!("{}", decorate("💰", &i18n::tr("no-earnings", &[])));
+        }
+        for (currency, amount) in &earned {
+            println!(
+                "{}",
+                decorate(
+                    "💰",
+                    &i18n::tr(
+                        "earned",
+                        &[(
+                            "amount",
+                            config
+                                .theme
+                                .total
+                                .paint(format!("{amount:.2} {currency}"))
+                                .to_string()
+                                .into()
+                        )]
+                    )
+                )
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Looks up the hourly rate and currency that apply to entries, preferring
+/// the project's and falling back to the workspace's.
+struct Rates {
+    workspaces: Vec<Workspace>,
+}
+
+impl Rates {
+    fn new(client: &Client) -> Result<Self> {
+        let workspaces = client
+            .get_workspaces()
+            .context("Failed to retrieve workspaces")?;
+
+        Ok(Self { workspaces })
+    }
+
+    fn get(&self, client: &Client, entry: &TimeEntry) -> Result<Option<(f64, String)>> {
+        let Some(workspace) = self.workspaces.iter().find(|w| w.id == entry.workspace_id) else {
+            return Ok(None);
+        };
+        let project = match entry.project_id {
+            Some(id) => client
+                .get_project(entry.workspace_id, id)
+                .context("Failed to get project")?,
+            None => None,
+        };
+        let (rate, currency) = match project {
+            Some(p) => (p.rate, p.currency.clone()),
+            None => (None, None),
+        };
+        let rate = rate.or(workspace.default_hourly_rate);
+        let currency = currency
+            .or_else(|| workspace.default_currency.clone())
+            .unwrap_or_default();
+
+        Ok(rate.map(|rate| (rate, currency)))
+    }
+}
+
+/// What `billable` time earns at an hourly `rate`.
+fn earnings(billable: Duration, rate: f64) -> f64 {
+    billable.num_seconds() as f64 / 3600.0 * rate
+}
+
+pub fn run_balance(config: &Config, args: &BalanceArgs) -> Result<()> {
+    let to = args.to.unwrap_or_else(|| Local::now().date_naive());
+    if args.from > to {
+        bail!("--from must not be after --to");
+    }
+    let client = get_client()?;
+    let totals = daily_totals(&get_range_entries(&client, args.from, to)?);
+    let weeks = weekly_balance(&totals, args.from, to, &target_work_hours(config));
+
+    let fmt = |dur| fmt_duration(dur, config.duration_format);
+    let mut balance = Duration::zero();
+    for (week, tracked, target) in &weeks {
+        balance += *tracked - *target;
+        println!(
+            "{}",
+            i18n::tr(
+                "balance-week",
+                &[
+                    ("week", i18n::fmt_date(*week, "date-format").into()),
+                    ("tracked", format!("{:>9}", fmt(*tracked)).into()),
+                    ("target", format!("{:>9}", fmt(*target)).into()),
+                    (
+                        "difference",
+                        format!(
+                            "{:>10}",
+                            fmt_signed(*tracked - *target, config.duration_format)
+                        )
+                        .into()
+                    ),
+                    (
+                        "balance",
+                        format!("{:>10}", fmt_signed(balance, config.duration_format)).into()
+                    ),
+                ]
+            )
+        );
+    }
+
+    println!();
+    let owing = if balance < Duration::zero() {
+        "true"
+    } else {
+        "false"
+    };
+    println!(
+        "{}",
+        decorate(
+            "⚖️ ",
+            &i18n::tr(
+                "balance-total",
+                &[
+                    ("from", i18n::fmt_date(args.from, "date-format").into()),
+                    ("to", i18n::fmt_date(to, "date-format").into()),
+                    (
+                        "balance",
+                        config
+                            .theme
+                            .total
+                            .paint(fmt_signed(balance, config.duration_format))
+                            .to_string()
+                            .into()
+                    ),
+                    ("owing", owing.into()),
+                ]
+            )
+        )
+    );
+
+    Ok(())
+}
+
+pub fn run_overtime(config: &Config, args: &BalanceArgs) -> Result<()> {
+    let to = args.to.unwrap_or_else(|| Local::now().date_naive());
+    if args.from > to {
+        bail!("--from must not be after --to");
+    }
+    let client = get_client()?;
+    let totals = daily_totals(&get_range_entries(&client, args.from, to)?);
+    let days = daily_overtime(&totals, args.from, to, &target_work_hours(config));
+
+    let fmt = |dur| fmt_duration(dur, config.duration_format);
+    let mut total = Duration::zero();
+    for (date, tracked, overtime) in &days {
+        total += *overtime;
+        println!(
+            "{}",
+            i18n::tr(
+                "overtime-day",
+                &[
+                    ("date", i18n::fmt_date(*date, "day-heading-format").into()),
+                    ("tracked", format!("{:>9}", fmt(*tracked)).into()),
+                    (
+                        "overtime",
+                        format!("{:>10}", fmt_signed(*overtime, config.duration_format)).into()
+                    ),
+                    (
+                        "total",
+                        format!("{:>10}", fmt_signed(total, config.duration_format)).into()
+                    ),
+                ]
+            )
+        );
+    }
+
+    println!();
+    println!(
+        "{}",
+        decorate(
+            "⚖️ ",
+            &i18n::tr(
+                "overtime-total",
+                &[
+                    ("from", i18n::fmt_date(args.from, "date-format").into()),
+                    ("to", i18n::fmt_date(to, "date-format").into()),
+                    (
+                        "overtime",
+                        config
+                            .theme
+                            .total
+                            .paint(fmt_signed(total, config.duration_format))
+                            .to_string()
+                            .into()
+                    ),
+                ]
+            )
+        )
+    );
+
+    Ok(())
+}
+
+/// Gets each day in `from..=to` that is a work day or has tracked time, with
+/// its tracked time and how far that is over the day's target. Days that
+/// aren't work days have no target, so time tracked on them is all overtime,
+/// as in [`weekly_balance`].
+fn daily_overtime(
+    totals: &BTreeMap<NaiveDate, Duration>,
+    from: NaiveDate,
+    to: NaiveDate,
+    hours: &WorkHours,
+) -> Vec<(NaiveDate, Duration, Duration)> {
+    let target = hours.daily_target.duration();
+    from.iter_days()
+        .take_while(|date| *date <= to)
+        .filter_map(|date| {
+            // Whole seconds, so the overtime agrees with the displayed totals.
+            let tracked = Duration::seconds(totals.get(&date).map_or(0, |d| d.num_seconds()));
+            match hours.is_work_day(date) {
+                true => Some((date, tracked, tracked - target)),
+                false if tracked > Duration::zero() => Some((date, tracked, tracked)),
+                false => None,
+            }
+        })
+        .collect()
+}
+
+/// Splits `from..=to` into weeks starting on Mondays, returning each week's
+/// first day in range, tracked time, and target time.
+fn weekly_balance(
+    totals: &BTreeMap<NaiveDate, Duration>,
+    from: NaiveDate,
+    to: NaiveDate,
+    hours: &WorkHours,
+) -> Vec<(NaiveDate, Duration, Duration)> {
+    let target = hours.daily_target.duration();
+    let mut weeks: Vec<(NaiveDate, Duration, Duration)> = Vec::new();
+    let mut date = from;
+    while date <= to {
+        if weeks.is_empty() || date.weekday() == Weekday::Mon {
+            weeks.push((date, Duration::zero(), Duration::zero()));
+        }
+        let week = weeks.last_mut().unwrap();
+        // Whole seconds, so the balance agrees with the displayed totals.
+        let tracked = totals.get(&date).map_or(0, |d| d.num_seconds());
+        week.1 += Duration::seconds(tracked);
+        if hours.is_work_day(date) {
+            week.2 += target;
+        }
+        date = date + Days::new(1);
+    }
+
+    weeks
+}
+
+#[derive(Serialize)]
+struct ExportEntry<'a> {
+    start: Option<DateTime<Utc>>,
+    stop: Option<DateTime<Utc>>,
+    duration_seconds: i64,
+    project: Option<&'a str>,
+    description: Option<&'a str>,
+}
+
+pub fn run_export(args: &ExportArgs) -> Result<()> {
+    let client = get_client()?;
+    let (from, to) = args.range.resolve()?;
+    let entries = get_range_entries(&client, from, to)?;
+    let roundings = args.round.roundings(&client)?;
+    let exported: Vec<_> = entries
+        .iter()
+        .map(|e| ExportEntry {
+            start: e.start,
+            stop: e.stop,
+            duration_seconds: roundings.apply(e).num_seconds(),
+            project: e.project_name.as_deref(),
+            description: e.description.as_deref(),
+        })
+        .collect();
+
+    match args.format {
+        ExportFormat::Csv => {
+            println!("start,stop,duration_hours,project,description");
+            for e in &exported {
+                let fmt_time =
+                    |t: Option<DateTime<Utc>>| t.map(|t| t.to_rfc3339()).unwrap_or_default();
+                println!(
+                    "{},{},{},{},{}",
+                    fmt_time(e.start),
+                    fmt_time(e.stop),
+                    fmt_duration_decimal(Duration::seconds(e.duration_seconds)),
+                    csv_field(e.project.unwrap_or_default()),
+                    csv_field(e.description.unwrap_or_default()),
+                );
+            }
+        }
+        ExportFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&exported)?);
+        }
+        ExportFormat::Timew => {
+            print!("{}", timew_export(&intervals(&entries, &roundings))?);
+        }
+        ExportFormat::Timeclock => {
+            for (entry, start, stop) in intervals(&entries, &roundings) {
+                println!("{}", timeclock_lines(entry, start, stop));
+            }
+        }
+        ExportFormat::Org => {
+            print!("{}", org_outline(&intervals(&entries, &roundings)));
+        }
+        ExportFormat::Hledger => {
+            print!("{}", timedot(&entries, &roundings));
+        }
+    }
+
+    Ok(())
+}
+
+/// Gets the start and stop of each stopped entry, with the stop moved to
+/// match the rounded duration. Running entries have no interval yet.
+fn intervals<'a>(
+    entries: &'a [TimeEntry],
+    roundings: &Roundings,
+) -> Vec<(&'a TimeEntry, DateTime<Utc>, DateTime<Utc>)> {
+    entries
+        .iter()
+        .filter_map(|e| {
+            let (start, stop) = (e.start?, e.stop?);
+            let stop = match roundings.get(e.workspace_id) {
+                Some(_) => start + roundings.apply(e),
+                None => stop,
+            };
+
+            Some((e, start, stop))
+        })
+        .collect()
+}
+
+/// An interval the way `timew export` writes it and `timew import` reads it.
+#[derive(Serialize)]
+struct TimewInterval<'a> {
+    start: String,
+    end: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    annotation: Option<&'a str>,
+}
+
+/// Formats entries as a JSON array of Timewarrior intervals, one per line
+/// like `timew export`, tagged with their projects and tags and annotated
+/// with their descriptions.
+fn timew_export(intervals: &[(&TimeEntry, DateTime<Utc>, DateTime<Utc>)]) -> Result<String> {
+    const FORMAT: &str = "%Y%m%dT%H%M%SZ";
+    let lines = intervals
+        .iter()
+        .map(|(entry, start, stop)| {
+            serde_json::to_string(&TimewInterval {
+                start: start.format(FORMAT).to_string(),
+                end: stop.format(FORMAT).to_string(),
+                tags: entry
+                    .project_name
+                    .iter()
+                    .chain(&entry.tags)
+                    .map(String::as_str)
+                    .collect(),
+                annotation: entry.description.as_deref().filter(|d| !d.is_empty()),
+            })
+        })
+        .collect::<serde_json::Result<Vec<_>>>()?;
+
+    let mut export = String::from("[\n");
+    for (i, line) in lines.iter().enumerate() {
+        export += line;
+        export += if i + 1 < lines.len() { ",\n" } else { "\n" };
+    }
+    export += "]\n";
+
+    Ok(export)
+}
+
+/// Formats an entry as an hledger timeclock check-in and check-out, in local
+/// time, with the project as the account.
+fn timeclock_lines(entry: &TimeEntry, start: DateTime<Utc>, stop: DateTime<Utc>) -> String {
+    const FORMAT: &str = "%Y/%m/%d %H:%M:%S";
+    let mut check_in = format!(
+        "i {} {}",
+        start.with_timezone(&Local).format(FORMAT),
+        ledger_account(entry)
+    );
+    if let Some(description) = entry.description.as_deref().filter(|d| !d.is_empty()) {
+        check_in += &format!("  {}", description.replace('\n', " "));
+    }
+
+    format!(
+        "{check_in}\no {}",
+        stop.with_timezone(&Local).format(FORMAT)
+    )
+}
+
+/// Gets the hledger account an entry's time is posted to.
+fn ledger_account(entry: &TimeEntry) -> String {
+    // Two spaces end an account name, so runs of whitespace in a project
+    // name would cut it short.
+    entry
+        .project_name
+        .as_deref()
+        .unwrap_or("no project")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Formats entries as hledger timedot, with each local day's hours summed
+/// per project.
+fn timedot(entries: &[TimeEntry], roundings: &Roundings) -> String {
+    let mut days: BTreeMap<NaiveDate, BTreeMap<String, Duration>> = BTreeMap::new();
+    for entry in entries {
+        let Some(start) = entry.start else {
+            continue;
+        };
+        let duration = roundings.apply(entry);
+        *days
+            .entry(start.with_timezone(&Local).date_naive())
+            .or_default()
+            .entry(ledger_account(entry))
+            .or_insert_with(Duration::zero) += duration;
+    }
+
+    let mut journal = String::new();
+    for (date, accounts) in days {
+        journal += &format!("{date}\n");
+        for (account, duration) in accounts {
+            journal += &format!("{account}  {}\n", fmt_duration_decimal(duration));
+        }
+        journal += "\n";
+    }
+
+    journal
+}
+
+/// Formats intervals as an Org-mode outline with a heading per project and a
+/// subheading per description, each holding its CLOCK lines in local time.
+fn org_outline(intervals: &[(&TimeEntry, DateTime<Utc>, DateTime<Utc>)]) -> String {
+    const FORMAT: &str = "[%Y-%m-%d %a %H:%M]";
+    let mut projects: BTreeMap<&str, BTreeMap<&str, Vec<String>>> = BTreeMap::new();
+    for (entry, start, stop) in intervals {
+        // Org clocks count whole minutes, so the total is taken from the
+        // printed times.
+        let start = start
+            .with_timezone(&Local)
+            .with_second(0)
+            .unwrap_or_default();
+        let stop = stop
+            .with_timezone(&Local)
+            .with_second(0)
+            .unwrap_or_default();
+        let minutes = (stop - start).num_minutes();
+        let clock = format!(
+            "CLOCK: {}--{} => {:>2}:{:02}",
+            start.format(FORMAT),
+            stop.format(FORMAT),
+            minutes / 60,
+            minutes % 60
+        );
+        projects
+            .entry(entry.project_name.as_deref().unwrap_or("No project"))
+            .or_default()
+            .entry(
+                entry
+                    .description
+                    .as_deref()
+                    .filter(|d| !d.is_empty())
+                    .unwrap_or("No description"),
+            )
+            .or_default()
+            .push(clock);
+    }
+
+    let mut outline = String::new();
+    for (project, descriptions) in projects {
+        outline += &format!("* {project}\n");
+        for (description, clocks) in descriptions {
+            outline += &format!("** {}\n:LOGBOOK:\n", description.replace('\n', " "));
+            for clock in clocks {
+                outline += &format!("{clock}\n");
+            }
+            outline += ":END:\n";
+        }
+    }
+
+    outline
+}
+
+/// Quotes a CSV field if it contains characters that require it.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weekly_balance_targets_work_days() {
+        let date = |d| NaiveDate::from_ymd_opt(2024, 1, d).unwrap();
+        let totals = BTreeMap::from([
+            (date(5), Duration::hours(9)),
+            (date(6), Duration::hours(2)),
+            (date(8), Duration::hours(8)),
+        ]);
+        // Friday the 5th through Tuesday the 9th.
+        let weeks = weekly_balance(&totals, date(5), date(9), &WorkHours::default());
+
+        assert_eq!(
+            vec![
+                (date(5), Duration::hours(11), Duration::hours(8)),
+                (date(8), Duration::hours(8), Duration::hours(16)),
+            ],
+            weeks
+        );
+    }
+
+    #[test]
+    fn overtime_counts_days_off_as_overtime() {
+        let date = |d| NaiveDate::from_ymd_opt(2024, 1, d).unwrap();
+        let totals = BTreeMap::from([
+            (date(5), Duration::hours(9)),
+            (date(6), Duration::hours(2)),
+            (date(8), Duration::minutes(450)),
+        ]);
+        // Friday the 5th through Monday the 8th.
+        let days = daily_overtime(&totals, date(5), date(8), &WorkHours::default());
+
+        assert_eq!(
+            vec![
+                (date(5), Duration::hours(9), Duration::hours(1)),
+                (date(6), Duration::hours(2), Duration::hours(2)),
+                (date(8), Duration::minutes(450), Duration::minutes(-30)),
+            ],
+            days
+        );
+    }
+
+    #[test]
+    fn formats_interval_exports() {
+        let entry: TimeEntry = serde_json::from_value(serde_json::json!({
+            "billable": false,
+            "client_id": null,
+            "description": "Fix \"login\" bug",
+            "duration": 3000,
+            "id": 1,
+            "is_running": false,
+            "project_id": 2,
+            "project_name": "Web  site",
+            "start": "2024-06-03T09:00:00Z",
+            "stop": "2024-06-03T09:50:00Z",
+            "tags": ["dev", "on call"],
+            "task_id": null,
+            "workspace_id": 1,
+        }))
+        .unwrap();
+        let rounding = Rounding {
+            interval: "1h".parse().unwrap(),
+            policy: RoundPolicy::Nearest,
+        };
+        let roundings = Roundings {
+            fixed: Some(rounding),
+            ..Default::default()
+        };
+        let [(entry, start, stop)] = intervals(std::slice::from_ref(&entry), &roundings)[..] else {
+            panic!("expected one interval");
+        };
+
+        assert_eq!(
+            concat!(
+                "[\n",
+                r#"{"start":"20240603T090000Z","end":"20240603T100000Z","tags":["Web  site","dev","on call"],"annotation":"Fix \"login\" bug"}"#,
+                "\n]\n"
+            ),
+            timew_export(&[(entry, start, stop)]).unwrap()
+        );
+        let local = |t: DateTime<Utc>| t.with_timezone(&Local).format("%Y/%m/%d %H:%M:%S");
+        assert_eq!(
+            format!(
+                "i {} Web site  Fix \"login\" bug\no {}",
+                local(start),
+                local(stop)
+            ),
+            timeclock_lines(entry, start, stop)
+        );
+    }
+
+    #[test]
+    fn groups_org_clocks_by_project() {
+        let entry = |description: &str, project: Option<&str>| -> TimeEntry {
+            serde_json::from_value(serde_json::json!({
+                "billable": false,
+                "client_id": null,
+                "description": description,
+                "duration": 5430,
+                "id": 1,
+                "is_running": false,
+                "project_id": null,
+                "project_name": project,
+                "start": "2024-06-03T09:00:00Z",
+                "stop": "2024-06-03T10:30:30Z",
+                "tags": [],
+                "task_id": null,
+                "workspace_id": 1,
+            }))
+            .unwrap()
+        };
+        let entries = [
+            entry("Standup", Some("Internal")),
+            entry("", None),
+            entry("Standup", Some("Internal")),
+        ];
+        let outline = org_outline(&intervals(&entries, &Roundings::default()));
+
+        let headings: Vec<_> = outline.lines().filter(|l| l.starts_with('*')).collect();
+        assert_eq!(
+            vec![
+                "* Internal",
+                "** Standup",
+                "* No project",
+                "** No description"
+            ],
+            headings
+        );
+        assert_eq!(3, outline.matches("=>  1:30\n").count());
+    }
+
+    #[test]
+    fn sums_timedot_hours_per_day_and_project() {
+        let entry = |start: &str, duration: i64, project: Option<&str>| -> TimeEntry {
+            serde_json::from_value(serde_json::json!({
+                "billable": false,
+                "client_id": null,
+                "description": null,
+                "duration": duration,
+                "id": 1,
+                "is_running": false,
+                "project_id": null,
+                "project_name": project,
+                "start": start,
+                "stop": null,
+                "tags": [],
+                "task_id": null,
+                "workspace_id": 1,
+            }))
+            .unwrap()
+        };
+        let entries = [
+            entry("2024-06-03T12:00:00Z", 3600, Some("Web  site")),
+            entry("2024-06-03T13:00:00Z", 1800, Some("Web  site")),
+            entry("2024-06-03T14:00:00Z", 900, None),
+        ];
+        let date = entries[0].start.unwrap().with_timezone(&Local).date_naive();
+
+        assert_eq!(
+            format!("{date}\nWeb site  1.50\nno project  0.25\n\n"),
+            timedot(&entries, &Roundings::default())
+        );
+    }
+}
@@ -0,0 +1,133 @@
+//! Recreating time entries from a backup file.
+
+use crate::{
+    bulk::fmt_dated_entry, decorate, find_workspaces, get_client, journal, when::parse_date,
+};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Local, NaiveDate, Utc};
+use clap::Args;
+use std::{collections::HashSet, fs, path::PathBuf};
+use tgl_cli::{TimeEntry, TimeEntryUpdate};
+
+#[derive(Args)]
+pub struct RestoreArgs {
+    /// JSON array of time entries in the format tgl saves them
+    file: PathBuf,
+    /// Only restore entries from this day on, e.g. 2024-06-03 or "last monday"
+    #[arg(long, value_parser = parse_date)]
+    from: Option<NaiveDate>,
+    /// Only restore entries up to and including this day, e.g. yesterday
+    #[arg(long, value_parser = parse_date)]
+    to: Option<NaiveDate>,
+    /// Only restore entries in the workspace with this name
+    #[arg(long)]
+    workspace: Option<String>,
+}
+
+/// What identifies an entry that was already restored: its start time and
+/// description.
+type EntryKey = (Option<DateTime<Utc>>, String);
+
+fn entry_key(entry: &TimeEntry) -> EntryKey {
+    (entry.start, entry.description.clone().unwrap_or_default())
+}
+
+/// Whether the entry started within `[from, to]`, in local dates.
+fn in_range(entry: &TimeEntry, from: Option<NaiveDate>, to: Option<NaiveDate>) -> bool {
+    let Some(date) = entry.start.map(|s| s.with_timezone(&Local).date_naive()) else {
+        return false;
+    };
+
+    from.is_none_or(|from| date >= from) && to.is_none_or(|to| date <= to)
+}
+
+pub fn run_restore(args: &RestoreArgs) -> Result<()> {
+    let contents =
+        fs::read(&args.file).with_context(|| format!("Failed to read {}", args.file.display()))?;
+    let backup: Vec<TimeEntry> = serde_json::from_slice(&contents)
+        .with_context(|| format!("Failed to parse {}", args.file.display()))?;
+
+    let client = get_client()?;
+    let workspace_ids: Vec<_> = find_workspaces(&client, args.workspace.as_deref())?
+        .iter()
+        .map(|w| w.id)
+        .collect();
+    // Running entries are left out so that restoring never starts a timer.
+    let mut entries: Vec<_> = backup
+        .iter()
+        .filter(|e| !e.is_running && workspace_ids.contains(&e.workspace_id))
+        .filter(|e| in_range(e, args.from, args.to))
+        .collect();
+    entries.sort_by_key(|e| e.start);
+    let (Some(first), Some(last)) = (entries.first(), entries.last()) else {
+        println!("{}", decorate("🤷", "No entries to restore"));
+        return Ok(());
+    };
+
+    let existing: HashSet<_> = client
+        .get_time_entries(
+            first.start.unwrap_or_default(),
+            last.start.unwrap_or_default() + Duration::seconds(1),
+        )
+        .context("Failed to retrieve time entries")?
+        .iter()
+        .map(entry_key)
+        .collect();
+    let mut skipped = 0;
+    for entry in &entries {
+        if existing.contains(&entry_key(entry)) {
+            skipped += 1;
+            continue;
+        }
+
+        let restored = client
+            .copy_time_entry(entry, &TimeEntryUpdate::new())
+            .context("Failed to create time entry")?;
+        println!("{}", fmt_dated_entry(&restored));
+    }
+    journal::forget();
+
+    println!(
+        "{}",
+        decorate(
+            "✅",
+            &format!(
+                "Restored {} entries, skipped {skipped} that already exist",
+                entries.len() - skipped
+            )
+        )
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_by_local_date() {
+        let entry: TimeEntry = serde_json::from_value(serde_json::json!({
+            "billable": false,
+            "client_id": null,
+            "description": "Standup",
+            "duration": 900,
+            "id": 1,
+            "is_running": false,
+            "project_id": null,
+            "project_name": null,
+            "start": "2024-06-03T12:00:00Z",
+            "stop": "2024-06-03T12:15:00Z",
+            "tags": [],
+            "task_id": null,
+            "workspace_id": 1,
+        }))
+        .unwrap();
+        let date = entry.start.unwrap().with_timezone(&Local).date_naive();
+
+        assert!(in_range(&entry, None, None));
+        assert!(in_range(&entry, Some(date), Some(date)));
+        assert!(!in_range(&entry, date.succ_opt(), None));
+        assert!(!in_range(&entry, None, date.pred_opt()));
+    }
+}
@@ -0,0 +1,121 @@
+//! A look back at yesterday's time entries.
+
+use crate::{
+    check::fmt_entry, config::Config, decorate, duration::fmt_duration, eprintln_stale_banner,
+    get_client, local_midnight, println_entry, targets::target_work_hours,
+};
+use anyhow::{Context, Result};
+use chrono::{Days, Duration, Local};
+use tgl_cli::TimeEntry;
+
+/// What an entry is missing, if anything.
+fn missing_fields(entry: &TimeEntry) -> Vec<&'static str> {
+    let mut missing = Vec::new();
+    if entry.project_id.is_none() {
+        missing.push("project");
+    }
+    if entry
+        .description
+        .as_deref()
+        .is_none_or(|d| d.trim().is_empty())
+    {
+        missing.push("description");
+    }
+
+    missing
+}
+
+pub fn run_yesterday(config: &Config) -> Result<()> {
+    let client = get_client()?;
+    let yesterday = Local::now().date_naive() - Days::new(1);
+    let entries = client
+        .get_entries_touching(
+            local_midnight(yesterday).into(),
+            local_midnight(yesterday + Days::new(1)).into(),
+        )
+        .context("Failed to retrieve time entries")?;
+    eprintln_stale_banner(&client);
+
+    let mut total = Duration::zero();
+    for entry in &entries {
+        println_entry(config, None, entry, false);
+        total += entry.duration;
+    }
+    if !entries.is_empty() {
+        println!();
+    }
+
+    let fmt = |dur| fmt_duration(dur, config.duration_format);
+    let target = config.work_hours.daily_target.duration();
+    let comparison = if !target_work_hours(config).is_work_day(yesterday) {
+        "It wasn't a work day.".to_string()
+    } else if total < target {
+        format!(
+            "That's {} short of your {} target.",
+            fmt(target - total),
+            fmt(target)
+        )
+    } else {
+        format!("That meets your {} target.", fmt(target))
+    };
+    println!(
+        "{}",
+        decorate(
+            "⏱ ",
+            &format!(
+                "{} logged yesterday. {comparison}",
+                config.theme.total.paint(fmt(total))
+            )
+        )
+    );
+
+    let incomplete: Vec<_> = entries
+        .iter()
+        .map(|e| (e, missing_fields(e)))
+        .filter(|(_, missing)| !missing.is_empty())
+        .collect();
+    if !incomplete.is_empty() {
+        println!();
+        println!("{}", decorate("⚠️ ", "Entries that need attention:"));
+        for (entry, missing) in &incomplete {
+            println!("    {} (no {})", fmt_entry(entry), missing.join(" or "));
+        }
+        println!("Fix them with `tgl edit --id <ID>`.");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_missing_fields() {
+        let entry = |project_id: Option<i64>, description: &str| -> TimeEntry {
+            serde_json::from_value(serde_json::json!({
+                "billable": false,
+                "client_id": null,
+                "description": description,
+                "duration": 60,
+                "id": 1,
+                "is_running": false,
+                "project_id": project_id,
+                "project_name": null,
+                "start": "2024-06-03T09:00:00Z",
+                "stop": "2024-06-03T09:01:00Z",
+                "tags": [],
+                "task_id": null,
+                "workspace_id": 1,
+            }))
+            .unwrap()
+        };
+
+        assert!(missing_fields(&entry(Some(1), "Standup")).is_empty());
+        assert_eq!(vec!["description"], missing_fields(&entry(Some(1), " ")));
+        assert_eq!(
+            vec!["project", "description"],
+            missing_fields(&entry(None, ""))
+        );
+    }
+}
@@ -0,0 +1,174 @@
+//! Rounding of durations to billing intervals, e.g. the nearest 15 minutes.
+
+use chrono::Duration;
+use clap::ValueEnum;
+use serde::{de, Deserialize, Deserializer};
+use std::str::FromStr;
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RoundPolicy {
+    /// Always round up to the next interval
+    Up,
+    /// Always round down to the previous interval
+    Down,
+    /// Round to the closest interval, rounding halfway values up
+    #[default]
+    Nearest,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RoundPer {
+    /// Round each time entry before adding it to totals
+    #[default]
+    Entry,
+    /// Round the totals of each report row
+    Group,
+}
+
+/// A rounding interval such as `6m`, `15m`, or `1h`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Interval(Duration);
+
+impl FromStr for Interval {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid interval '{s}', expected e.g. 30s, 15m, or 1h");
+        let (value, unit) = s.split_at(s.find(|c: char| !c.is_ascii_digit()).ok_or_else(invalid)?);
+        let value: i64 = value.parse().map_err(|_| invalid())?;
+        let interval = match unit {
+            "s" => Duration::try_seconds(value),
+            "m" | "min" => Duration::try_minutes(value),
+            "h" => Duration::try_hours(value),
+            _ => return Err(invalid()),
+        }
+        .ok_or_else(invalid)?;
+        if interval <= Duration::zero() {
+            return Err(invalid());
+        }
+
+        Ok(Interval(interval))
+    }
+}
+
+impl Interval {
+    pub fn duration(self) -> Duration {
+        self.0
+    }
+}
+
+/// What `--round` rounds to: a fixed interval, or the rounding set up in
+/// each entry's workspace.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoundTo {
+    Interval(Interval),
+    Workspace,
+}
+
+impl FromStr for RoundTo {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("workspace") {
+            return Ok(RoundTo::Workspace);
+        }
+
+        s.parse().map(RoundTo::Interval)
+    }
+}
+
+impl<'de> Deserialize<'de> for Interval {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rounding {
+    pub interval: Interval,
+    pub policy: RoundPolicy,
+}
+
+impl Rounding {
+    pub fn apply(&self, dur: Duration) -> Duration {
+        let step = self.interval.0.num_seconds();
+        let secs = dur.num_seconds();
+        let down = secs.div_euclid(step) * step;
+        let rem = secs - down;
+        let rounded = match self.policy {
+            _ if rem == 0 => secs,
+            RoundPolicy::Down => down,
+            RoundPolicy::Up => down + step,
+            RoundPolicy::Nearest if rem * 2 >= step => down + step,
+            RoundPolicy::Nearest => down,
+        };
+
+        Duration::seconds(rounded)
+    }
+
+    /// Converts a Toggl workspace's rounding setting, where `direction` is
+    /// -1 to round down, 0 to the nearest interval, and 1 up. Zero minutes
+    /// means no rounding.
+    pub fn from_toggl(direction: i64, minutes: i64) -> Option<Self> {
+        let policy = match direction {
+            -1 => RoundPolicy::Down,
+            0 => RoundPolicy::Nearest,
+            1 => RoundPolicy::Up,
+            _ => return None,
+        };
+        let interval = Interval(Duration::minutes(minutes));
+
+        (minutes > 0).then_some(Self { interval, policy })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round(interval: &str, policy: RoundPolicy, minutes: i64, seconds: i64) -> i64 {
+        let rounding = Rounding {
+            interval: interval.parse().unwrap(),
+            policy,
+        };
+        rounding
+            .apply(Duration::minutes(minutes) + Duration::seconds(seconds))
+            .num_minutes()
+    }
+
+    #[test]
+    fn apply_policies() {
+        assert_eq!(15, round("15m", RoundPolicy::Up, 1, 0));
+        assert_eq!(0, round("15m", RoundPolicy::Down, 14, 59));
+        assert_eq!(15, round("15m", RoundPolicy::Nearest, 7, 30));
+        assert_eq!(0, round("15m", RoundPolicy::Nearest, 7, 29));
+        assert_eq!(30, round("6m", RoundPolicy::Up, 30, 0));
+        assert_eq!(60, round("1h", RoundPolicy::Nearest, 31, 0));
+    }
+
+    #[test]
+    fn parse_interval() {
+        assert_eq!(Ok(Interval(Duration::minutes(6))), "6m".parse());
+        assert_eq!(Ok(Interval(Duration::hours(1))), "1h".parse());
+        assert_eq!(Ok(Interval(Duration::seconds(30))), "30s".parse());
+        assert!("15".parse::<Interval>().is_err());
+        assert!("0m".parse::<Interval>().is_err());
+        assert!("m".parse::<Interval>().is_err());
+        assert!("99999999999999h".parse::<Interval>().is_err());
+        assert_eq!(Ok(RoundTo::Workspace), "workspace".parse());
+    }
+
+    #[test]
+    fn convert_toggl_rounding() {
+        assert_eq!(
+            Some(Rounding {
+                interval: "15m".parse().unwrap(),
+                policy: RoundPolicy::Up,
+            }),
+            Rounding::from_toggl(1, 15)
+        );
+        assert_eq!(None, Rounding::from_toggl(0, 0));
+    }
+}
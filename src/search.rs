@@ -0,0 +1,160 @@
+//! Finding past time entries by their descriptions.
+
+use crate::{
+    config::Config, decorate, duration::fmt_duration, eprintln_stale_banner, get_client, i18n,
+    local_midnight, project_label, when::parse_date,
+};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Days, Duration, Local, NaiveDate};
+use clap::Args;
+use regex::{Regex, RegexBuilder};
+use tgl_cli::TimeEntry;
+
+/// How far back searches look without --from.
+const SEARCH_DAYS: u64 = 90;
+
+#[derive(Args)]
+pub struct SearchArgs {
+    /// Text or regular expression to find in descriptions, ignoring case
+    pattern: String,
+    /// Only entries in this project, ignoring case
+    #[arg(long, value_name = "NAME")]
+    project: Option<String>,
+    /// First day to search, e.g. 2024-06-03 or "last month" [default: 90 days
+    /// ago]
+    #[arg(long, value_parser = parse_date)]
+    from: Option<NaiveDate>,
+    /// Last day to search [default: today]
+    #[arg(long, value_parser = parse_date)]
+    to: Option<NaiveDate>,
+}
+
+fn matches(pattern: &Regex, project: Option<&str>, entry: &TimeEntry) -> bool {
+    let project_matches = project.is_none_or(|project| {
+        entry
+            .project_name
+            .as_ref()
+            .is_some_and(|name| name.eq_ignore_ascii_case(project))
+    });
+
+    project_matches && pattern.is_match(entry.description.as_deref().unwrap_or_default())
+}
+
+pub fn run_search(config: &Config, args: &SearchArgs) -> Result<()> {
+    let pattern = RegexBuilder::new(&args.pattern)
+        .case_insensitive(true)
+        .build()
+        .context("Failed to parse the search pattern")?;
+    let today = Local::now().date_naive();
+    let to = args.to.unwrap_or(today);
+    let from = args.from.unwrap_or(today - Days::new(SEARCH_DAYS));
+    if from > to {
+        bail!("--from must not be after --to");
+    }
+
+    let client = get_client()?;
+    // With the local store, months of history come from its index.
+    let mut entries = client
+        .get_indexed_entries(
+            local_midnight(from).into(),
+            local_midnight(to + Days::new(1)).into(),
+        )
+        .context("Failed to retrieve time entries")?;
+    eprintln_stale_banner(&client);
+    entries.retain(|e| matches(&pattern, args.project.as_deref(), e));
+    if entries.is_empty() {
+        println!(
+            "{}",
+            decorate(
+                "🤷",
+                &format!(
+                    "Nothing matches from {} to {}",
+                    i18n::fmt_date(from, "date-format"),
+                    i18n::fmt_date(to, "date-format")
+                )
+            )
+        );
+        return Ok(());
+    }
+
+    // Newest first, since the latest match is usually what's wanted.
+    entries.sort_by_key(|e| std::cmp::Reverse(e.start));
+    let mut total = Duration::zero();
+    for entry in &entries {
+        let date = entry
+            .start
+            .map(|s| DateTime::<Local>::from(s).date_naive())
+            .map(|d| i18n::fmt_date(d, "day-heading-format"))
+            .unwrap_or_default();
+        println!(
+            "{date} {:>8} [{}] {}",
+            fmt_duration(entry.duration, config.duration_format),
+            config.theme.paint_project(
+                project_label(
+                    config,
+                    entry.client_name.as_deref(),
+                    entry.project_name.as_deref().unwrap_or_default()
+                ),
+                entry.project_color.as_deref()
+            ),
+            entry.description.as_deref().unwrap_or_default()
+        );
+        total += entry.duration;
+    }
+
+    println!();
+    let count = match entries.len() {
+        1 => "1 match".to_string(),
+        count => format!("{count} matches"),
+    };
+    println!(
+        "{}",
+        decorate(
+            "⏱ ",
+            &format!(
+                "{count}, {} in total.",
+                config
+                    .theme
+                    .total
+                    .paint(fmt_duration(total, config.duration_format))
+            )
+        )
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_descriptions_and_projects() {
+        let entry: TimeEntry = serde_json::from_value(serde_json::json!({
+            "billable": false,
+            "client_id": null,
+            "description": "Billing migration: backfill",
+            "duration": 3000,
+            "id": 1,
+            "is_running": false,
+            "project_id": 2,
+            "project_name": "Website",
+            "start": "2024-06-03T09:00:00Z",
+            "stop": "2024-06-03T09:50:00Z",
+            "tags": [],
+            "task_id": null,
+            "workspace_id": 1,
+        }))
+        .unwrap();
+        let pattern = |p: &str| RegexBuilder::new(p).case_insensitive(true).build().unwrap();
+
+        assert!(matches(&pattern("billing migration"), None, &entry));
+        assert!(matches(
+            &pattern("^billing.*fill$"),
+            Some("website"),
+            &entry
+        ));
+        assert!(!matches(&pattern("billing"), Some("Internal"), &entry));
+        assert!(!matches(&pattern("invoices"), None, &entry));
+    }
+}
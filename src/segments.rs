@@ -0,0 +1,103 @@
+//! Merging entries for the same work that stopping and restarting the timer
+//! split into segments, for display only.
+
+use chrono::Local;
+use tgl_cli::TimeEntry;
+
+/// One row of a listing: an entry, or several consecutive ones merged.
+pub struct Row {
+    /// The 1-based index of the row's first entry in the listing.
+    pub index: usize,
+    pub entry: TimeEntry,
+    /// How many entries the row merges.
+    pub segments: usize,
+}
+
+/// Gets one row per entry, as listed.
+pub fn rows(entries: &[TimeEntry]) -> Vec<Row> {
+    entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| Row {
+            index: i + 1,
+            entry: entry.clone(),
+            segments: 1,
+        })
+        .collect()
+}
+
+/// Merges consecutive entries on the same day with the same project and
+/// description. A merged row spans its entries' starts and stops, adds up
+/// their durations, and is running if any of them is.
+pub fn collapse(entries: &[TimeEntry]) -> Vec<Row> {
+    let mut collapsed: Vec<Row> = Vec::new();
+    for row in rows(entries) {
+        match collapsed.last_mut() {
+            Some(last) if same_work(&last.entry, &row.entry) => {
+                let (merged, entry) = (&mut last.entry, row.entry);
+                merged.duration += entry.duration;
+                merged.start = merged.start.min(entry.start);
+                merged.stop = match (merged.stop, entry.stop) {
+                    (Some(a), Some(b)) => Some(a.max(b)),
+                    _ => None,
+                };
+                merged.is_running |= entry.is_running;
+                last.segments += 1;
+            }
+            _ => collapsed.push(row),
+        }
+    }
+
+    collapsed
+}
+
+fn same_work(a: &TimeEntry, b: &TimeEntry) -> bool {
+    let date = |e: &TimeEntry| e.start.map(|s| s.with_timezone(&Local).date_naive());
+
+    a.project_id == b.project_id && a.description == b.description && date(a) == date(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Duration, Utc};
+
+    #[test]
+    fn merges_consecutive_segments() {
+        let entry = |project: i64, description: &str, start: &str, minutes| -> TimeEntry {
+            let start: DateTime<Utc> = start.parse().unwrap();
+            serde_json::from_value(serde_json::json!({
+                "billable": false,
+                "client_id": null,
+                "description": description,
+                "duration": minutes * 60,
+                "id": 1,
+                "is_running": false,
+                "project_id": project,
+                "project_name": null,
+                "start": start,
+                "stop": start + Duration::minutes(minutes),
+                "tags": [],
+                "task_id": null,
+                "workspace_id": 1,
+            }))
+            .unwrap()
+        };
+        let entries = [
+            entry(1, "Fix bug", "2024-06-03T12:00:00Z", 30),
+            entry(1, "Fix bug", "2024-06-03T11:00:00Z", 20),
+            entry(1, "Fix bug", "2024-06-03T10:00:00Z", 10),
+            entry(2, "Standup", "2024-06-03T09:45:00Z", 15),
+            entry(1, "Fix bug", "2024-06-03T09:00:00Z", 45),
+        ];
+        let rows = collapse(&entries);
+
+        let summary: Vec<_> = rows
+            .iter()
+            .map(|r| (r.index, r.segments, r.entry.duration.num_minutes()))
+            .collect();
+        assert_eq!(vec![(1, 3, 60), (4, 1, 15), (5, 1, 45)], summary);
+        assert_eq!(entries[2].start, rows[0].entry.start);
+        assert_eq!(entries[0].stop, rows[0].entry.stop);
+    }
+}
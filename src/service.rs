@@ -0,0 +1,255 @@
+//! Running `tgl daemon` at login, as a systemd user service on Linux or a
+//! launchd agent on macOS.
+
+use crate::{daemon, decorate, profile, rounding::Interval};
+use anyhow::{anyhow, bail, Context, Result};
+use clap::{Args, Subcommand};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+#[derive(Subcommand)]
+pub enum ServiceCommand {
+    /// Run the daemon at login, as a systemd user service on Linux or a
+    /// launchd agent on macOS, and start it now
+    Install(InstallArgs),
+    /// Stop the daemon and no longer run it at login
+    Uninstall,
+    /// Show whether the daemon is installed and running
+    Status,
+}
+
+#[derive(Args)]
+pub struct InstallArgs {
+    /// How often the daemon polls Toggl, e.g. 30s or 2m
+    #[arg(long, default_value = "30s")]
+    interval: Interval,
+    /// Also serve the daemon's HTTP API on this port of localhost
+    #[arg(long)]
+    port: Option<u16>,
+    /// Also receive Toggl webhooks on this port of localhost
+    #[arg(long, value_name = "PORT")]
+    webhook_port: Option<u16>,
+}
+
+/// The service manager that runs the daemon on this platform.
+#[derive(Clone, Copy)]
+enum Manager {
+    Systemd,
+    Launchd,
+}
+
+impl Manager {
+    fn current() -> Result<Self> {
+        match env::consts::OS {
+            "linux" => Ok(Manager::Systemd),
+            "macos" => Ok(Manager::Launchd),
+            os => bail!("Installing the daemon isn't supported on {os}"),
+        }
+    }
+
+    /// The unit or agent name, with the profile for any but the default one
+    /// so that each profile can have its own daemon.
+    fn name(self) -> String {
+        let base = match self {
+            Manager::Systemd => "tgl-daemon",
+            Manager::Launchd => "com.github.blachniet.tgl.daemon",
+        };
+        match profile() {
+            "default" => base.to_string(),
+            profile => format!("{base}-{profile}"),
+        }
+    }
+
+    fn path(self) -> Result<PathBuf> {
+        let path = match self {
+            Manager::Systemd => dirs::config_dir().map(|dir| {
+                dir.join("systemd")
+                    .join("user")
+                    .join(format!("{}.service", self.name()))
+            }),
+            Manager::Launchd => dirs::home_dir().map(|dir| {
+                dir.join("Library")
+                    .join("LaunchAgents")
+                    .join(format!("{}.plist", self.name()))
+            }),
+        };
+
+        path.ok_or_else(|| anyhow!("Failed to find where to install the daemon"))
+    }
+}
+
+/// The command line that runs the daemon for the current profile.
+fn daemon_command(exe: &Path, args: &InstallArgs) -> Vec<String> {
+    let mut command = vec![exe.display().to_string()];
+    if profile() != "default" {
+        command.extend(["--profile".to_string(), profile().to_string()]);
+    }
+    command.extend([
+        "daemon".to_string(),
+        "--interval".to_string(),
+        format!("{}s", args.interval.duration().num_seconds()),
+    ]);
+    if let Some(port) = args.port {
+        command.extend(["--port".to_string(), port.to_string()]);
+    }
+    if let Some(port) = args.webhook_port {
+        command.extend(["--webhook-port".to_string(), port.to_string()]);
+    }
+
+    command
+}
+
+fn systemd_unit(command: &[String]) -> String {
+    let exec_start = command
+        .iter()
+        .map(|arg| format!("\"{}\"", arg.replace('\\', "\\\\").replace('"', "\\\"")))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    format!(
+        "[Unit]
+Description=tgl daemon
+
+[Service]
+ExecStart={exec_start}
+Restart=on-failure
+
+[Install]
+WantedBy=default.target
+"
+    )
+}
+
+fn launchd_plist(label: &str, command: &[String]) -> String {
+    let escape = |s: &str| {
+        s.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+    };
+    let arguments: String = command
+        .iter()
+        .map(|arg| format!("        <string>{}</string>\n", escape(arg)))
+        .collect();
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{}</string>
+    <key>ProgramArguments</key>
+    <array>
+{arguments}    </array>
+    <key>RunAtLoad</key>
+    <true/>
+    <key>KeepAlive</key>
+    <true/>
+</dict>
+</plist>
+"#,
+        escape(label)
+    )
+}
+
+fn run(program: &str, args: &[&str]) -> Result<()> {
+    let status = Command::new(program)
+        .args(args)
+        .status()
+        .with_context(|| format!("Failed to run {program}"))?;
+    if !status.success() {
+        bail!("{program} {} failed with {status}", args.join(" "));
+    }
+
+    Ok(())
+}
+
+pub fn run_service(command: &ServiceCommand) -> Result<()> {
+    let manager = Manager::current()?;
+    let path = manager.path()?;
+    let name = manager.name();
+    match command {
+        ServiceCommand::Install(args) => {
+            let exe = env::current_exe().context("Failed to find the tgl executable")?;
+            let command = daemon_command(&exe, args);
+            let contents = match manager {
+                Manager::Systemd => systemd_unit(&command),
+                Manager::Launchd => launchd_plist(&name, &command),
+            };
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)
+                    .with_context(|| format!("Failed to create {}", dir.display()))?;
+            }
+            fs::write(&path, contents)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            match manager {
+                Manager::Systemd => {
+                    run("systemctl", &["--user", "daemon-reload"])?;
+                    run("systemctl", &["--user", "enable", "--now", &name])?;
+                }
+                Manager::Launchd => run("launchctl", &["load", "-w", &path.to_string_lossy()])?,
+            }
+            println!(
+                "{}",
+                decorate("✅", &format!("Installed {}", path.display()))
+            );
+        }
+        ServiceCommand::Uninstall => {
+            if !path.exists() {
+                println!("{}", decorate("🤷", "The daemon isn't installed"));
+                return Ok(());
+            }
+            match manager {
+                Manager::Systemd => run("systemctl", &["--user", "disable", "--now", &name])?,
+                Manager::Launchd => run("launchctl", &["unload", "-w", &path.to_string_lossy()])?,
+            }
+            fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+            if let Manager::Systemd = manager {
+                run("systemctl", &["--user", "daemon-reload"])?;
+            }
+            println!(
+                "{}",
+                decorate("✅", &format!("Uninstalled {}", path.display()))
+            );
+        }
+        ServiceCommand::Status => {
+            match path.exists() {
+                true => println!("Installed: {}", path.display()),
+                false => println!("Installed: no"),
+            }
+            // Whether the daemon answers matters more than what the service
+            // manager thinks, and covers a daemon started by hand too.
+            let running = daemon::is_running();
+            println!("Running: {}", if running { "yes" } else { "no" });
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_units() {
+        let command = vec![
+            "/home/me/my bin/tgl".to_string(),
+            "daemon".to_string(),
+            "--interval".to_string(),
+            "30s".to_string(),
+        ];
+
+        assert!(systemd_unit(&command)
+            .contains("ExecStart=\"/home/me/my bin/tgl\" \"daemon\" \"--interval\" \"30s\"\n"));
+        assert!(
+            launchd_plist("com.github.blachniet.tgl.daemon", &command).contains(
+                "        <string>/home/me/my bin/tgl</string>\n        <string>daemon</string>\n"
+            )
+        );
+    }
+}
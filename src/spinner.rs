@@ -0,0 +1,47 @@
+//! A spinner shown while waiting on Toggl, so slow requests don't look like
+//! a frozen terminal.
+
+use indicatif::{ProgressBar, ProgressStyle};
+use std::{sync::Mutex, time::Duration};
+use tgl_cli::Progress;
+
+/// How often the spinner moves.
+const TICK: Duration = Duration::from_millis(80);
+
+/// Returns a progress hook that shows a spinner on stderr while requests
+/// are in flight. Concurrent requests share one spinner, which shows the
+/// latest to start and clears once they've all finished.
+pub fn spinner() -> impl Fn(Progress) + Send + Sync + 'static {
+    let state: Mutex<(usize, Option<ProgressBar>)> = Mutex::new((0, None));
+
+    move |progress| {
+        let Ok(mut state) = state.lock() else {
+            return;
+        };
+        let (in_flight, current) = &mut *state;
+        match progress {
+            Progress::Started(action) => {
+                *in_flight += 1;
+                let message = format!("{action}…");
+                match current {
+                    Some(spinner) => spinner.set_message(message),
+                    None => {
+                        let spinner = ProgressBar::new_spinner()
+                            .with_style(ProgressStyle::default_spinner())
+                            .with_message(message);
+                        spinner.enable_steady_tick(TICK);
+                        *current = Some(spinner);
+                    }
+                }
+            }
+            _ => {
+                *in_flight = in_flight.saturating_sub(1);
+                if *in_flight == 0 {
+                    if let Some(spinner) = current.take() {
+                        spinner.finish_and_clear();
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,332 @@
+//! A local SQLite mirror of recent time entries, projects, and workspaces,
+//! read when Toggl can't be reached.
+
+use crate::{
+    api,
+    svc::{Project, Workspace},
+};
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{de::DeserializeOwned, Serialize};
+use std::path::Path;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS time_entries (
+        id INTEGER PRIMARY KEY,
+        start INTEGER,
+        running INTEGER NOT NULL,
+        body TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS projects (
+        workspace_id INTEGER NOT NULL,
+        id INTEGER NOT NULL,
+        body TEXT NOT NULL,
+        PRIMARY KEY (workspace_id, id)
+    );
+    CREATE TABLE IF NOT EXISTS workspaces (
+        id INTEGER PRIMARY KEY,
+        body TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS sync (
+        id INTEGER PRIMARY KEY CHECK (id = 0),
+        synced_at INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS entry_index (
+        id INTEGER PRIMARY KEY CHECK (id = 0),
+        start INTEGER NOT NULL,
+        end INTEGER NOT NULL
+    );
+";
+
+pub(crate) struct Store {
+    conn: Connection,
+}
+
+impl Store {
+    pub fn open(path: &Path) -> rusqlite::Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(SCHEMA)?;
+
+        Ok(Self { conn })
+    }
+
+    /// When data was last saved, if ever.
+    pub fn synced_at(&self) -> rusqlite::Result<Option<DateTime<Utc>>> {
+        let secs: Option<i64> = self
+            .conn
+            .query_row("SELECT synced_at FROM sync WHERE id = 0", [], |r| r.get(0))
+            .optional()?;
+
+        Ok(secs.and_then(|s| Utc.timestamp_opt(s, 0).single()))
+    }
+
+    /// Saves fetched entries. Stored entries in the fetched range are
+    /// replaced so that entries deleted in Toggl disappear here too. Without
+    /// a range, the range starts at the earliest fetched entry.
+    pub fn save_entries(
+        &mut self,
+        entries: &[api::TimeEntry],
+        range: Option<(DateTime<Utc>, DateTime<Utc>)>,
+        now: DateTime<Utc>,
+    ) -> rusqlite::Result<()> {
+        let tx = self.conn.transaction()?;
+        let range = range.map(|(start, end)| (start.timestamp(), end.timestamp()));
+        let range = range.or_else(|| {
+            entries
+                .iter()
+                .filter_map(start_secs)
+                .min()
+                .map(|start| (start, i64::MAX))
+        });
+        if let Some((start, end)) = range {
+            tx.execute(
+                "DELETE FROM time_entries WHERE start >= ?1 AND start < ?2",
+                params![start, end],
+            )?;
+        }
+        for entry in entries {
+            insert_entry(&tx, entry)?;
+        }
+        set_synced_at(&tx, now)?;
+
+        tx.commit()
+    }
+
+    /// Saves the running entry, replacing any entry stored as running.
+    pub fn save_current_entry(
+        &mut self,
+        entry: Option<&api::TimeEntry>,
+        now: DateTime<Utc>,
+    ) -> rusqlite::Result<()> {
+        let tx = self.conn.transaction()?;
+        tx.execute("DELETE FROM time_entries WHERE running = 1", [])?;
+        if let Some(entry) = entry {
+            insert_entry(&tx, entry)?;
+        }
+        set_synced_at(&tx, now)?;
+
+        tx.commit()
+    }
+
+    /// Gets stored entries, latest first, optionally only those that started
+    /// within `[start, end)`.
+    pub fn entries(
+        &self,
+        range: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    ) -> rusqlite::Result<Vec<api::TimeEntry>> {
+        let (start, end) = range.map_or((i64::MIN, i64::MAX), |(start, end)| {
+            (start.timestamp(), end.timestamp())
+        });
+
+        self.bodies(
+            "SELECT body FROM time_entries WHERE start >= ?1 AND start < ?2 ORDER BY start DESC",
+            params![start, end],
+        )
+    }
+
+    pub fn current_entry(&self) -> rusqlite::Result<Option<api::TimeEntry>> {
+        let entries = self.bodies("SELECT body FROM time_entries WHERE running = 1", [])?;
+
+        Ok(entries.into_iter().next())
+    }
+
+    /// The span of time whose entries have all been saved, if any.
+    pub fn indexed(&self) -> rusqlite::Result<Option<(DateTime<Utc>, DateTime<Utc>)>> {
+        let span: Option<(i64, i64)> = self
+            .conn
+            .query_row("SELECT start, end FROM entry_index WHERE id = 0", [], |r| {
+                Ok((r.get(0)?, r.get(1)?))
+            })
+            .optional()?;
+
+        Ok(span.and_then(|(start, end)| {
+            Some((
+                Utc.timestamp_opt(start, 0).single()?,
+                Utc.timestamp_opt(end, 0).single()?,
+            ))
+        }))
+    }
+
+    /// Records that all entries within `[start, end)` have been saved.
+    pub fn set_indexed(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> rusqlite::Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO entry_index (id, start, end) VALUES (0, ?1, ?2)",
+            params![start.timestamp(), end.timestamp()],
+        )?;
+
+        Ok(())
+    }
+
+    /// Replaces the workspace's stored projects.
+    pub fn save_projects(
+        &mut self,
+        workspace_id: i64,
+        projects: &[Project],
+        now: DateTime<Utc>,
+    ) -> rusqlite::Result<()> {
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "DELETE FROM projects WHERE workspace_id = ?1",
+            [workspace_id],
+        )?;
+        for project in projects {
+            tx.execute(
+                "INSERT INTO projects (workspace_id, id, body) VALUES (?1, ?2, ?3)",
+                params![workspace_id, project.id, to_body(project)],
+            )?;
+        }
+        set_synced_at(&tx, now)?;
+
+        tx.commit()
+    }
+
+    pub fn projects(&self, workspace_id: i64) -> rusqlite::Result<Vec<Project>> {
+        self.bodies(
+            "SELECT body FROM projects WHERE workspace_id = ?1",
+            [workspace_id],
+        )
+    }
+
+    /// Replaces the stored workspaces.
+    pub fn save_workspaces(
+        &mut self,
+        workspaces: &[Workspace],
+        now: DateTime<Utc>,
+    ) -> rusqlite::Result<()> {
+        let tx = self.conn.transaction()?;
+        tx.execute("DELETE FROM workspaces", [])?;
+        for workspace in workspaces {
+            tx.execute(
+                "INSERT INTO workspaces (id, body) VALUES (?1, ?2)",
+                params![workspace.id, to_body(workspace)],
+            )?;
+        }
+        set_synced_at(&tx, now)?;
+
+        tx.commit()
+    }
+
+    pub fn workspaces(&self) -> rusqlite::Result<Vec<Workspace>> {
+        self.bodies("SELECT body FROM workspaces ORDER BY id", [])
+    }
+
+    /// Runs a query for JSON bodies, skipping any that no longer parse.
+    fn bodies<T: DeserializeOwned>(
+        &self,
+        sql: &str,
+        params: impl rusqlite::Params,
+    ) -> rusqlite::Result<Vec<T>> {
+        let mut stmt = self.conn.prepare(sql)?;
+        let bodies = stmt.query_map(params, |r| r.get::<_, String>(0))?;
+        let mut values = Vec::new();
+        for body in bodies {
+            if let Ok(value) = serde_json::from_str(&body?) {
+                values.push(value);
+            }
+        }
+
+        Ok(values)
+    }
+}
+
+fn insert_entry(conn: &Connection, entry: &api::TimeEntry) -> rusqlite::Result<()> {
+    let running = entry.duration.as_i64().is_some_and(|d| d < 0);
+    conn.execute(
+        "INSERT OR REPLACE INTO time_entries (id, start, running, body) VALUES (?1, ?2, ?3, ?4)",
+        params![
+            entry.id.as_i64(),
+            start_secs(entry),
+            running,
+            to_body(entry)
+        ],
+    )?;
+
+    Ok(())
+}
+
+fn set_synced_at(conn: &Connection, now: DateTime<Utc>) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO sync (id, synced_at) VALUES (0, ?1)",
+        [now.timestamp()],
+    )?;
+
+    Ok(())
+}
+
+fn start_secs(entry: &api::TimeEntry) -> Option<i64> {
+    let start: DateTime<Utc> = entry.start.as_deref()?.parse().ok()?;
+
+    Some(start.timestamp())
+}
+
+fn to_body(value: &impl Serialize) -> String {
+    serde_json::to_string(value).expect("models serialize to JSON")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn entry(id: i64, start: &str, duration: i64) -> api::TimeEntry {
+        serde_json::from_value(json!({
+            "id": id,
+            "start": start,
+            "duration": duration,
+            "workspace_id": 1,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn saved_entries_replace_their_range() {
+        let mut store = Store::open(Path::new(":memory:")).unwrap();
+        let now = Utc::now();
+        let day = |d: u32| Utc.with_ymd_and_hms(2024, 3, d, 0, 0, 0).unwrap();
+        store
+            .save_entries(
+                &[
+                    entry(1, "2024-03-01T09:00:00Z", 60),
+                    entry(2, "2024-03-02T09:00:00Z", 60),
+                ],
+                None,
+                now,
+            )
+            .unwrap();
+        store
+            .save_entries(
+                &[entry(3, "2024-03-02T10:00:00Z", -1)],
+                Some((day(2), day(3))),
+                now,
+            )
+            .unwrap();
+
+        let ids = |entries: Vec<api::TimeEntry>| -> Vec<_> {
+            entries.iter().map(|e| e.id.as_i64().unwrap()).collect()
+        };
+        assert_eq!(vec![3, 1], ids(store.entries(None).unwrap()));
+        assert_eq!(vec![1], ids(store.entries(Some((day(1), day(2)))).unwrap()));
+        assert_eq!(
+            Some(3),
+            store
+                .current_entry()
+                .unwrap()
+                .map(|e| e.id.as_i64().unwrap())
+        );
+        assert_eq!(
+            Some(now.timestamp()),
+            store.synced_at().unwrap().map(|t| t.timestamp())
+        );
+    }
+
+    #[test]
+    fn remembers_the_indexed_span() {
+        let store = Store::open(Path::new(":memory:")).unwrap();
+        let day = |d: u32| Utc.with_ymd_and_hms(2024, 3, d, 0, 0, 0).unwrap();
+        assert_eq!(None, store.indexed().unwrap());
+
+        store.set_indexed(day(1), day(9)).unwrap();
+        store.set_indexed(day(2), day(20)).unwrap();
+        assert_eq!(Some((day(2), day(20))), store.indexed().unwrap());
+    }
+}
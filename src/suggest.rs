@@ -0,0 +1,566 @@
+//! Suggesting time entries from the meetings in a calendar, and reading days
+//! off from one.
+
+use crate::{
+    clear_prompt_cache,
+    config::Config,
+    decorate, ensure_can_prompt, find_workspaces, get_client, hooks,
+    journal::{self, Action},
+    local_midnight, resolve_template,
+    when::parse_date,
+    NewEntry,
+};
+use anyhow::{anyhow, Context, Result};
+use chrono::{
+    DateTime, Datelike, Days, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday,
+};
+use clap::Args;
+use regex::Regex;
+use std::fs;
+use tgl_cli::{Client, TimeEntry, TimeEntryUpdate};
+
+#[derive(Args)]
+pub struct SuggestArgs {
+    /// Calendar to read, as a file path or an http(s) URL [default: from config]
+    #[arg(long)]
+    ics: Option<String>,
+    /// Day to suggest entries for, e.g. 2024-06-03 or yesterday [default: today]
+    #[arg(long, value_parser = parse_date)]
+    date: Option<NaiveDate>,
+}
+
+/// A `VEVENT` from a calendar, with the properties tgl uses.
+#[derive(Debug, Default)]
+struct Event {
+    uid: Option<String>,
+    summary: String,
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+    /// The first day of an all-day event.
+    start_date: Option<NaiveDate>,
+    /// The day after an all-day event.
+    end_date: Option<NaiveDate>,
+    duration: Option<Duration>,
+    rrule: Option<String>,
+    exdates: Vec<NaiveDate>,
+    /// The date of the occurrence this event replaces, for changed
+    /// occurrences of recurring events.
+    recurrence_id: Option<NaiveDate>,
+    cancelled: bool,
+}
+
+/// One meeting on a given day.
+#[derive(Debug, PartialEq)]
+struct Meeting<'a> {
+    summary: &'a str,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+}
+
+/// Parses the events of an iCalendar file. All-day events have dates instead
+/// of a start and end, and are left for [`meetings_on`] to skip.
+fn parse_ics(text: &str) -> Vec<Event> {
+    // Long lines are folded onto following lines that start with a space or
+    // a tab.
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+
+    let mut events = Vec::new();
+    let mut event: Option<Event> = None;
+    for line in &lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let (name, params) = name.split_once(';').unwrap_or((name, ""));
+        match (name.to_ascii_uppercase().as_str(), event.as_mut()) {
+            ("BEGIN", None) if value.eq_ignore_ascii_case("VEVENT") => {
+                event = Some(Event::default());
+            }
+            ("END", Some(_)) if value.eq_ignore_ascii_case("VEVENT") => {
+                events.extend(event.take());
+            }
+            ("UID", Some(e)) => e.uid = Some(value.to_string()),
+            ("SUMMARY", Some(e)) => e.summary = unescape(value),
+            ("DTSTART", Some(e)) => {
+                e.start = parse_time(value, params);
+                e.start_date = parse_date_value(value, params);
+            }
+            ("DTEND", Some(e)) => {
+                e.end = parse_time(value, params);
+                e.end_date = parse_date_value(value, params);
+            }
+            ("DURATION", Some(e)) => e.duration = parse_duration(value),
+            ("RRULE", Some(e)) => e.rrule = Some(value.to_ascii_uppercase()),
+            ("EXDATE", Some(e)) => e.exdates.extend(
+                value
+                    .split(',')
+                    .filter_map(|v| parse_time(v, params))
+                    .map(|t| t.with_timezone(&Local).date_naive()),
+            ),
+            ("RECURRENCE-ID", Some(e)) => {
+                e.recurrence_id =
+                    parse_time(value, params).map(|t| t.with_timezone(&Local).date_naive());
+            }
+            ("STATUS", Some(e)) => e.cancelled = value.eq_ignore_ascii_case("CANCELLED"),
+            _ => {}
+        }
+    }
+
+    events
+}
+
+/// Undoes the escaping of text values, turning line breaks into spaces.
+fn unescape(value: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => unescaped.push(' '),
+            Some(c) => unescaped.push(c),
+            None => {}
+        }
+    }
+
+    unescaped
+}
+
+/// Parses a `DTSTART`-style value. Times in UTC end with `Z`; others are
+/// taken as local time, including those with a `TZID`. Dates without a time
+/// give `None`.
+fn parse_time(value: &str, params: &str) -> Option<DateTime<Utc>> {
+    if params
+        .split(';')
+        .any(|p| p.eq_ignore_ascii_case("VALUE=DATE"))
+    {
+        return None;
+    }
+    if let Some(value) = value.strip_suffix('Z') {
+        let time = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+        return Some(time.and_utc());
+    }
+    let time = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+
+    Some(Local.from_local_datetime(&time).earliest()?.into())
+}
+
+/// Parses a `DTSTART`-style value that's a date without a time.
+fn parse_date_value(value: &str, params: &str) -> Option<NaiveDate> {
+    if !params
+        .split(';')
+        .any(|p| p.eq_ignore_ascii_case("VALUE=DATE"))
+    {
+        return None;
+    }
+
+    NaiveDate::parse_from_str(value, "%Y%m%d").ok()
+}
+
+/// Parses a `DURATION` value such as `PT1H30M` or `P1D`.
+fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.strip_prefix('P')?;
+    let mut duration = Duration::zero();
+    let mut number = String::new();
+    for c in value.chars() {
+        let unit = match c {
+            '0'..='9' => {
+                number.push(c);
+                continue;
+            }
+            'T' => continue,
+            'W' => Duration::weeks(1),
+            'D' => Duration::days(1),
+            'H' => Duration::hours(1),
+            'M' => Duration::minutes(1),
+            'S' => Duration::seconds(1),
+            _ => return None,
+        };
+        duration += unit * number.parse().ok()?;
+        number.clear();
+    }
+
+    Some(duration)
+}
+
+/// Gets the meetings on a local date, earliest first. Daily and weekly
+/// recurring events are expanded; other recurrences only count on their
+/// first day.
+fn meetings_on(events: &[Event], date: NaiveDate) -> Vec<Meeting<'_>> {
+    let mut meetings = Vec::new();
+    for event in events {
+        let Some(start) = event.start else {
+            continue;
+        };
+        let length = match (event.end, event.duration) {
+            (Some(end), _) => end - start,
+            (None, Some(duration)) => duration,
+            (None, None) => continue,
+        };
+        let first = start.with_timezone(&Local);
+        let replaced = event.recurrence_id.is_none()
+            && events
+                .iter()
+                .any(|e| e.recurrence_id == Some(date) && e.uid.is_some() && e.uid == event.uid);
+        let occurs = match &event.rrule {
+            _ if event.cancelled || replaced || event.exdates.contains(&date) => false,
+            Some(rrule) if event.recurrence_id.is_none() => {
+                recurs_on(rrule, first.date_naive(), date)
+            }
+            _ => first.date_naive() == date,
+        };
+        if !occurs {
+            continue;
+        }
+
+        let Some(start) = Local
+            .from_local_datetime(&date.and_time(first.time()))
+            .earliest()
+        else {
+            continue;
+        };
+        let start = start.with_timezone(&Utc);
+        meetings.push(Meeting {
+            summary: &event.summary,
+            start,
+            end: start + length,
+        });
+    }
+    meetings.sort_by_key(|m| m.start);
+
+    meetings
+}
+
+/// Whether a daily or weekly `RRULE` that started on `first` has an
+/// occurrence on `date`.
+fn recurs_on(rrule: &str, first: NaiveDate, date: NaiveDate) -> bool {
+    let part = |name: &str| {
+        rrule
+            .split(';')
+            .find_map(|p| p.strip_prefix(name)?.strip_prefix('='))
+    };
+    let interval: i64 = part("INTERVAL").and_then(|i| i.parse().ok()).unwrap_or(1);
+    let until =
+        part("UNTIL").and_then(|u| NaiveDate::parse_from_str(&u[..8.min(u.len())], "%Y%m%d").ok());
+    let count: Option<usize> = part("COUNT").and_then(|c| c.parse().ok());
+    let weekdays: Vec<Weekday> = part("BYDAY")
+        .map(|days| days.split(',').filter_map(parse_weekday).collect())
+        .unwrap_or_else(|| vec![first.weekday()]);
+    let occurs = |day: NaiveDate| match part("FREQ") {
+        Some("DAILY") => (day - first).num_days() % interval == 0,
+        Some("WEEKLY") => {
+            let weeks = (day.week(Weekday::Mon).first_day() - first.week(Weekday::Mon).first_day())
+                .num_weeks();
+            weeks % interval == 0 && weekdays.contains(&day.weekday())
+        }
+        _ => day == first,
+    };
+
+    if date < first || until.is_some_and(|until| date > until) || !occurs(date) {
+        return false;
+    }
+    match count {
+        Some(count) => {
+            first
+                .iter_days()
+                .take_while(|d| *d <= date)
+                .filter(|d| occurs(*d))
+                .count()
+                <= count
+        }
+        None => true,
+    }
+}
+
+fn parse_weekday(day: &str) -> Option<Weekday> {
+    // Monthly rules prefix days with an ordinal such as `1MO`, which weekly
+    // rules don't use.
+    match day.trim_start_matches(['+', '-', '0', '1', '2', '3', '4', '5', '6', '7', '8', '9']) {
+        "MO" => Some(Weekday::Mon),
+        "TU" => Some(Weekday::Tue),
+        "WE" => Some(Weekday::Wed),
+        "TH" => Some(Weekday::Thu),
+        "FR" => Some(Weekday::Fri),
+        "SA" => Some(Weekday::Sat),
+        "SU" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+/// Gets the days covered by the all-day events in a calendar, as a file path
+/// or an http(s) URL. Recurring all-day events only count once.
+pub fn read_days_off(source: &str) -> Result<Vec<NaiveDate>> {
+    Ok(all_day_dates(&parse_ics(&read_calendar(source)?)))
+}
+
+fn all_day_dates(events: &[Event]) -> Vec<NaiveDate> {
+    events
+        .iter()
+        .filter(|e| !e.cancelled)
+        .filter_map(|e| {
+            let first = e.start_date?;
+            let end = e.end_date.unwrap_or(first + Days::new(1));
+            Some(first.iter_days().take_while(move |date| *date < end))
+        })
+        .flatten()
+        .collect()
+}
+
+/// Reads a calendar from an http(s) URL or a file.
+fn read_calendar(source: &str) -> Result<String> {
+    if source.starts_with("http://") || source.starts_with("https://") {
+        return reqwest::blocking::get(source)
+            .and_then(|r| r.error_for_status())
+            .and_then(|r| r.text())
+            .with_context(|| format!("Failed to download {source}"));
+    }
+
+    fs::read_to_string(source).with_context(|| format!("Failed to read {source}"))
+}
+
+/// Picks the entry for a meeting from the first rule matching its title.
+/// Without a matching rule, the entry has no project.
+fn entry_for(
+    client: &Client,
+    config: &Config,
+    rules: &[(Regex, &str)],
+    summary: &str,
+) -> Result<NewEntry> {
+    let entry = match rules.iter().find(|(pattern, _)| pattern.is_match(summary)) {
+        Some((_, template)) => resolve_template(client, config, template)?,
+        None => match find_workspaces(client, None)?.as_slice() {
+            [workspace] => NewEntry::Manual {
+                workspace_id: workspace.id,
+                project_id: None,
+                description: String::new(),
+                tags: Vec::new(),
+                billable: None,
+            },
+            _ => return Err(anyhow!("No rule matches '{summary}' and you have several workspaces, add a rule to [suggest] in the config file")),
+        },
+    };
+
+    // Entries are named after their meeting.
+    Ok(match entry {
+        NewEntry::Manual {
+            workspace_id,
+            project_id,
+            tags,
+            billable,
+            ..
+        } => NewEntry::Manual {
+            workspace_id,
+            project_id,
+            description: summary.to_string(),
+            tags,
+            billable,
+        },
+        favorite => favorite,
+    })
+}
+
+fn confirm(prompt: String) -> Result<bool> {
+    ensure_can_prompt("confirmation of each suggestion", None)?;
+    dialoguer::Confirm::new()
+        .with_prompt(prompt)
+        .default(true)
+        .interact()
+        .context("Failed to read confirmation")
+}
+
+pub fn run_suggest(config: &Config, args: &SuggestArgs) -> Result<()> {
+    let source = args
+        .ics
+        .as_deref()
+        .or(config.suggest.ics.as_deref())
+        .ok_or_else(|| anyhow!("Pass --ics or set 'ics' under [suggest] in the config file"))?;
+    let rules = config
+        .suggest
+        .rules
+        .iter()
+        .map(|rule| {
+            let pattern = Regex::new(&rule.pattern)
+                .with_context(|| format!("Invalid suggest pattern '{}'", rule.pattern))?;
+            Ok((pattern, rule.template.as_str()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let date = args.date.unwrap_or_else(|| Local::now().date_naive());
+    let events = parse_ics(&read_calendar(source)?);
+    let meetings = meetings_on(&events, date);
+    if meetings.is_empty() {
+        println!("{}", decorate("🤷", &format!("No meetings on {date}")));
+        return Ok(());
+    }
+
+    let client = get_client()?;
+    let tracked = client
+        .get_time_entries(
+            local_midnight(date).into(),
+            local_midnight(date + Days::new(1)).into(),
+        )
+        .context("Failed to retrieve time entries")?;
+    let is_tracked = |m: &Meeting| {
+        tracked
+            .iter()
+            .any(|e| e.start == Some(m.start) && e.description.as_deref() == Some(m.summary))
+    };
+
+    let now = Utc::now();
+    let fmt_time = |t: DateTime<Utc>| t.with_timezone(&Local).format("%H:%M").to_string();
+    let mut actions = Vec::new();
+    for meeting in meetings.iter().filter(|m| !is_tracked(m)) {
+        let times = format!("{}-{}", fmt_time(meeting.start), fmt_time(meeting.end));
+        if meeting.start > now {
+            println!(
+                "{}",
+                decorate(
+                    "⏰",
+                    &format!("{times} {} hasn't started yet", meeting.summary)
+                )
+            );
+            continue;
+        }
+
+        let entry = entry_for(&client, config, &rules, meeting.summary)?;
+        let created = if meeting.end <= now {
+            if !confirm(format!("Add '{}' from {times}?", meeting.summary))? {
+                continue;
+            }
+            add_entry(&client, &entry, meeting)?
+        } else {
+            if !confirm(format!(
+                "'{}' started at {}. Start a timer from then?",
+                meeting.summary,
+                fmt_time(meeting.start)
+            ))? {
+                continue;
+            }
+            entry.start_at(&client, meeting.start)?
+        };
+        println!(
+            "{}",
+            decorate("✅", created.description.as_deref().unwrap_or_default())
+        );
+        actions.push(Action::Started { entry: created });
+    }
+    if !actions.is_empty() {
+        hooks::run(&actions);
+        journal::record(actions);
+        clear_prompt_cache();
+    }
+
+    Ok(())
+}
+
+/// Adds a stopped entry covering the meeting.
+fn add_entry(client: &Client, entry: &NewEntry, meeting: &Meeting) -> Result<TimeEntry> {
+    let (workspace_id, details) = match entry {
+        NewEntry::Manual {
+            workspace_id,
+            project_id,
+            description,
+            tags,
+            billable,
+        } => {
+            let mut details = TimeEntryUpdate::new()
+                .description(description)
+                .project_id(*project_id)
+                .tags(tags.clone());
+            if let Some(billable) = billable {
+                details = details.billable(*billable);
+            }
+            (*workspace_id, details)
+        }
+        NewEntry::Favorite(_) => unreachable!("rules only pick templates"),
+    };
+
+    client
+        .add_detailed_time_entry(workspace_id, meeting.start, meeting.end, &details)
+        .context("Failed to create time entry")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_meetings_on_a_day() {
+        let events = parse_ics(
+            "BEGIN:VCALENDAR\r
+BEGIN:VEVENT\r
+UID:standup\r
+SUMMARY:Stand\\, up\r
+DTSTART:20240603T090000\r
+DURATION:PT15M\r
+RRULE:FREQ=WEEKLY;BYDAY=MO,WE,FR;COUNT=5\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:standup\r
+RECURRENCE-ID:20240607T090000\r
+SUMMARY:Stand up (moved)\r
+DTSTART:20240607T100000\r
+DTEND:20240607T101500\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+SUMMARY:Design\r
+  review\r
+DTSTART:20240605T130000\r
+DTEND:20240605T140000\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+SUMMARY:Holiday\r
+DTSTART;VALUE=DATE:20240605\r
+END:VEVENT\r
+END:VCALENDAR\r
+",
+        );
+        let date = |d| NaiveDate::from_ymd_opt(2024, 6, d).unwrap();
+        let summaries = |d| -> Vec<_> {
+            meetings_on(&events, date(d))
+                .iter()
+                .map(|m| m.summary.to_string())
+                .collect()
+        };
+
+        assert_eq!(vec!["Stand, up", "Design review"], summaries(5));
+        assert_eq!(vec!["Stand up (moved)"], summaries(7));
+        assert_eq!(Vec::<String>::new(), summaries(4));
+        assert_eq!(vec!["Stand, up"], summaries(12));
+        assert_eq!(Vec::<String>::new(), summaries(14));
+        let standup = &meetings_on(&events, date(3))[0];
+        assert_eq!(Duration::minutes(15), standup.end - standup.start);
+    }
+
+    #[test]
+    fn all_day_events_are_days_off() {
+        let events = parse_ics(
+            "BEGIN:VCALENDAR\r
+BEGIN:VEVENT\r
+SUMMARY:Vacation\r
+DTSTART;VALUE=DATE:20240603\r
+DTEND;VALUE=DATE:20240605\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+SUMMARY:Holiday\r
+DTSTART;VALUE=DATE:20240610\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+SUMMARY:Design review\r
+DTSTART:20240611T130000\r
+DTEND:20240611T140000\r
+END:VEVENT\r
+END:VCALENDAR\r
+",
+        );
+        let date = |d| NaiveDate::from_ymd_opt(2024, 6, d).unwrap();
+
+        assert_eq!(vec![date(3), date(4), date(10)], all_day_dates(&events));
+    }
+}
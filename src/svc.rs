@@ -1,39 +1,327 @@
 //! High-level client for interacting with Toggl. Uses the [api].
 
-use crate::api;
-use chrono::{DateTime, Duration, TimeZone, Utc};
+use crate::{
+    api::{self, PlannedRequest, Progress},
+    cache::DiskCache,
+    cassette::Cassette,
+};
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::{cell::Cell, path::PathBuf};
+#[cfg(feature = "mirror")]
+use {
+    crate::store::Store,
+    std::{cell::RefCell, path::Path},
+    tracing::warn,
+};
 
 const CREATED_WITH: &str = "github.com/blachniet/tgl";
 
+/// The most time entries Toggl updates in one bulk request.
+const BULK_UPDATE_LIMIT: usize = 100;
+
+/// The longest range of time entries fetched in one request. Toggl cuts
+/// longer ranges short, so they're fetched in pieces.
+const ENTRY_RANGE_DAYS: i64 = 30;
+
+/// How many of the latest days of the indexed history are fetched again on
+/// each indexed read, since recent entries are still likely to change.
+#[cfg(feature = "mirror")]
+const INDEX_REFRESH_DAYS: i64 = 7;
+
+/// Runs `fetch` through [`Client::fetch_mirrored`], or on its own without
+/// the `mirror` feature, where `save` and `load` have no store to use.
+macro_rules! fetch_mirrored {
+    ($client:expr, $fetch:expr, $save:expr, $load:expr $(,)?) => {{
+        #[cfg(feature = "mirror")]
+        let result = $client.fetch_mirrored($fetch, $save, $load);
+        #[cfg(not(feature = "mirror"))]
+        let result: Result<_> = ($fetch)();
+        result
+    }};
+}
+
+/// High-level client for interacting with Toggl.
 pub struct Client {
     c: api::Client,
     get_now: fn() -> DateTime<Utc>,
     project_cache: elsa::map::FrozenMap<(i64, i64), Box<Project>>,
+    project_list_cache: elsa::map::FrozenMap<i64, Vec<Project>>,
+    client_cache: elsa::map::FrozenMap<(i64, i64), Box<WorkspaceClient>>,
+    disk_cache: Option<DiskCache>,
+    #[cfg(feature = "mirror")]
+    store: Option<RefCell<Store>>,
+    stale_since: Cell<Option<DateTime<Utc>>>,
+    latest_days: Option<u32>,
+    latest_limit: Option<usize>,
 }
 
 impl Client {
     pub fn new(token: String, get_now: fn() -> DateTime<Utc>) -> Result<Self> {
         Ok(Self {
-            c: api::Client::new(token)?,
+            c: api::Client::new(token, &api::Connection::default())?,
             get_now,
             project_cache: elsa::map::FrozenMap::new(),
+            project_list_cache: elsa::map::FrozenMap::new(),
+            client_cache: elsa::map::FrozenMap::new(),
+            disk_cache: None,
+            #[cfg(feature = "mirror")]
+            store: None,
+            stale_since: Cell::new(None),
+            latest_days: None,
+            latest_limit: None,
         })
     }
 
+    /// Makes the client report each request that would change data to
+    /// `hook` instead of sending it. Reads are still sent, so commands see
+    /// real data and get plausible responses back.
+    pub fn with_dry_run(mut self, hook: impl Fn(&PlannedRequest) + Send + Sync + 'static) -> Self {
+        self.c.set_dry_run(Box::new(hook));
+        self
+    }
+
+    /// Changes how the client reaches Toggl, e.g. through a proxy or with
+    /// shorter timeouts.
+    pub fn with_connection(mut self, connection: &api::Connection) -> Result<Self> {
+        self.c.set_connection(connection)?;
+        Ok(self)
+    }
+
+    /// Records Toggl's responses to a cassette file, or replays them from
+    /// one without reaching Toggl. See [`Cassette`].
+    pub fn with_cassette(mut self, cassette: Cassette) -> Self {
+        self.c.set_cassette(cassette);
+        self
+    }
+
+    /// Makes the client report each request to `hook` as it starts and
+    /// finishes, for example to show a spinner.
+    pub fn with_progress(mut self, hook: impl Fn(Progress) + Send + Sync + 'static) -> Self {
+        self.c.set_progress(Box::new(hook));
+        self
+    }
+
+    /// Keeps workspaces and projects in a file at `path` between runs,
+    /// refetching them once they're older than `ttl`. A zero `ttl` refetches
+    /// everything but still updates the file.
+    pub fn with_disk_cache(mut self, path: impl Into<PathBuf>, ttl: Duration) -> Self {
+        self.disk_cache = Some(DiskCache::load(path.into(), ttl));
+        self
+    }
+
+    /// Mirrors fetched time entries, projects, and workspaces to a SQLite
+    /// database at `path`, and reads them from it when Toggl can't be
+    /// reached. See [`Client::stale_since`].
+    #[cfg(feature = "mirror")]
+    pub fn with_store(mut self, path: &Path) -> Result<Self> {
+        self.store = Some(RefCell::new(Store::open(path)?));
+        Ok(self)
+    }
+
+    /// Limits [`Client::get_latest_entries`] to entries that started in the
+    /// last `days` days, and to the newest `limit` of those. Without either,
+    /// it returns whatever Toggl considers recent.
+    pub fn with_latest_window(mut self, days: Option<u32>, limit: Option<usize>) -> Self {
+        self.latest_days = days;
+        self.latest_limit = limit;
+        self
+    }
+
+    /// If Toggl couldn't be reached and data was read from the store instead,
+    /// returns when the store was last updated.
+    pub fn stale_since(&self) -> Option<DateTime<Utc>> {
+        self.stale_since.get()
+    }
+
+    /// Runs `fetch` and saves the result to the store, or loads it from the
+    /// store if Toggl can't be reached. Once Toggl couldn't be reached, the
+    /// rest of the client's reads go to the store directly.
+    #[cfg(feature = "mirror")]
+    fn fetch_mirrored<T>(
+        &self,
+        fetch: impl FnOnce() -> Result<T>,
+        save: impl FnOnce(&mut Store, &T) -> rusqlite::Result<()>,
+        load: impl FnOnce(&Store) -> rusqlite::Result<T>,
+    ) -> Result<T> {
+        let Some(store) = &self.store else {
+            return fetch();
+        };
+        if self.stale_since.get().is_some() {
+            return Ok(load(&store.borrow())?);
+        }
+
+        match fetch() {
+            Ok(value) => {
+                if let Err(error) = save(&mut store.borrow_mut(), &value) {
+                    warn!(%error, "failed to update the local store");
+                }
+                Ok(value)
+            }
+            Err(error) if error.is_unavailable() => {
+                let Some(synced_at) = store.borrow().synced_at()? else {
+                    return Err(error);
+                };
+                warn!(%error, "Toggl is unavailable, reading the local store");
+                self.stale_since.set(Some(synced_at));
+                Ok(load(&store.borrow())?)
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Gets recent time entries. See [`Client::with_latest_window`].
     pub fn get_latest_entries(&self) -> Result<Vec<TimeEntry>> {
-        let api_entries = self.c.get_time_entries(None)?;
-        let entries: Result<Vec<_>> = api_entries
+        let now = (self.get_now)();
+        let mut entries = if let Some(days) = self.latest_days {
+            let start = now
+                .checked_sub_signed(Duration::days(days.into()))
+                .ok_or(Error::OutOfRange)?;
+            self.get_time_entries(start, now)?
+        } else {
+            let api_entries = fetch_mirrored!(
+                self,
+                || self.c.get_time_entries(None),
+                |store, entries| store.save_entries(entries, None, now),
+                |store| store.entries(None),
+            )?;
+            self.build_time_entries(api_entries)?
+        };
+        if let Some(limit) = self.latest_limit {
+            entries.sort_by_key(|e| std::cmp::Reverse(e.start));
+            entries.truncate(limit);
+        }
+
+        Ok(entries)
+    }
+
+    /// Gets the time entries that started within `[start, end)`.
+    pub fn get_time_entries(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<TimeEntry>> {
+        let api_entries = self.fetch_time_entries(start, end)?;
+        self.build_time_entries(api_entries)
+    }
+
+    fn fetch_time_entries(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<api::TimeEntry>> {
+        let mut api_entries = Vec::new();
+        for range in split_range(start, end, Duration::days(ENTRY_RANGE_DAYS)) {
+            let range = Some(range);
+            api_entries.extend(fetch_mirrored!(
+                self,
+                || self.c.get_time_entries(range),
+                |store, entries| store.save_entries(entries, range, (self.get_now)()),
+                |store| store.entries(range),
+            )?);
+        }
+
+        Ok(api_entries)
+    }
+
+    /// Gets the time entries that started within `[start, end)`, like
+    /// [`Client::get_time_entries`], but from the store where it already has
+    /// them. Only the parts of the range it hasn't indexed yet, and the last
+    /// few indexed days, are fetched from Toggl, so long ranges are quick to
+    /// read again. Without a store, every entry is fetched.
+    pub fn get_indexed_entries(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<TimeEntry>> {
+        #[cfg(feature = "mirror")]
+        if let Some(store) = &self.store {
+            return self.read_indexed_entries(store, start, end);
+        }
+
+        self.get_time_entries(start, end)
+    }
+
+    #[cfg(feature = "mirror")]
+    fn read_indexed_entries(
+        &self,
+        store: &RefCell<Store>,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<TimeEntry>> {
+        let indexed = store.borrow().indexed()?;
+        let refresh_from = (self.get_now)() - Duration::days(INDEX_REFRESH_DAYS);
+        for (start, end) in unindexed_ranges(start, end, indexed, refresh_from) {
+            self.fetch_time_entries(start, end)?;
+        }
+        if self.stale_since.get().is_none() {
+            let (from, to) = indexed.map_or((start, end), |(from, to)| {
+                (from.min(start), to.min(refresh_from).max(end))
+            });
+            store.borrow().set_indexed(from, to)?;
+        }
+
+        let api_entries = store.borrow().entries(Some((start, end)))?;
+        self.build_time_entries(api_entries)
+    }
+
+    /// Gets the time entries that start or stop within `[start, end)`,
+    /// earliest first.
+    pub fn get_entries_touching(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<TimeEntry>> {
+        // Toggl filters by start time, so look back a day for entries that
+        // stop within the range.
+        let mut entries = self.get_time_entries(start - Duration::days(1), end)?;
+        entries.retain(|e| e.touches(start, end));
+        entries.sort_unstable_by_key(|e| e.start);
+
+        Ok(entries)
+    }
+
+    pub fn get_time_entry(&self, id: i64) -> Result<TimeEntry> {
+        let api_entry = self.c.get_time_entry(&id.into())?;
+        self.build_time_entry(api_entry)
+    }
+
+    pub fn get_current_entry(&self) -> Result<Option<TimeEntry>> {
+        let api_entry = fetch_mirrored!(
+            self,
+            || self.c.get_current_entry(),
+            |store, entry| store.save_current_entry(entry.as_ref(), (self.get_now)()),
+            |store| store.current_entry(),
+        )?;
+        match api_entry {
+            Some(api_entry) => Ok(Some(self.build_time_entry(api_entry)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Builds several entries, first fetching the projects they need from
+    /// all of their workspaces at once.
+    fn build_time_entries(&self, api_entries: Vec<api::TimeEntry>) -> Result<Vec<TimeEntry>> {
+        self.prefetch_projects(
+            api_entries
+                .iter()
+                .filter(|e| e.project_id.is_some() && e.project_name.is_none())
+                .filter_map(|e| e.workspace_id.as_i64()),
+        );
+
+        api_entries
             .into_iter()
             .map(|e| self.build_time_entry(e))
-            .collect();
-
-        entries
+            .collect()
     }
 
     fn build_time_entry(&self, api_entry: api::TimeEntry) -> Result<TimeEntry> {
+        let workspace_id = api_entry.workspace_id.as_i64().unwrap();
         let project_id = api_entry.project_id.map(|pid| pid.as_i64().unwrap());
         let project = match project_id {
-            Some(pid) => self.get_project(api_entry.workspace_id.as_i64().unwrap(), pid)?,
+            // Toggl already named the project, so only use what's cached.
+            Some(pid) if api_entry.project_name.is_some() => self.cached_project(workspace_id, pid),
+            Some(pid) => self.get_project(workspace_id, pid)?,
             None => None,
         };
         let (duration, is_running) = parse_duration((self.get_now)(), api_entry.duration);
@@ -47,31 +335,259 @@ impl Client {
         };
 
         Ok(TimeEntry {
+            billable: api_entry.billable,
+            client_id: project.and_then(|p| p.client_id),
+            client_name: api_entry.client_name,
             description: api_entry.description,
             duration,
+            id: api_entry.id.as_i64().unwrap(),
             is_running,
             project_id,
-            project_name: project.map(|p| p.name.to_string()),
+            project_color: api_entry
+                .project_color
+                .or_else(|| project.and_then(|p| p.color.clone())),
+            project_name: api_entry
+                .project_name
+                .or_else(|| project.map(|p| p.name.to_string())),
             start,
             stop,
-            workspace_id: api_entry.workspace_id.as_i64().unwrap(),
+            tags: api_entry.tags.unwrap_or_default(),
+            task_id: api_entry.task_id.map(|t| t.as_i64().unwrap()),
+            workspace_id,
         })
     }
 
+    /// Prepares a running time entry in the workspace, which is started by
+    /// [`StartEntry::send`].
+    ///
+    /// ```no_run
+    /// # fn main() -> Result<(), tgl_cli::Error> {
+    /// # let client = tgl_cli::Client::new("token".to_string(), chrono::Utc::now)?;
+    /// let entry = client
+    ///     .start(1234)
+    ///     .project(5678)
+    ///     .description("Write docs")
+    ///     .billable(true)
+    ///     .send()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn start(&self, workspace_id: i64) -> StartEntry<'_> {
+        StartEntry {
+            client: self,
+            workspace_id,
+            project_id: None,
+            description: None,
+            tags: Vec::new(),
+            billable: None,
+            start: None,
+        }
+    }
+
     pub fn start_time_entry(
         &self,
         workspace_id: i64,
         project_id: Option<i64>,
         description: Option<&str>,
     ) -> Result<TimeEntry> {
-        let now = (self.get_now)();
+        let mut entry = self.start(workspace_id);
+        entry.project_id = project_id;
+        entry.description = description.map(str::to_string);
+        entry.send()
+    }
+
+    /// Starts a running time entry with tags and a billable flag whose start
+    /// time is `start` rather than now. A `billable` of `None` uses the
+    /// project's default.
+    pub fn start_time_entry_at(
+        &self,
+        workspace_id: i64,
+        project_id: Option<i64>,
+        description: Option<&str>,
+        tags: &[String],
+        billable: Option<bool>,
+        start: DateTime<Utc>,
+    ) -> Result<TimeEntry> {
+        let mut entry = self.start(workspace_id).tags(tags.to_vec()).start_at(start);
+        entry.project_id = project_id;
+        entry.description = description.map(str::to_string);
+        entry.billable = billable;
+        entry.send()
+    }
+
+    /// Creates a new entry like `entry`, with `changes` applied. The copy is
+    /// running if it has no stop time, either from `changes` or because
+    /// `entry` is running.
+    pub fn copy_time_entry(
+        &self,
+        entry: &TimeEntry,
+        changes: &TimeEntryUpdate,
+    ) -> Result<TimeEntry> {
+        let start = changes
+            .start
+            .or(entry.start)
+            .unwrap_or_else(|| (self.get_now)());
+        let stop = changes.stop.or(entry.stop.filter(|_| !entry.is_running));
+        let project_id = changes.project_id.unwrap_or(entry.project_id);
+        let tags = changes.tags.as_ref().unwrap_or(&entry.tags);
         let api_entry = self.c.create_time_entry(api::NewTimeEntry {
+            billable: Some(changes.billable.unwrap_or(entry.billable)),
             created_with: CREATED_WITH.to_string(),
-            description: description.map(|d| d.to_string()),
-            duration: (-now.timestamp()).into(),
+            description: changes.description.clone().or(entry.description.clone()),
+            duration: match stop {
+                Some(stop) => (stop - start).num_seconds().into(),
+                None => (-start.timestamp()).into(),
+            },
             project_id: project_id.map(|i| i.into()),
-            start: now.to_rfc3339(),
-            stop: None,
+            start: start.to_rfc3339(),
+            stop: stop.map(|s| s.to_rfc3339()),
+            tags: (!tags.is_empty()).then(|| tags.clone()),
+            // Tasks belong to a project, so only keep it in the same one.
+            task_id: entry
+                .task_id
+                .filter(|_| project_id == entry.project_id)
+                .map(|t| t.into()),
+            workspace_id: entry.workspace_id.into(),
+        })?;
+
+        self.build_time_entry(api_entry)
+    }
+
+    /// Applies `update` to an existing entry and returns the updated entry.
+    pub fn update_time_entry(
+        &self,
+        entry: &TimeEntry,
+        update: &TimeEntryUpdate,
+    ) -> Result<TimeEntry> {
+        let start = update.start.or(entry.start);
+        let stop = update.stop.or(entry.stop);
+        // Toggl derives running state from the duration, so keep it
+        // consistent with any new start and stop times. Setting the stop
+        // time of a running entry stops it.
+        let duration = match (start, stop) {
+            (Some(start), _) if entry.is_running && update.stop.is_none() => {
+                Some(-start.timestamp())
+            }
+            (Some(start), Some(stop)) => Some((stop - start).num_seconds()),
+            _ => None,
+        };
+        let api_update = api::TimeEntryUpdate {
+            billable: update.billable,
+            description: update.description.clone(),
+            duration: duration.map(|d| d.into()),
+            project_id: update.project_id.map(|p| p.map(|p| p.into())),
+            start: update.start.map(|s| s.to_rfc3339()),
+            stop: update.stop.map(|s| Some(s.to_rfc3339())),
+            tags: update.tags.clone(),
+        };
+        let api_entry =
+            self.c
+                .update_time_entry(&entry.workspace_id.into(), &entry.id.into(), &api_update)?;
+
+        self.build_time_entry(api_entry)
+    }
+
+    /// Applies `update` to all `entries`, batching requests per workspace.
+    /// Entries that Toggl fails to update are reported in the result rather
+    /// than as an error.
+    pub fn bulk_update_time_entries(
+        &self,
+        entries: &[TimeEntry],
+        update: &BulkUpdate,
+    ) -> Result<BulkUpdateResult> {
+        let operations = update.operations();
+        let mut result = BulkUpdateResult::default();
+        if operations.is_empty() {
+            return Ok(result);
+        }
+
+        let mut by_workspace: std::collections::BTreeMap<i64, Vec<serde_json::Number>> =
+            std::collections::BTreeMap::new();
+        for entry in entries {
+            by_workspace
+                .entry(entry.workspace_id)
+                .or_default()
+                .push(entry.id.into());
+        }
+        for (workspace_id, ids) in by_workspace {
+            for chunk in ids.chunks(BULK_UPDATE_LIMIT) {
+                let patched =
+                    self.c
+                        .patch_time_entries(&workspace_id.into(), chunk, &operations)?;
+                result
+                    .updated
+                    .extend(patched.success.iter().filter_map(|id| id.as_i64()));
+                result.failed.extend(
+                    patched
+                        .failure
+                        .into_iter()
+                        .filter_map(|f| Some((f.id.as_i64()?, f.message))),
+                );
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Makes a stopped entry run again from its original start time.
+    pub fn resume_time_entry(&self, entry: &TimeEntry) -> Result<TimeEntry> {
+        let start = entry.start.unwrap_or_else(|| (self.get_now)());
+        let api_update = api::TimeEntryUpdate {
+            duration: Some((-start.timestamp()).into()),
+            stop: Some(None),
+            ..Default::default()
+        };
+        let api_entry =
+            self.c
+                .update_time_entry(&entry.workspace_id.into(), &entry.id.into(), &api_update)?;
+
+        self.build_time_entry(api_entry)
+    }
+
+    pub fn delete_time_entry(&self, entry: &TimeEntry) -> Result<()> {
+        self.c
+            .delete_time_entry(&entry.workspace_id.into(), &entry.id.into())?;
+
+        Ok(())
+    }
+
+    /// Creates a stopped time entry spanning `start` to `stop`, e.g. to log
+    /// time after the fact.
+    pub fn add_time_entry(
+        &self,
+        workspace_id: i64,
+        project_id: Option<i64>,
+        description: Option<&str>,
+        start: DateTime<Utc>,
+        stop: DateTime<Utc>,
+    ) -> Result<TimeEntry> {
+        let mut details = TimeEntryUpdate::new().project_id(project_id);
+        if let Some(description) = description {
+            details = details.description(description);
+        }
+
+        self.add_detailed_time_entry(workspace_id, start, stop, &details)
+    }
+
+    /// Like [`Client::add_time_entry`], taking the billable flag,
+    /// description, project, and tags from `details`. Its start and stop
+    /// times are ignored.
+    pub fn add_detailed_time_entry(
+        &self,
+        workspace_id: i64,
+        start: DateTime<Utc>,
+        stop: DateTime<Utc>,
+        details: &TimeEntryUpdate,
+    ) -> Result<TimeEntry> {
+        let api_entry = self.c.create_time_entry(api::NewTimeEntry {
+            billable: details.billable,
+            created_with: CREATED_WITH.to_string(),
+            description: details.description.clone(),
+            duration: (stop - start).num_seconds().into(),
+            project_id: details.project_id.flatten().map(|i| i.into()),
+            start: start.to_rfc3339(),
+            stop: Some(stop.to_rfc3339()),
+            tags: details.tags.clone().filter(|t| !t.is_empty()),
             task_id: None,
             workspace_id: workspace_id.into(),
         })?;
@@ -80,6 +596,53 @@ impl Client {
         Ok(entry)
     }
 
+    /// Starts a running time entry pre-filled from a Toggl favorite.
+    pub fn start_favorite(&self, favorite: &Favorite) -> Result<TimeEntry> {
+        self.start_favorite_at(favorite, (self.get_now)())
+    }
+
+    /// Starts a running time entry pre-filled from a Toggl favorite whose
+    /// start time is `start` rather than now.
+    pub fn start_favorite_at(
+        &self,
+        favorite: &Favorite,
+        start: DateTime<Utc>,
+    ) -> Result<TimeEntry> {
+        self.start_time_entry_at(
+            favorite.workspace_id,
+            favorite.project_id,
+            favorite.description.as_deref(),
+            &favorite.tags,
+            Some(favorite.billable),
+            start,
+        )
+    }
+
+    pub fn get_favorites(&self) -> Result<Vec<Favorite>> {
+        self.c
+            .get_favorites()?
+            .into_iter()
+            .map(|f| {
+                let workspace_id = f.workspace_id.as_i64().unwrap();
+                let project_id = f.project_id.map(|p| p.as_i64().unwrap());
+                let project_name = match project_id {
+                    Some(pid) => self.get_project(workspace_id, pid)?.map(|p| p.name.clone()),
+                    None => None,
+                };
+
+                Ok(Favorite {
+                    billable: f.billable,
+                    description: f.description,
+                    id: f.favorite_id.as_i64().unwrap(),
+                    project_id,
+                    project_name,
+                    tags: f.tags.unwrap_or_default(),
+                    workspace_id,
+                })
+            })
+            .collect()
+    }
+
     pub fn stop_current_time_entry(&self) -> Result<Option<TimeEntry>> {
         if let Some(api_entry) = self.c.get_current_entry()? {
             let api_entry = self
@@ -93,62 +656,384 @@ impl Client {
         }
     }
 
-    fn get_project(&self, workspace_id: i64, project_id: i64) -> Result<Option<&Project>> {
+    /// Gets a project by ID, fetching the workspace's projects on the first
+    /// lookup.
+    pub fn get_project(&self, workspace_id: i64, project_id: i64) -> Result<Option<&Project>> {
         let key = (workspace_id, project_id);
         if let Some(project) = self.project_cache.get(&key) {
             return Ok(Some(project));
         }
 
-        let workspace_id_num = workspace_id.into();
-        let projects = self.c.get_projects(&workspace_id_num)?;
-        for p in projects {
-            self.project_cache.insert(
-                (workspace_id, p.id.as_i64().expect("parse number as i64")),
-                Box::new(Project {
-                    active: p.active,
-                    id: p.id.as_i64().unwrap(),
-                    name: p.name,
-                }),
-            );
-        }
+        self.get_projects(workspace_id)?;
 
         Ok(self.project_cache.get(&key))
     }
 
+    /// Gets a project if it's been fetched already, without fetching it.
+    fn cached_project(&self, workspace_id: i64, project_id: i64) -> Option<&Project> {
+        let key = (workspace_id, project_id);
+        if self.project_cache.get(&key).is_none() {
+            let now = (self.get_now)();
+            let cached = self.disk_cache.as_ref()?.projects(workspace_id, now)?;
+            self.remember_projects(&cached);
+        }
+
+        self.project_cache.get(&key)
+    }
+
+    /// Finds a project by name, ignoring case. The workspace's projects are
+    /// fetched at most once per client.
+    pub fn find_project_by_name(&self, workspace_id: i64, name: &str) -> Result<Option<&Project>> {
+        let projects = match self.project_list_cache.get(&workspace_id) {
+            Some(projects) => projects,
+            None => self
+                .project_list_cache
+                .insert(workspace_id, self.get_projects(workspace_id)?),
+        };
+
+        Ok(projects.iter().find(|p| p.name.eq_ignore_ascii_case(name)))
+    }
+
+    /// Gets the workspace's projects, including archived ones.
     pub fn get_projects(&self, workspace_id: i64) -> Result<Vec<Project>> {
-        let api_projects = self.c.get_projects(&workspace_id.into())?;
-        let mut projects = Vec::new();
+        let now = (self.get_now)();
+        if let Some(projects) = self
+            .disk_cache
+            .as_ref()
+            .and_then(|c| c.projects(workspace_id, now))
+        {
+            self.remember_projects(&projects);
+            return Ok(projects);
+        }
 
-        for p in api_projects {
+        let cached = self
+            .disk_cache
+            .as_ref()
+            .and_then(|c| c.etagged_projects(workspace_id));
+        let etag = cached.as_ref().map(|(etag, _)| etag.clone());
+        let mut new_etag = None;
+        let projects = fetch_mirrored!(
+            self,
+            || {
+                let response = self.c.get_projects(&workspace_id.into(), etag.as_deref())?;
+                let (projects, etag) = revalidated(response, cached);
+                new_etag = etag;
+                Ok(projects)
+            },
+            |store, projects| store.save_projects(workspace_id, projects, now),
+            |store| store.projects(workspace_id),
+        )?;
+        if let (Some(cache), None) = (&self.disk_cache, self.stale_since()) {
+            cache.set_projects(workspace_id, now, &projects, new_etag);
+        }
+        self.remember_projects(&projects);
+
+        Ok(projects)
+    }
+
+    /// Fetches the projects of each workspace that doesn't have them cached,
+    /// all at the same time, so that the time it takes doesn't grow with the
+    /// number of workspaces. Failures are left for later lookups to report.
+    fn prefetch_projects(&self, workspace_ids: impl Iterator<Item = i64>) {
+        if self.stale_since().is_some() {
+            return;
+        }
+        let now = (self.get_now)();
+        let mut missing: Vec<i64> = workspace_ids
+            .filter(|ws| {
+                let cached = self.disk_cache.as_ref().and_then(|c| c.projects(*ws, now));
+                match cached {
+                    Some(projects) => {
+                        self.remember_projects(&projects);
+                        false
+                    }
+                    None => true,
+                }
+            })
+            .collect();
+        missing.sort_unstable();
+        missing.dedup();
+        // A single workspace is fetched just as fast when it's looked up.
+        if missing.len() < 2 {
+            return;
+        }
+
+        let api = &self.c;
+        let cached: Vec<_> = missing
+            .iter()
+            .map(|&ws| {
+                self.disk_cache
+                    .as_ref()
+                    .and_then(|c| c.etagged_projects(ws))
+            })
+            .collect();
+        let fetched: Vec<_> = std::thread::scope(|scope| {
+            let handles: Vec<_> = missing
+                .iter()
+                .zip(&cached)
+                .map(|(&ws, cached)| {
+                    let etag = cached.as_ref().map(|(etag, _)| etag.as_str());
+                    scope.spawn(move || (ws, api.get_projects(&ws.into(), etag)))
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|h| h.join().expect("fetching projects doesn't panic"))
+                .collect()
+        });
+
+        for ((workspace_id, result), cached) in fetched.into_iter().zip(cached) {
+            let Ok(response) = result else {
+                continue;
+            };
+            let (projects, etag) = revalidated(response, cached);
+            #[cfg(feature = "mirror")]
+            if let Some(store) = &self.store {
+                if let Err(error) = store
+                    .borrow_mut()
+                    .save_projects(workspace_id, &projects, now)
+                {
+                    warn!(%error, "failed to update the local store");
+                }
+            }
+            if let Some(cache) = &self.disk_cache {
+                cache.set_projects(workspace_id, now, &projects, etag);
+            }
+            self.remember_projects(&projects);
+        }
+    }
+
+    /// Adds projects to the in-memory cache that [`Client::get_project`]
+    /// reads.
+    fn remember_projects(&self, projects: &[Project]) {
+        for project in projects {
             self.project_cache.insert(
-                (workspace_id, p.id.as_i64().expect("parse number as i64")),
-                Box::new(Project {
-                    active: p.active,
-                    id: p.id.as_i64().unwrap(),
-                    name: p.name.to_string(),
-                }),
+                (project.workspace_id, project.id),
+                Box::new(project.clone()),
             );
+        }
+    }
 
-            projects.push(Project {
-                active: p.active,
-                id: p.id.as_i64().unwrap(),
-                name: p.name,
-            });
+    /// Creates an active project, optionally for a Toggl client.
+    pub fn create_project(
+        &self,
+        workspace_id: i64,
+        name: &str,
+        client_id: Option<i64>,
+    ) -> Result<Project> {
+        let api_project = self.c.create_project(
+            &workspace_id.into(),
+            &api::NewProject {
+                active: true,
+                client_id: client_id.map(|c| c.into()),
+                name: name.to_string(),
+            },
+        )?;
+        if let Some(cache) = &self.disk_cache {
+            cache.forget_projects(workspace_id);
         }
 
-        Ok(projects)
+        Ok(api_project.into())
     }
 
-    pub fn get_workspaces(&self) -> Result<Vec<Workspace>> {
-        let workspaces = self.c.get_workspaces()?;
-        Ok(workspaces
+    /// Archives or restores a project and returns the updated project.
+    pub fn set_project_active(&self, project: &Project, active: bool) -> Result<Project> {
+        let api_project = self.c.update_project(
+            &project.workspace_id.into(),
+            &project.id.into(),
+            &api::ProjectUpdate {
+                active: Some(active),
+            },
+        )?;
+        if let Some(cache) = &self.disk_cache {
+            cache.forget_projects(project.workspace_id);
+        }
+
+        Ok(api_project.into())
+    }
+
+    /// Gets a Toggl client (customer) by ID, fetching the workspace's
+    /// clients on the first lookup.
+    pub fn get_client(
+        &self,
+        workspace_id: i64,
+        client_id: i64,
+    ) -> Result<Option<&WorkspaceClient>> {
+        let key = (workspace_id, client_id);
+        if let Some(client) = self.client_cache.get(&key) {
+            return Ok(Some(client));
+        }
+
+        self.get_clients(workspace_id)?;
+
+        Ok(self.client_cache.get(&key))
+    }
+
+    /// Gets the workspace's clients, including archived ones.
+    pub fn get_clients(&self, workspace_id: i64) -> Result<Vec<WorkspaceClient>> {
+        let mut clients = Vec::new();
+        for c in self.c.get_clients(&workspace_id.into())? {
+            let client = WorkspaceClient::from(c);
+            self.client_cache
+                .insert((workspace_id, client.id), Box::new(client.clone()));
+            clients.push(client);
+        }
+
+        Ok(clients)
+    }
+
+    pub fn create_client(&self, workspace_id: i64, name: &str) -> Result<WorkspaceClient> {
+        let api_client = self.c.create_client(&api::NewClient {
+            name: name.to_string(),
+            wid: workspace_id.into(),
+        })?;
+
+        Ok(api_client.into())
+    }
+
+    /// Archives a client. Toggl archives the client's projects with it.
+    pub fn archive_client(&self, client: &WorkspaceClient) -> Result<()> {
+        self.c
+            .archive_client(&client.workspace_id.into(), &client.id.into())?;
+        if let Some(cache) = &self.disk_cache {
+            cache.forget_projects(client.workspace_id);
+        }
+
+        Ok(())
+    }
+
+    /// Gets the workspace's webhook subscriptions, including other apps'.
+    pub fn get_webhook_subscriptions(&self, workspace_id: i64) -> Result<Vec<WebhookSubscription>> {
+        Ok(self
+            .c
+            .get_webhook_subscriptions(&workspace_id.into())?
             .into_iter()
-            .map(|w| Workspace {
-                id: w.id.as_i64().unwrap(),
-                name: w.name,
-            })
+            .map(WebhookSubscription::from)
             .collect())
     }
+
+    /// Subscribes `url` to every change to time entries in the workspace.
+    pub fn create_webhook_subscription(
+        &self,
+        workspace_id: i64,
+        url: &str,
+        description: &str,
+    ) -> Result<WebhookSubscription> {
+        let subscription = self.c.create_webhook_subscription(
+            &workspace_id.into(),
+            &api::NewWebhookSubscription {
+                description: description.to_string(),
+                enabled: true,
+                event_filters: vec![api::EventFilter {
+                    entity: "time_entry".to_string(),
+                    action: "*".to_string(),
+                }],
+                url_callback: url.to_string(),
+            },
+        )?;
+
+        Ok(subscription.into())
+    }
+
+    pub fn delete_webhook_subscription(&self, subscription: &WebhookSubscription) -> Result<()> {
+        self.c.delete_webhook_subscription(
+            &subscription.workspace_id.into(),
+            &subscription.id.into(),
+        )?;
+
+        Ok(())
+    }
+
+    pub fn get_workspaces(&self) -> Result<Vec<Workspace>> {
+        let now = (self.get_now)();
+        if let Some(workspaces) = self.disk_cache.as_ref().and_then(|c| c.workspaces(now)) {
+            return Ok(workspaces);
+        }
+
+        let cached = self
+            .disk_cache
+            .as_ref()
+            .and_then(|c| c.etagged_workspaces());
+        let etag = cached.as_ref().map(|(etag, _)| etag.clone());
+        let mut new_etag = None;
+        let workspaces = fetch_mirrored!(
+            self,
+            || {
+                let response = self.c.get_workspaces(etag.as_deref())?;
+                let (workspaces, etag) = revalidated(response, cached);
+                new_etag = etag;
+                Ok(workspaces)
+            },
+            |store, workspaces| store.save_workspaces(workspaces, now),
+            |store| store.workspaces(),
+        )?;
+        if let (Some(cache), None) = (&self.disk_cache, self.stale_since()) {
+            cache.set_workspaces(now, &workspaces, new_etag);
+        }
+
+        Ok(workspaces)
+    }
+}
+
+/// Gets the parts of `[start, end)` that need fetching to extend the indexed
+/// span to cover it, treating the span as ending no later than
+/// `refresh_from`. Gaps between the span and the range are included so that
+/// the span stays whole.
+#[cfg(feature = "mirror")]
+fn unindexed_ranges(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    indexed: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    refresh_from: DateTime<Utc>,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let Some((from, to)) = indexed else {
+        return vec![(start, end)];
+    };
+    let to = to.min(refresh_from);
+    let mut ranges = Vec::new();
+    if end > to {
+        ranges.push((to, end));
+    }
+    if start < from {
+        ranges.push((start, from));
+    }
+
+    ranges
+}
+
+/// Splits `[start, end)` into consecutive ranges no longer than `size`,
+/// latest first so that entries fetched for them stay newest first.
+fn split_range(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    size: Duration,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let mut ranges = Vec::new();
+    let mut chunk_end = end;
+    while chunk_end - start > size {
+        ranges.push((chunk_end - size, chunk_end));
+        chunk_end -= size;
+    }
+    ranges.push((start, chunk_end));
+
+    ranges
+}
+
+/// Gets the data and its ETag from the response to a conditional request,
+/// using the cached version it was made with if that's still current.
+fn revalidated<A, T: From<A>>(
+    response: api::Conditional<Vec<A>>,
+    cached: Option<(String, Vec<T>)>,
+) -> (Vec<T>, Option<String>) {
+    match response {
+        api::Conditional::Modified { value, etag } => {
+            (value.into_iter().map(T::from).collect(), etag)
+        }
+        api::Conditional::NotModified => {
+            let (etag, value) = cached.expect("only cached data is revalidated");
+            (value, Some(etag))
+        }
+    }
 }
 
 /// Creates a [`chrono::Duration`] from a Toggle API duration.
@@ -173,44 +1058,670 @@ fn parse_duration(now: DateTime<Utc>, duration: serde_json::Number) -> (Duration
 }
 
 #[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
 pub enum Error {
-    #[error("reqwest error")]
-    Reqwest(#[from] reqwest::Error),
+    /// The service rejected the API token.
+    #[error("the API token was rejected")]
+    Auth,
+    /// The service couldn't be reached, or didn't answer in time.
+    #[error("couldn't reach the server")]
+    Network(#[source] reqwest::Error),
+    /// The service answered with an error, explained by `message`.
+    #[error("the server answered {status}: {message}")]
+    Api { status: u16, message: String },
+    /// The API token couldn't be read from or saved to the keyring.
+    #[error("couldn't use the keyring")]
+    Keyring(#[source] Box<dyn std::error::Error + Send + Sync>),
+    #[error("I/O error")]
+    Io(#[from] std::io::Error),
+    /// The user cancelled a prompt.
+    #[error("aborted")]
+    UserAbort,
+    /// A request that couldn't be built, or a response that couldn't be
+    /// read.
+    #[error("request failed")]
+    Reqwest(#[source] reqwest::Error),
     #[error("chrono parse error")]
     ChronoParse(#[from] chrono::ParseError),
+    #[cfg(feature = "mirror")]
+    #[error("local store error")]
+    Store(#[from] rusqlite::Error),
+    /// An ID that the backend never handed out.
+    #[error("unknown ID {0}")]
+    UnknownId(i64),
+    /// The backend can't log time without a project it can bill to.
+    #[error("entries need a project")]
+    ProjectRequired,
+    /// A date that's too far from now to represent.
+    #[error("date out of range")]
+    OutOfRange,
 }
 
-type Result<T> = std::result::Result<T, Error>;
+impl Error {
+    /// Whether the service couldn't be reached or had a problem of its own,
+    /// rather than a problem with the request.
+    #[cfg_attr(not(feature = "mirror"), allow(dead_code))]
+    fn is_unavailable(&self) -> bool {
+        match self {
+            Error::Network(_) => true,
+            Error::Api { status, .. } => *status >= 500,
+            _ => false,
+        }
+    }
 
-#[derive(Debug)]
+    /// Makes an error from a response with an error status, explained by
+    /// the response's body.
+    pub(crate) fn from_response(status: reqwest::StatusCode, body: &str) -> Self {
+        if status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN {
+            return Error::Auth;
+        }
+
+        Error::Api {
+            status: status.as_u16(),
+            message: api_message(body)
+                .or_else(|| status.canonical_reason().map(str::to_string))
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(error: reqwest::Error) -> Self {
+        match error.status() {
+            Some(status) => Error::from_response(status, ""),
+            None if error.is_connect() || error.is_timeout() => Error::Network(error),
+            None => Error::Reqwest(error),
+        }
+    }
+}
+
+/// Finds the explanation in an error response, which Toggl sends as a JSON
+/// string, and other services as an object with a message or as text.
+fn api_message(body: &str) -> Option<String> {
+    let body = body.trim();
+    let message =
+        match serde_json::from_str::<serde_json::Value>(body) {
+            Ok(serde_json::Value::String(message)) => message,
+            Ok(serde_json::Value::Object(fields)) => ["message", "error", "error_description"]
+                .iter()
+                .find_map(|key| fields.get(*key)?.as_str().map(str::to_string))?,
+            _ => body.chars().take(200).collect(),
+        };
+
+    Some(message.trim().to_string()).filter(|m| !m.is_empty())
+}
+
+pub(crate) type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct TimeEntry {
+    pub billable: bool,
+    /// The project's client. May be missing when Toggl named the project
+    /// itself and its details weren't cached; see [`TimeEntry::client_name`].
+    pub client_id: Option<i64>,
+    /// The name of the project's client, when Toggl includes it.
+    #[serde(default)]
+    pub client_name: Option<String>,
     pub description: Option<String>,
+    #[serde(with = "duration_seconds")]
     pub duration: Duration,
+    pub id: i64,
     pub is_running: bool,
     pub project_id: Option<i64>,
+    /// The project's color, as hex such as `#0b83d9`.
+    #[serde(default)]
+    pub project_color: Option<String>,
     pub project_name: Option<String>,
     pub start: Option<DateTime<Utc>>,
     pub stop: Option<DateTime<Utc>>,
+    pub tags: Vec<String>,
+    pub task_id: Option<i64>,
     pub workspace_id: i64,
 }
 
-#[derive(Debug)]
+impl TimeEntry {
+    /// Whether the entry starts or stops within `[start, end)`.
+    pub fn touches(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> bool {
+        let within = |t: Option<DateTime<Utc>>| t.is_some_and(|t| t >= start && t < end);
+
+        within(self.start) || within(self.stop)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct Project {
     pub active: bool,
+    /// Hours tracked on the project so far, as calculated by Toggl.
+    pub actual_hours: Option<f64>,
+    pub client_id: Option<i64>,
+    /// The project's color in Toggl, as hex such as `#0b83d9`.
+    #[serde(default)]
+    pub color: Option<String>,
+    /// Currency of [`Project::rate`], if it differs from the workspace's.
+    pub currency: Option<String>,
+    /// Hours the project is expected to take.
+    pub estimated_hours: Option<f64>,
     pub id: i64,
     pub name: String,
+    /// Hourly rate for billable time, if it differs from the workspace's.
+    pub rate: Option<f64>,
+    pub workspace_id: i64,
+}
+
+impl From<api::Project> for Project {
+    fn from(p: api::Project) -> Self {
+        Self {
+            active: p.active,
+            actual_hours: p.actual_hours,
+            client_id: p.client_id.map(|c| c.as_i64().unwrap()),
+            color: p.color,
+            currency: p.currency,
+            estimated_hours: p.estimated_hours,
+            id: p.id.as_i64().expect("parse number as i64"),
+            name: p.name,
+            rate: p.rate,
+            workspace_id: p.workspace_id.as_i64().unwrap(),
+        }
+    }
+}
+
+impl From<api::Workspace> for Workspace {
+    fn from(w: api::Workspace) -> Self {
+        Self {
+            default_currency: w.default_currency,
+            default_hourly_rate: w.default_hourly_rate,
+            id: w.id.as_i64().unwrap(),
+            // Toggl sends a timestamp, of which only the day matters.
+            lock_date: w
+                .lock_date
+                .and_then(|d| NaiveDate::parse_from_str(d.get(..10)?, "%Y-%m-%d").ok()),
+            name: w.name,
+            rounding: w.rounding,
+            rounding_minutes: w.rounding_minutes,
+        }
+    }
+}
+
+/// A time entry to start, from [`Client::start`]. Unset fields are left
+/// empty, or for billable, use the project's default.
+#[must_use = "the entry isn't started until `send` is called"]
+pub struct StartEntry<'a> {
+    client: &'a Client,
+    workspace_id: i64,
+    project_id: Option<i64>,
+    description: Option<String>,
+    tags: Vec<String>,
+    billable: Option<bool>,
+    start: Option<DateTime<Utc>>,
+}
+
+impl StartEntry<'_> {
+    pub fn project(mut self, project_id: i64) -> Self {
+        self.project_id = Some(project_id);
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    pub fn billable(mut self, billable: bool) -> Self {
+        self.billable = Some(billable);
+        self
+    }
+
+    /// Starts the entry at `start` instead of now.
+    pub fn start_at(mut self, start: DateTime<Utc>) -> Self {
+        self.start = Some(start);
+        self
+    }
+
+    /// Starts the entry in Toggl.
+    pub fn send(self) -> Result<TimeEntry> {
+        let client = self.client;
+        let start = self.start.unwrap_or_else(|| (client.get_now)());
+        let api_entry = client.c.create_time_entry(api::NewTimeEntry {
+            billable: self.billable,
+            created_with: CREATED_WITH.to_string(),
+            description: self.description,
+            duration: (-start.timestamp()).into(),
+            project_id: self.project_id.map(|i| i.into()),
+            start: start.to_rfc3339(),
+            stop: None,
+            tags: (!self.tags.is_empty()).then_some(self.tags),
+            task_id: None,
+            workspace_id: self.workspace_id.into(),
+        })?;
+
+        client.build_time_entry(api_entry)
+    }
+}
+
+/// Changes to apply with [`Client::update_time_entry`]. Only the fields
+/// that are set are changed.
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct TimeEntryUpdate {
+    pub billable: Option<bool>,
+    pub description: Option<String>,
+    pub project_id: Option<Option<i64>>,
+    pub start: Option<DateTime<Utc>>,
+    pub stop: Option<DateTime<Utc>>,
+    pub tags: Option<Vec<String>>,
+}
+
+impl TimeEntryUpdate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn billable(mut self, billable: bool) -> Self {
+        self.billable = Some(billable);
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Sets the project, or removes it with `None`.
+    pub fn project_id(mut self, project_id: Option<i64>) -> Self {
+        self.project_id = Some(project_id);
+        self
+    }
+
+    pub fn start(mut self, start: DateTime<Utc>) -> Self {
+        self.start = Some(start);
+        self
+    }
+
+    pub fn stop(mut self, stop: DateTime<Utc>) -> Self {
+        self.stop = Some(stop);
+        self
+    }
+
+    pub fn tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = Some(tags);
+        self
+    }
+
+    /// Returns `true` if the update wouldn't change anything.
+    pub fn is_empty(&self) -> bool {
+        self.billable.is_none()
+            && self.description.is_none()
+            && self.project_id.is_none()
+            && self.start.is_none()
+            && self.stop.is_none()
+            && self.tags.is_none()
+    }
+}
+
+/// Changes to apply to many entries at once with
+/// [`Client::bulk_update_time_entries`]. Only the fields that are set are
+/// changed.
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct BulkUpdate {
+    /// Tags to add to each entry, keeping its existing tags.
+    pub add_tags: Vec<String>,
+    pub billable: Option<bool>,
+    pub description: Option<String>,
+    pub project_id: Option<Option<i64>>,
+}
+
+impl BulkUpdate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_tags(mut self, tags: Vec<String>) -> Self {
+        self.add_tags = tags;
+        self
+    }
+
+    pub fn billable(mut self, billable: bool) -> Self {
+        self.billable = Some(billable);
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Sets the project, or removes it with `None`.
+    pub fn project_id(mut self, project_id: Option<i64>) -> Self {
+        self.project_id = Some(project_id);
+        self
+    }
+
+    /// Returns `true` if the update wouldn't change anything.
+    pub fn is_empty(&self) -> bool {
+        self.operations().is_empty()
+    }
+
+    fn operations(&self) -> Vec<api::PatchOperation> {
+        let mut operations = Vec::new();
+        if !self.add_tags.is_empty() {
+            operations.push(api::PatchOperation {
+                op: "add",
+                path: "/tags",
+                value: self.add_tags.clone().into(),
+            });
+        }
+        if let Some(billable) = self.billable {
+            operations.push(api::PatchOperation {
+                op: "replace",
+                path: "/billable",
+                value: billable.into(),
+            });
+        }
+        if let Some(description) = &self.description {
+            operations.push(api::PatchOperation {
+                op: "replace",
+                path: "/description",
+                value: description.clone().into(),
+            });
+        }
+        if let Some(project_id) = self.project_id {
+            operations.push(api::PatchOperation {
+                op: "replace",
+                path: "/project_id",
+                value: project_id.into(),
+            });
+        }
+
+        operations
+    }
 }
 
+/// The outcome of [`Client::bulk_update_time_entries`].
+#[derive(Debug, Default)]
+#[non_exhaustive]
+pub struct BulkUpdateResult {
+    /// IDs of the entries that were updated.
+    pub updated: Vec<i64>,
+    /// IDs of the entries that weren't updated, with Toggl's reason.
+    pub failed: Vec<(i64, String)>,
+}
+
+/// A saved time entry preset from the Toggl favorites list.
 #[derive(Debug)]
+#[non_exhaustive]
+pub struct Favorite {
+    pub billable: bool,
+    pub description: Option<String>,
+    pub id: i64,
+    pub project_id: Option<i64>,
+    pub project_name: Option<String>,
+    pub tags: Vec<String>,
+    pub workspace_id: i64,
+}
+
+/// A Toggl client, i.e. the customer that projects are done for.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct WorkspaceClient {
+    pub archived: bool,
+    pub id: i64,
+    pub name: String,
+    pub workspace_id: i64,
+}
+
+impl From<api::WorkspaceClient> for WorkspaceClient {
+    fn from(c: api::WorkspaceClient) -> Self {
+        Self {
+            archived: c.archived,
+            id: c.id.as_i64().unwrap(),
+            name: c.name,
+            workspace_id: c.wid.as_i64().unwrap(),
+        }
+    }
+}
+
+/// A URL that Toggl sends a workspace's events to.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct WebhookSubscription {
+    pub description: String,
+    pub enabled: bool,
+    pub id: i64,
+    /// The key that Toggl signs the subscription's events with.
+    pub secret: Option<String>,
+    pub url: String,
+    /// Whether the URL answered Toggl's validation ping. Toggl sends no
+    /// events until it has.
+    pub validated: bool,
+    pub workspace_id: i64,
+}
+
+impl From<api::WebhookSubscription> for WebhookSubscription {
+    fn from(s: api::WebhookSubscription) -> Self {
+        Self {
+            description: s.description,
+            enabled: s.enabled,
+            id: s.subscription_id.as_i64().unwrap(),
+            secret: s.secret,
+            url: s.url_callback,
+            validated: s.validated_at.is_some(),
+            workspace_id: s.workspace_id.as_i64().unwrap(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct Workspace {
+    pub default_currency: Option<String>,
+    /// Hourly rate for billable time in projects without their own rate.
+    pub default_hourly_rate: Option<f64>,
     pub id: i64,
+    /// Entries that start before this day can no longer be changed.
+    #[serde(default)]
+    pub lock_date: Option<NaiveDate>,
     pub name: String,
+    /// How reports round durations: -1 down, 0 to the nearest interval, or
+    /// 1 up.
+    #[serde(default)]
+    pub rounding: i64,
+    /// The interval reports round durations to, or 0 for no rounding.
+    #[serde(default)]
+    pub rounding_minutes: i64,
+}
+
+/// Serializes a [`Duration`] as whole seconds.
+mod duration_seconds {
+    use chrono::Duration;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(dur: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(dur.num_seconds())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        i64::deserialize(deserializer).map(Duration::seconds)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn bulk_update_operations() {
+        let update = BulkUpdate::new()
+            .add_tags(vec!["meeting".into()])
+            .project_id(None);
+        let operations = serde_json::to_value(update.operations()).unwrap();
+
+        assert_eq!(
+            serde_json::json!([
+                {"op": "add", "path": "/tags", "value": ["meeting"]},
+                {"op": "replace", "path": "/project_id", "value": null},
+            ]),
+            operations
+        );
+        assert!(BulkUpdate::new().is_empty());
+    }
+
+    #[test]
+    fn entries_touch_ranges_they_start_or_stop_in() {
+        let at = |h| Utc.with_ymd_and_hms(2024, 6, 3, h, 0, 0).unwrap();
+        let entry = TimeEntry {
+            billable: false,
+            client_id: None,
+            client_name: None,
+            description: None,
+            duration: Duration::hours(2),
+            id: 1,
+            is_running: false,
+            project_id: None,
+            project_color: None,
+            project_name: None,
+            start: Some(at(9)),
+            stop: Some(at(11)),
+            tags: Vec::new(),
+            task_id: None,
+            workspace_id: 1,
+        };
+
+        assert!(entry.touches(at(8), at(10)));
+        assert!(entry.touches(at(10), at(12)));
+        assert!(!entry.touches(at(12), at(13)));
+        assert!(!entry.touches(at(6), at(9)));
+    }
+
+    #[test]
+    #[cfg(feature = "mirror")]
+    fn unindexed_ranges_extend_the_span() {
+        let day = |d| Utc.with_ymd_and_hms(2024, 1, d, 0, 0, 0).unwrap();
+        let indexed = Some((day(10), day(20)));
+
+        assert_eq!(
+            vec![(day(1), day(31))],
+            unindexed_ranges(day(1), day(31), None, day(25))
+        );
+        assert_eq!(
+            vec![(day(18), day(21))],
+            unindexed_ranges(day(12), day(21), indexed, day(18))
+        );
+        assert_eq!(
+            vec![(day(20), day(30)), (day(5), day(10))],
+            unindexed_ranges(day(5), day(30), indexed, day(25))
+        );
+        assert_eq!(
+            vec![(day(20), day(28))],
+            unindexed_ranges(day(25), day(28), indexed, day(26))
+        );
+        assert_eq!(
+            Vec::<(DateTime<Utc>, DateTime<Utc>)>::new(),
+            unindexed_ranges(day(12), day(15), indexed, day(25))
+        );
+    }
+
+    #[test]
+    fn error_from_response_keeps_message() {
+        let error = |status, body| {
+            Error::from_response(reqwest::StatusCode::from_u16(status).unwrap(), body)
+        };
+
+        assert!(matches!(
+            error(403, "Incorrect username and/or password"),
+            Error::Auth
+        ));
+        assert!(matches!(
+            error(400, r#""Workspace needs a project""#),
+            Error::Api { status: 400, message } if message == "Workspace needs a project"
+        ));
+        assert!(matches!(
+            error(429, r#"{"message": "Too many requests, slow down"}"#),
+            Error::Api { message, .. } if message == "Too many requests, slow down"
+        ));
+        assert!(matches!(
+            error(502, ""),
+            Error::Api { status: 502, message } if message == "Bad Gateway"
+        ));
+        assert!(error(502, "").is_unavailable());
+    }
+
+    #[test]
+    fn latest_window_beyond_dates_is_an_error() {
+        let client = Client::new("token".to_string(), Utc::now)
+            .unwrap()
+            .with_latest_window(Some(u32::MAX), None);
+
+        assert!(matches!(
+            client.get_latest_entries(),
+            Err(Error::OutOfRange)
+        ));
+    }
+
+    #[test]
+    fn split_range_covers_range_latest_first() {
+        let day = |d| Utc.with_ymd_and_hms(2024, 1, d, 0, 0, 0).unwrap();
+
+        assert_eq!(
+            vec![(day(1), day(5))],
+            split_range(day(1), day(5), Duration::days(7))
+        );
+        assert_eq!(
+            vec![(day(24), day(31)), (day(17), day(24)), (day(15), day(17))],
+            split_range(day(15), day(31), Duration::days(7))
+        );
+    }
+
+    #[test]
+    fn revalidated_keeps_unchanged_data() {
+        let cached = Some(("\"v1\"".to_string(), vec![1i64]));
+
+        assert_eq!(
+            (vec![1], Some("\"v1\"".to_string())),
+            revalidated::<i64, i64>(api::Conditional::NotModified, cached.clone())
+        );
+        assert_eq!(
+            (vec![2], Some("\"v2\"".to_string())),
+            revalidated::<i64, i64>(
+                api::Conditional::Modified {
+                    value: vec![2],
+                    etag: Some("\"v2\"".to_string()),
+                },
+                cached
+            )
+        );
+    }
+
+    #[test]
+    fn time_entry_serializes_duration_as_seconds() {
+        let entry: TimeEntry = serde_json::from_value(serde_json::json!({
+            "billable": false,
+            "client_id": null,
+            "description": "Standup",
+            "duration": 1200,
+            "id": 1,
+            "is_running": false,
+            "project_id": null,
+            "project_name": null,
+            "start": "2024-06-03T09:00:00Z",
+            "stop": "2024-06-03T09:20:00Z",
+            "tags": [],
+            "task_id": null,
+            "workspace_id": 2,
+        }))
+        .unwrap();
+
+        assert_eq!(Duration::minutes(20), entry.duration);
+        assert_eq!(1200, serde_json::to_value(&entry).unwrap()["duration"]);
+    }
+
     #[test]
     fn parse_duration_stopped() {
         let now = Utc.timestamp_opt(1404810600, 0).unwrap();
@@ -0,0 +1,99 @@
+//! Commands that tag a time entry while the work is still going on.
+
+use crate::{
+    config::Config, decorate, ensure_unlocked, entry::apply_update, get_client, icon, is_quiet,
+    report_change,
+};
+use anyhow::{anyhow, Context, Result};
+use clap::{Args, Subcommand};
+use tgl_cli::TimeEntryUpdate;
+
+#[derive(Subcommand)]
+pub enum TagCommand {
+    /// Add tags to the running time entry, or the given one
+    Add(TagArgs),
+    /// Remove tags from the running time entry, or the given one
+    Remove(TagArgs),
+}
+
+#[derive(Args)]
+pub struct TagArgs {
+    /// Tags to add or remove, ignoring case
+    #[arg(required = true)]
+    tags: Vec<String>,
+    /// Toggl ID of the entry, as shown by `tgl status --ids` [default: the
+    /// running entry]
+    #[arg(long)]
+    id: Option<i64>,
+}
+
+/// Adds the tags the entry doesn't have yet, keeping its own order.
+fn add_tags(current: &[String], tags: &[String]) -> Vec<String> {
+    let mut added = current.to_vec();
+    for tag in tags {
+        if !added.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            added.push(tag.clone());
+        }
+    }
+
+    added
+}
+
+fn remove_tags(current: &[String], tags: &[String]) -> Vec<String> {
+    current
+        .iter()
+        .filter(|t| !tags.iter().any(|tag| tag.eq_ignore_ascii_case(t)))
+        .cloned()
+        .collect()
+}
+
+pub fn run_tag(config: &Config, command: &TagCommand) -> Result<()> {
+    let (TagCommand::Add(args) | TagCommand::Remove(args)) = command;
+    let client = get_client()?;
+    let entry = match args.id {
+        Some(id) => client
+            .get_time_entry(id)
+            .with_context(|| format!("Failed to retrieve time entry {id}"))?,
+        None => client
+            .get_current_entry()
+            .context("Failed to retrieve current time entry")?
+            .ok_or_else(|| anyhow!("{} No timers running, pass --id", icon("🤷")))?,
+    };
+    ensure_unlocked(&client, [&entry])?;
+
+    let tags = match command {
+        TagCommand::Add(_) => add_tags(&entry.tags, &args.tags),
+        TagCommand::Remove(_) => remove_tags(&entry.tags, &args.tags),
+    };
+    if tags == entry.tags {
+        if is_quiet() {
+            println!("Nothing to change");
+        } else {
+            println!("{}", decorate("🤷", "Nothing to change\n"));
+        }
+        return report_change(config, &client, None);
+    }
+    let updated = apply_update(&client, &entry, &TimeEntryUpdate::new().tags(tags))?;
+
+    report_change(config, &client, updated.as_ref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changes_tags_ignoring_case() {
+        let tags = |tags: &[&str]| -> Vec<String> { tags.iter().map(|t| t.to_string()).collect() };
+        let current = tags(&["Meeting", "billable"]);
+
+        assert_eq!(
+            tags(&["Meeting", "billable", "urgent"]),
+            add_tags(&current, &tags(&["meeting", "urgent"]))
+        );
+        assert_eq!(
+            tags(&["billable"]),
+            remove_tags(&current, &tags(&["MEETING", "unknown"]))
+        );
+    }
+}
@@ -0,0 +1,252 @@
+//! Progress toward weekly targets: each project's from `[targets]`, and the
+//! overall one from `target_hours_per_week`.
+
+use crate::{
+    config::{Config, WorkHours},
+    decorate,
+    duration::fmt_duration,
+    eprintln_stale_banner, get_client, i18n, icon, icon_column,
+    report::get_range_entries,
+    suggest::read_days_off,
+};
+use anyhow::Result;
+use chrono::{Datelike, Days, Duration, Local, NaiveDateTime};
+use std::collections::HashMap;
+
+/// How far from the expected pace a project can be, as a share of its
+/// weekly target, before it's ahead or behind.
+const PACE_TOLERANCE: f64 = 0.1;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Pace {
+    OnTrack,
+    Ahead,
+    Behind,
+    Over,
+}
+
+fn pace(logged: Duration, expected: Duration, target: Duration) -> Pace {
+    let tolerance = target.num_seconds() as f64 * PACE_TOLERANCE;
+    let difference = (logged - expected).num_seconds() as f64;
+    if logged > target {
+        Pace::Over
+    } else if difference > tolerance {
+        Pace::Ahead
+    } else if -difference > tolerance {
+        Pace::Behind
+    } else {
+        Pace::OnTrack
+    }
+}
+
+/// How much of the week's working hours have passed by `now`, from 0 to 1.
+fn week_elapsed(work_hours: &WorkHours, now: NaiveDateTime) -> f64 {
+    let monday = now.date() - Days::new(now.weekday().num_days_from_monday().into());
+    let day_length = work_hours.end - work_hours.start;
+    let (mut elapsed, mut total) = (Duration::zero(), Duration::zero());
+    for date in (0..7).map(|d| monday + Days::new(d)) {
+        if !work_hours.is_work_day(date) {
+            continue;
+        }
+        total += day_length;
+        let start = date.and_time(work_hours.start);
+        elapsed += (now - start).clamp(Duration::zero(), day_length);
+    }
+    if total <= Duration::zero() {
+        return 1.0;
+    }
+
+    elapsed.num_seconds() as f64 / total.num_seconds() as f64
+}
+
+/// Gets the working hours with the all-day events of `days_off_ics` added to
+/// the days off. A calendar that can't be read is left out with a warning.
+pub fn target_work_hours(config: &Config) -> WorkHours {
+    let mut work_hours = config.work_hours.clone();
+    if let Some(source) = &work_hours.days_off_ics {
+        match read_days_off(source) {
+            Ok(days_off) => work_hours.days_off.extend(days_off),
+            Err(err) => eprintln!("{} Ignoring the days off in {source}: {err:#}", icon("⚠️")),
+        }
+    }
+
+    work_hours
+}
+
+/// How many of the week's work days are left at `now`, counting today until
+/// its working hours end.
+fn work_days_left(work_hours: &WorkHours, now: NaiveDateTime) -> i32 {
+    let monday = now.date() - Days::new(now.weekday().num_days_from_monday().into());
+    (0..7)
+        .map(|d| monday + Days::new(d))
+        .filter(|date| work_hours.is_work_day(*date))
+        .filter(|date| date.and_time(work_hours.end) > now)
+        .count() as i32
+}
+
+/// Shrinks a weekly target by the share of the week's work days that are
+/// days off.
+fn without_days_off(target: Duration, work_hours: &WorkHours, now: NaiveDateTime) -> Duration {
+    let monday = now.date() - Days::new(now.weekday().num_days_from_monday().into());
+    let week: Vec<_> = (0..7).map(|d| monday + Days::new(d)).collect();
+    let scheduled = week
+        .iter()
+        .filter(|date| work_hours.days.contains(&date.weekday()))
+        .count() as i32;
+    let working = week
+        .iter()
+        .filter(|date| work_hours.is_work_day(**date))
+        .count() as i32;
+    if scheduled == 0 {
+        return target;
+    }
+
+    target * working / scheduled
+}
+
+/// Describes what's left of `target_hours_per_week` after `logged` this week,
+/// and how much it takes on each remaining work day. Nothing without a
+/// target.
+pub fn week_forecast(config: &Config, logged: Duration) -> Option<String> {
+    let target = config.weekly_target()?;
+    let work_hours = target_work_hours(config);
+    let now = Local::now().naive_local();
+    let target = without_days_off(target, &work_hours, now);
+    let fmt = |dur| fmt_duration(dur, config.duration_format);
+    let remaining = target - logged;
+    if remaining <= Duration::zero() {
+        let message = i18n::tr("week-target-met", &[("target", fmt(target).into())]);
+        return Some(decorate("🎯", &message));
+    }
+
+    let message = match work_days_left(&work_hours, now) {
+        0 => i18n::tr(
+            "week-remaining-none",
+            &[("remaining", fmt(remaining).into())],
+        ),
+        days => i18n::tr(
+            "week-remaining",
+            &[
+                ("remaining", fmt(remaining).into()),
+                ("daily", fmt(remaining / days).into()),
+                ("days", days.into()),
+            ],
+        ),
+    };
+
+    Some(decorate("🎯", &message))
+}
+
+pub fn run_targets(config: &Config) -> Result<()> {
+    if config.targets.is_empty() {
+        println!(
+            "{}",
+            decorate(
+                "🤷",
+                "No targets. Add some under [targets] in the config file"
+            )
+        );
+        return Ok(());
+    }
+
+    let client = get_client()?;
+    let now = Local::now();
+    let today = now.date_naive();
+    let monday = today - Days::new(today.weekday().num_days_from_monday().into());
+    let entries = get_range_entries(&client, monday, monday + Days::new(6))?;
+    eprintln_stale_banner(&client);
+
+    let mut logged: HashMap<&str, Duration> = HashMap::new();
+    for entry in &entries {
+        if let Some(project) = &entry.project_name {
+            *logged.entry(project).or_insert_with(Duration::zero) += entry.duration;
+        }
+    }
+    let elapsed = week_elapsed(&target_work_hours(config), now.naive_local());
+    let fmt = |dur| fmt_duration(dur, config.duration_format);
+    for (project, target) in &config.targets {
+        let logged = logged.get(project.as_str()).copied().unwrap_or_default();
+        let goal = target.duration();
+        let expected = Duration::seconds((goal.num_seconds() as f64 * elapsed) as i64);
+        let percent = match goal.num_seconds() {
+            0 => 0.0,
+            seconds => logged.num_seconds() as f64 / seconds as f64 * 100.0,
+        };
+        let (emoji, note) = match pace(logged, expected, goal) {
+            Pace::OnTrack => (None, String::new()),
+            Pace::Ahead => (
+                None,
+                format!(" ahead of pace, {} expected by now", fmt(expected)),
+            ),
+            Pace::Behind => (
+                Some("⚠️ "),
+                format!(" behind pace, {} expected by now", fmt(expected)),
+            ),
+            Pace::Over => (Some("🔥"), format!(" over by {}", fmt(logged - goal))),
+        };
+        println!(
+            "{} {:>9} of {:>9} {percent:>4.0}% [{}]{note}",
+            icon_column(emoji),
+            fmt(logged),
+            fmt(goal),
+            config.theme.project.paint(project)
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn elapsed_counts_work_hours_only() {
+        let work_hours = WorkHours::default();
+        // 2024-06-03 is a Monday.
+        let at = |day, hour| {
+            NaiveDate::from_ymd_opt(2024, 6, day)
+                .unwrap()
+                .and_hms_opt(hour, 0, 0)
+                .unwrap()
+        };
+
+        assert_eq!(0.0, week_elapsed(&work_hours, at(3, 8)));
+        assert_eq!(0.1, week_elapsed(&work_hours, at(3, 13)));
+        assert_eq!(0.6, week_elapsed(&work_hours, at(5, 23)));
+        assert_eq!(1.0, week_elapsed(&work_hours, at(9, 12)));
+    }
+
+    #[test]
+    fn days_left_include_today_until_work_ends() {
+        let mut work_hours = WorkHours::default();
+        // 2024-06-05 is a Wednesday.
+        let at = |day, hour| {
+            NaiveDate::from_ymd_opt(2024, 6, day)
+                .unwrap()
+                .and_hms_opt(hour, 0, 0)
+                .unwrap()
+        };
+
+        assert_eq!(3, work_days_left(&work_hours, at(5, 12)));
+        assert_eq!(2, work_days_left(&work_hours, at(5, 17)));
+        assert_eq!(0, work_days_left(&work_hours, at(8, 12)));
+        work_hours.days_off.push(at(6, 0).date());
+        assert_eq!(2, work_days_left(&work_hours, at(5, 12)));
+        assert_eq!(
+            Duration::hours(32),
+            without_days_off(Duration::hours(40), &work_hours, at(5, 12))
+        );
+    }
+
+    #[test]
+    fn paces() {
+        let hours = Duration::hours;
+
+        assert_eq!(Pace::OnTrack, pace(hours(4), hours(5), hours(10)));
+        assert_eq!(Pace::Behind, pace(hours(3), hours(5), hours(10)));
+        assert_eq!(Pace::Ahead, pace(hours(7), hours(5), hours(10)));
+        assert_eq!(Pace::Over, pace(hours(11), hours(10), hours(10)));
+    }
+}
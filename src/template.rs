@@ -0,0 +1,165 @@
+//! User-definable output templates, e.g. `"{duration} {project}"`.
+
+use serde::{de, Deserialize, Deserializer};
+use std::{fmt, str::FromStr};
+
+/// A value that can be substituted into a [`Template`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Field {
+    Client,
+    Description,
+    Duration,
+    DurationDecimal,
+    Id,
+    Index,
+    Project,
+    Start,
+    Stop,
+}
+
+impl Field {
+    const ALL: [(&'static str, Field); 9] = [
+        ("client", Field::Client),
+        ("description", Field::Description),
+        ("duration", Field::Duration),
+        ("duration_decimal", Field::DurationDecimal),
+        ("id", Field::Id),
+        ("index", Field::Index),
+        ("project", Field::Project),
+        ("start", Field::Start),
+        ("stop", Field::Stop),
+    ];
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Part {
+    Literal(String),
+    Field(Field),
+}
+
+/// A parsed format string. Placeholders are written as `{name}`; use `{{`
+/// and `}}` for literal braces.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+impl Template {
+    /// Renders the template, asking `value` for each placeholder.
+    pub fn render(&self, mut value: impl FnMut(Field) -> String) -> String {
+        let mut out = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Literal(s) => out.push_str(s),
+                Part::Field(f) => out.push_str(&value(*f)),
+            }
+        }
+
+        out
+    }
+
+    /// Returns `true` if the template contains the given placeholder.
+    pub fn uses(&self, field: Field) -> bool {
+        self.parts.contains(&Part::Field(field))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseError(String);
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl FromStr for Template {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = s.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err(ParseError("Unclosed '{' in template".into())),
+                        }
+                    }
+                    let field = Field::ALL
+                        .iter()
+                        .find(|(n, _)| *n == name)
+                        .map(|(_, f)| *f)
+                        .ok_or_else(|| {
+                            let names: Vec<_> = Field::ALL.iter().map(|(n, _)| *n).collect();
+                            ParseError(format!(
+                                "Unknown placeholder {{{name}}}, expected one of: {}",
+                                names.join(", ")
+                            ))
+                        })?;
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(Part::Field(field));
+                }
+                '}' => return Err(ParseError("Unmatched '}' in template".into())),
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+
+        Ok(Template { parts })
+    }
+}
+
+impl<'de> Deserialize<'de> for Template {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_placeholders_and_escapes() {
+        let template: Template = "{{{project}}} {duration}h".parse().unwrap();
+        let out = template.render(|f| match f {
+            Field::Project => "Website".into(),
+            Field::Duration => "1:45:00".into(),
+            _ => unreachable!(),
+        });
+
+        assert_eq!("{Website} 1:45:00h", out);
+        assert!(template.uses(Field::Project));
+        assert!(!template.uses(Field::Client));
+    }
+
+    #[test]
+    fn parse_rejects_unknown_and_unbalanced() {
+        assert!("{nope}".parse::<Template>().is_err());
+        assert!("{project".parse::<Template>().is_err());
+        assert!("project}".parse::<Template>().is_err());
+    }
+}
@@ -0,0 +1,177 @@
+//! Colors and icons used for terminal output.
+
+use clap::ValueEnum;
+use dialoguer::console::{self, Style};
+use serde::{Deserialize, Deserializer};
+use std::env;
+
+#[derive(Deserialize, ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorChoice {
+    /// Use colors when writing to a terminal and `NO_COLOR` is not set
+    #[default]
+    Auto,
+    /// Always use colors
+    Always,
+    /// Never use colors
+    Never,
+}
+
+impl ColorChoice {
+    /// Enables or disables colors for all output, including prompts.
+    pub fn apply(self) {
+        let enabled = match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            // See https://no-color.org/
+            ColorChoice::Auto if env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) => false,
+            ColorChoice::Auto => return,
+        };
+        console::set_colors_enabled(enabled);
+        console::set_colors_enabled_stderr(enabled);
+    }
+}
+
+/// How messages are marked, configured with `icons`.
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Icons {
+    #[default]
+    Emoji,
+    /// ASCII stand-ins for terminals and fonts that render emoji poorly
+    Ascii,
+}
+
+impl Icons {
+    /// Gets what to show for an emoji, which may be followed by padding.
+    pub fn get(self, emoji: &str) -> &str {
+        match self {
+            Icons::Emoji => emoji,
+            Icons::Ascii => ascii(emoji.trim_end()),
+        }
+    }
+}
+
+fn ascii(emoji: &str) -> &'static str {
+    match emoji {
+        "✅" | "📁" | "📂" => "+",
+        "🤷" => "-",
+        "⏱" | "⚖️" | "🎯" => "=",
+        "⚠️" | "🔑" | "📴" | "🔒" => "!",
+        "🔥" => "!!",
+        "💰" => "$",
+        "🧪" => "~",
+        "🗄️" => "#",
+        "❌" | "🛑" => "x",
+        "↩️" => "<",
+        "⏰" => "@",
+        "▶" => ">",
+        "⏳:⏳" => "--:--",
+        _ => "*",
+    }
+}
+
+/// Output styles, configurable under `[theme]` using dotted style strings
+/// such as `"cyan.bold"`.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    pub project: ThemeStyle,
+    /// Whether project names take the color of their project in Toggl
+    /// instead of the one in `project`.
+    pub project_colors: bool,
+    pub running: ThemeStyle,
+    pub total: ThemeStyle,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            project: ThemeStyle(Style::new().cyan()),
+            project_colors: true,
+            running: ThemeStyle(Style::new().green()),
+            total: ThemeStyle(Style::new().bold()),
+        }
+    }
+}
+
+impl Theme {
+    /// Paints a project's name in the project style, recolored with the
+    /// project's own hex color when it has one.
+    pub fn paint_project<D>(&self, name: D, color: Option<&str>) -> console::StyledObject<D> {
+        match color.filter(|_| self.project_colors).and_then(nearest_256) {
+            Some(color) => self.project.0.clone().color256(color).apply_to(name),
+            None => self.project.paint(name),
+        }
+    }
+}
+
+/// Finds the closest of the 256 terminal colors to a hex color such as
+/// `#0b83d9`, from the 6×6×6 color cube and the gray ramp.
+fn nearest_256(hex: &str) -> Option<u8> {
+    const LEVELS: [i32; 6] = [0, 95, 135, 175, 215, 255];
+    let hex = hex.strip_prefix('#').unwrap_or(hex);
+    if hex.len() != 6 {
+        return None;
+    }
+    let channel = |i: usize| {
+        hex.get(i..i + 2)
+            .and_then(|c| i32::from_str_radix(c, 16).ok())
+    };
+    let rgb = [channel(0)?, channel(2)?, channel(4)?];
+    let distance =
+        |other: [i32; 3]| -> i32 { rgb.iter().zip(other).map(|(a, b)| (a - b) * (a - b)).sum() };
+
+    let level = |c: i32| {
+        (0..LEVELS.len())
+            .min_by_key(|&i| (LEVELS[i] - c).abs())
+            .unwrap()
+    };
+    let [r, g, b] = rgb.map(level);
+    let cube = [LEVELS[r], LEVELS[g], LEVELS[b]];
+    let gray = ((rgb.iter().sum::<i32>() / 3 - 8) / 10).clamp(0, 23);
+    let gray_level = 8 + 10 * gray;
+
+    if distance([gray_level; 3]) < distance(cube) {
+        Some(232 + gray as u8)
+    } else {
+        Some(16 + (36 * r + 6 * g + b) as u8)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ThemeStyle(Style);
+
+impl ThemeStyle {
+    pub fn paint<D>(&self, val: D) -> console::StyledObject<D> {
+        self.0.apply_to(val)
+    }
+}
+
+impl<'de> Deserialize<'de> for ThemeStyle {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let spec = String::deserialize(deserializer)?;
+        Ok(ThemeStyle(Style::from_dotted_str(&spec)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nearest_terminal_colors() {
+        assert_eq!(Some(32), nearest_256("#0b83d9"));
+        assert_eq!(Some(231), nearest_256("#ffffff"));
+        assert_eq!(Some(244), nearest_256("#808080"));
+        assert_eq!(None, nearest_256("blue"));
+    }
+
+    #[test]
+    fn ascii_icons_drop_padding() {
+        assert_eq!("⏱ ", Icons::Emoji.get("⏱ "));
+        assert_eq!("=", Icons::Ascii.get("⏱ "));
+        assert_eq!("--:--", Icons::Ascii.get("⏳:⏳"));
+        assert_eq!("*", Icons::Ascii.get("🦀"));
+    }
+}
@@ -0,0 +1,73 @@
+//! The operations every time tracking backend supports, so that callers can
+//! work with Toggl or [Clockify](crate::ClockifyClient) alike.
+
+use crate::svc::{Client, Project, Result, TimeEntry, Workspace};
+use chrono::{DateTime, Utc};
+
+/// A time tracking service. [`Client`] talks to Toggl and has many more
+/// operations of its own; this covers what other backends support too.
+pub trait TimeTracker {
+    fn workspaces(&self) -> Result<Vec<Workspace>>;
+
+    /// Gets the workspace's projects, including archived ones.
+    fn projects(&self, workspace_id: i64) -> Result<Vec<Project>>;
+
+    /// Gets the time entries that started within `[start, end)`.
+    fn time_entries(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<TimeEntry>>;
+
+    fn current_entry(&self) -> Result<Option<TimeEntry>>;
+
+    /// Starts a running time entry at `start`.
+    fn start_entry(
+        &self,
+        workspace_id: i64,
+        project_id: Option<i64>,
+        description: Option<&str>,
+        start: DateTime<Utc>,
+    ) -> Result<TimeEntry>;
+
+    /// Stops the running entry, if there is one, and returns it.
+    fn stop_current_entry(&self) -> Result<Option<TimeEntry>>;
+
+    /// The Toggl client behind this tracker, for callers that make use of
+    /// what only Toggl has when it's there.
+    fn toggl(&self) -> Option<&Client> {
+        None
+    }
+}
+
+impl TimeTracker for Client {
+    fn workspaces(&self) -> Result<Vec<Workspace>> {
+        self.get_workspaces()
+    }
+
+    fn projects(&self, workspace_id: i64) -> Result<Vec<Project>> {
+        self.get_projects(workspace_id)
+    }
+
+    fn time_entries(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<TimeEntry>> {
+        self.get_time_entries(start, end)
+    }
+
+    fn current_entry(&self) -> Result<Option<TimeEntry>> {
+        self.get_current_entry()
+    }
+
+    fn start_entry(
+        &self,
+        workspace_id: i64,
+        project_id: Option<i64>,
+        description: Option<&str>,
+        start: DateTime<Utc>,
+    ) -> Result<TimeEntry> {
+        self.start_time_entry_at(workspace_id, project_id, description, &[], None, start)
+    }
+
+    fn stop_current_entry(&self) -> Result<Option<TimeEntry>> {
+        self.stop_current_time_entry()
+    }
+
+    fn toggl(&self) -> Option<&Client> {
+        Some(self)
+    }
+}
@@ -0,0 +1,314 @@
+//! Toggl webhooks for `tgl daemon`, so that entries started, stopped, or
+//! changed elsewhere, such as in the mobile app, show up at once instead of
+//! on the next poll.
+//!
+//! Toggl first pings a new subscription with a validation code, which the
+//! daemon echoes back. After that, events only count when they're signed
+//! with the secret that Toggl gave the subscription, which
+//! `tgl daemon webhook add` keeps in the cache directory. Even then they only
+//! make the daemon poll again, at most once every few seconds, since the
+//! payload isn't needed.
+
+use crate::{
+    cache_path, clients::single_workspace, decorate, find_workspaces, get_client, http_api,
+    is_dry_run,
+};
+use anyhow::{anyhow, Context, Result};
+use clap::{Args, Subcommand};
+use hmac::{Hmac, Mac, NewMac};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::{collections::BTreeMap, fs, net::TcpListener, sync::mpsc, thread, time::Duration};
+use tgl_cli::{Client, WebhookSubscription};
+
+/// The subscriptions' secrets by URL, under [`cache_path`].
+const SECRETS_FILE: &str = "webhooks.json";
+
+/// The least time between the polls that events set off. Toggl sends a few
+/// events for one change, and they're taken together.
+const EVENT_GAP: Duration = Duration::from_secs(5);
+
+#[derive(Subcommand)]
+pub enum WebhookCommand {
+    /// Have Toggl send changes to time entries to a URL that reaches the
+    /// daemon's --webhook-port, e.g. through a tunnel
+    Add(WebhookArgs),
+    /// List the workspace's webhook subscriptions
+    List(WebhookListArgs),
+    /// Stop Toggl sending changes to a URL
+    Remove(WebhookArgs),
+}
+
+#[derive(Args)]
+pub struct WebhookArgs {
+    /// Public URL that forwards to the daemon's --webhook-port
+    url: String,
+    /// Name of the workspace, required if you have several
+    #[arg(long)]
+    workspace: Option<String>,
+}
+
+#[derive(Args)]
+pub struct WebhookListArgs {
+    /// Only list the subscriptions in this workspace
+    #[arg(long)]
+    workspace: Option<String>,
+}
+
+/// Toggl requires each subscription in a workspace to have its own
+/// description.
+fn description(url: &str) -> String {
+    format!("tgl daemon at {url}")
+}
+
+pub fn run_webhook(command: &WebhookCommand) -> Result<()> {
+    let client = get_client()?;
+    match command {
+        WebhookCommand::Add(args) => {
+            let workspace_id = single_workspace(&client, args.workspace.as_deref())?;
+            if let Some(subscription) = find_subscription(&client, workspace_id, &args.url)? {
+                save_secret(&args.url, subscription.secret.as_deref())?;
+                println!(
+                    "{}",
+                    decorate("🤷", &format!("{} is already subscribed", args.url))
+                );
+                return Ok(());
+            }
+            let subscription = client
+                .create_webhook_subscription(workspace_id, &args.url, &description(&args.url))
+                .context("Failed to create webhook subscription")?;
+            save_secret(&args.url, subscription.secret.as_deref())?;
+            println!("{}", decorate("✅", &format!("Subscribed {}", args.url)));
+        }
+        WebhookCommand::List(args) => {
+            let workspaces = find_workspaces(&client, args.workspace.as_deref())?;
+            for workspace in &workspaces {
+                let subscriptions = client
+                    .get_webhook_subscriptions(workspace.id)
+                    .context("Failed to retrieve webhook subscriptions")?;
+                if workspaces.len() > 1 {
+                    println!("{}", workspace.name);
+                }
+                if subscriptions.is_empty() {
+                    println!("{}", decorate("🤷", "No webhook subscriptions found"));
+                }
+                for subscription in &subscriptions {
+                    println!("{}", fmt_subscription(subscription));
+                }
+            }
+        }
+        WebhookCommand::Remove(args) => {
+            let workspace_id = single_workspace(&client, args.workspace.as_deref())?;
+            let Some(subscription) = find_subscription(&client, workspace_id, &args.url)? else {
+                println!(
+                    "{}",
+                    decorate("🤷", &format!("{} isn't subscribed", args.url))
+                );
+                return Ok(());
+            };
+            client
+                .delete_webhook_subscription(&subscription)
+                .context("Failed to delete webhook subscription")?;
+            save_secret(&args.url, None)?;
+            println!("{}", decorate("🗑️ ", &format!("Unsubscribed {}", args.url)));
+        }
+    }
+
+    Ok(())
+}
+
+fn find_subscription(
+    client: &Client,
+    workspace_id: i64,
+    url: &str,
+) -> Result<Option<WebhookSubscription>> {
+    Ok(client
+        .get_webhook_subscriptions(workspace_id)
+        .context("Failed to retrieve webhook subscriptions")?
+        .into_iter()
+        .find(|s| s.url == url))
+}
+
+fn read_secrets() -> BTreeMap<String, String> {
+    cache_path(SECRETS_FILE)
+        .and_then(|path| fs::read(path).ok())
+        .and_then(|contents| serde_json::from_slice(&contents).ok())
+        .unwrap_or_default()
+}
+
+/// Whether any subscription's secret is kept, without which no event
+/// counts.
+pub fn has_secrets() -> bool {
+    !read_secrets().is_empty()
+}
+
+/// Keeps the secret of the subscription for `url`, or forgets it.
+fn save_secret(url: &str, secret: Option<&str>) -> Result<()> {
+    if is_dry_run() {
+        return Ok(());
+    }
+    let mut secrets = read_secrets();
+    match secret {
+        Some(secret) => secrets.insert(url.to_string(), secret.to_string()),
+        None => secrets.remove(url),
+    };
+    let path = cache_path(SECRETS_FILE)
+        .ok_or_else(|| anyhow!("No cache directory to keep the webhook's secret in"))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(&path, serde_json::to_vec_pretty(&secrets)?)
+        .with_context(|| format!("Failed to save the webhook's secret to {}", path.display()))
+}
+
+fn fmt_subscription(subscription: &WebhookSubscription) -> String {
+    let mut line = format!("{} ({})", subscription.url, subscription.description);
+    if !subscription.enabled {
+        line.push_str(" (disabled)");
+    } else if !subscription.validated {
+        line.push_str(" (not validated)");
+    }
+
+    line
+}
+
+/// Answers Toggl's requests one at a time until the listener fails, calling
+/// `on_event` after signed events, but no more than once every
+/// [`EVENT_GAP`].
+pub fn serve(listener: TcpListener, on_event: impl Fn() + Send + 'static) {
+    let (events, received) = mpsc::channel();
+    thread::spawn(move || {
+        while received.recv().is_ok() {
+            // Events that came in during the wait are covered by one poll.
+            while received.try_recv().is_ok() {}
+            on_event();
+            thread::sleep(EVENT_GAP);
+        }
+    });
+    for stream in listener.incoming().flatten() {
+        let Ok(request) = http_api::read_request(&stream) else {
+            continue;
+        };
+        let secrets: Vec<_> = read_secrets().into_values().collect();
+        let (status, body, is_event) = reply(&request, &secrets);
+        // Answer first, since Toggl disables subscriptions that are slow.
+        let _ = http_api::respond(stream, status, &body);
+        if is_event {
+            let _ = events.send(());
+        }
+    }
+}
+
+/// The response to a request from Toggl, and whether it was an event.
+fn reply(request: &http_api::Request, secrets: &[String]) -> (u16, Value, bool) {
+    if request.method != "POST" {
+        return (405, json!({ "error": "Method not allowed" }), false);
+    }
+    let Ok(body) = serde_json::from_slice::<Value>(&request.body) else {
+        return (400, json!({ "error": "Invalid request body" }), false);
+    };
+
+    // Echoing a validation code or a ping sets nothing off, so only events
+    // need to be signed.
+    match (body.get("validation_code"), body.get("payload")) {
+        (Some(code), _) => (200, json!({ "validation_code": code }), false),
+        // Toggl also pings validated subscriptions now and then.
+        (None, Some(Value::String(payload))) if payload == "ping" => (200, json!({}), false),
+        (None, _)
+            if !is_signed(
+                &request.body,
+                request.header("x-webhook-signature-256"),
+                secrets,
+            ) =>
+        {
+            (401, json!({ "error": "Invalid signature" }), false)
+        }
+        (None, _) => (200, json!({}), true),
+    }
+}
+
+/// Whether `signature`, as `sha256=<hex>`, is the HMAC-SHA256 of `body`
+/// with one of the `secrets`.
+fn is_signed(body: &[u8], signature: Option<&str>, secrets: &[String]) -> bool {
+    let Some(tag) = signature
+        .and_then(|signature| signature.strip_prefix("sha256="))
+        .and_then(decode_hex)
+    else {
+        return false;
+    };
+
+    secrets.iter().any(|secret| {
+        let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(secret.as_bytes()) else {
+            return false;
+        };
+        mac.update(body);
+        mac.verify(&tag).is_ok()
+    })
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_then_takes_signed_events() {
+        let secrets = ["Jefe".to_string()];
+        let reply = |method: &str, body: &[u8], signature: Option<&str>| {
+            let request = http_api::Request {
+                method: method.to_string(),
+                path: "/".to_string(),
+                headers: signature
+                    .map(|s| ("x-webhook-signature-256".to_string(), s.to_string()))
+                    .into_iter()
+                    .collect(),
+                body: body.to_vec(),
+            };
+            reply(&request, &secrets)
+        };
+        let ping = br#"{"payload": "ping", "validation_code": "abc"}"#;
+        assert_eq!(
+            (200, json!({ "validation_code": "abc" }), false),
+            reply("POST", ping, None)
+        );
+
+        let event = br#"{"metadata": {"action": "updated", "model": "time_entry"}}"#;
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"Jefe").unwrap();
+        mac.update(event);
+        let signature: String = mac
+            .finalize()
+            .into_bytes()
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect();
+        assert_eq!(
+            (200, json!({}), true),
+            reply("POST", event, Some(&format!("sha256={signature}")))
+        );
+        assert_eq!(401, reply("POST", event, None).0);
+        assert_eq!(401, reply("POST", event, Some("sha256=00")).0);
+        assert_eq!(
+            (200, json!({}), false),
+            reply("POST", br#"{"payload": "ping"}"#, None)
+        );
+        assert_eq!(400, reply("POST", b"not json", None).0);
+        assert_eq!(405, reply("GET", b"", None).0);
+
+        // RFC 4231, test case 2.
+        assert!(is_signed(
+            b"what do ya want for nothing?",
+            Some("sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"),
+            &secrets
+        ));
+    }
+}
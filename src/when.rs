@@ -0,0 +1,167 @@
+//! Dates given the way people say them, e.g. `yesterday` or `last monday`.
+
+use chrono::{Datelike, Days, Local, Months, NaiveDate, Weekday};
+
+/// Parses a date argument relative to today. See [`parse_date_from`].
+pub fn parse_date(s: &str) -> Result<NaiveDate, String> {
+    parse_date_from(s, Local::now().date_naive())
+}
+
+/// Parses a month argument relative to today, returning its first day. See
+/// [`parse_month_from`].
+pub fn parse_month(s: &str) -> Result<NaiveDate, String> {
+    parse_month_from(s, Local::now().date_naive())
+}
+
+/// Parses `YYYY-MM-DD`, `today`, `yesterday`, `tomorrow`, `N days ago`
+/// (or weeks, months), and weekdays. A bare weekday is the latest one up to
+/// today; `last` and `next` pick the one strictly before or after today.
+fn parse_date_from(s: &str, today: NaiveDate) -> Result<NaiveDate, String> {
+    let invalid = || {
+        format!("Invalid date '{s}', expected e.g. 2024-06-03, yesterday, \"last monday\", or \"3 days ago\"")
+    };
+    if let Ok(date) = NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d") {
+        return Ok(date);
+    }
+
+    let s = s.trim().to_lowercase();
+    let words: Vec<_> = s.split_whitespace().collect();
+    let date = match words[..] {
+        ["today"] => Some(today),
+        ["yesterday"] => today.checked_sub_days(Days::new(1)),
+        ["tomorrow"] => today.checked_add_days(Days::new(1)),
+        [count, unit, "ago"] => {
+            let count: u32 = count.parse().map_err(|_| invalid())?;
+            match unit.trim_end_matches('s') {
+                "day" => today.checked_sub_days(Days::new(count.into())),
+                "week" => today.checked_sub_days(Days::new(u64::from(count) * 7)),
+                "month" => today.checked_sub_months(Months::new(count)),
+                _ => None,
+            }
+        }
+        [day] => {
+            let day = parse_weekday(day).ok_or_else(invalid)?;
+            Some(today - Days::new(days_since(today.weekday(), day)))
+        }
+        ["last", day] => {
+            let day = parse_weekday(day).ok_or_else(invalid)?;
+            let back = match days_since(today.weekday(), day) {
+                0 => 7,
+                n => n,
+            };
+            Some(today - Days::new(back))
+        }
+        ["next", day] => {
+            let day = parse_weekday(day).ok_or_else(invalid)?;
+            let ahead = match days_since(day, today.weekday()) {
+                0 => 7,
+                n => n,
+            };
+            Some(today + Days::new(ahead))
+        }
+        _ => None,
+    };
+
+    date.ok_or_else(invalid)
+}
+
+/// Parses `YYYY-MM`, `this`, `last`, `next`, or a month name, which means
+/// the latest such month up to this one.
+fn parse_month_from(s: &str, today: NaiveDate) -> Result<NaiveDate, String> {
+    let invalid = || format!("Invalid month '{s}', expected e.g. 2024-06, last, or june");
+    let this_month = today.with_day(1).expect("every month has a first day");
+    let s = s.trim().to_lowercase();
+    let month = match s.as_str() {
+        "this" => Some(this_month),
+        "last" => this_month.checked_sub_months(Months::new(1)),
+        "next" => this_month.checked_add_months(Months::new(1)),
+        name => match NaiveDate::parse_from_str(&format!("{name}-01"), "%Y-%m-%d") {
+            Ok(month) => Some(month),
+            Err(_) => {
+                let month = parse_month_name(name).ok_or_else(invalid)?;
+                let back = (this_month.month() + 12 - month) % 12;
+                this_month.checked_sub_months(Months::new(back))
+            }
+        },
+    };
+
+    month.ok_or_else(invalid)
+}
+
+/// How many days back from `from` the latest `day` is.
+fn days_since(from: Weekday, day: Weekday) -> u64 {
+    ((from.num_days_from_monday() + 7 - day.num_days_from_monday()) % 7).into()
+}
+
+fn parse_weekday(s: &str) -> Option<Weekday> {
+    // chrono accepts full names and three-letter abbreviations.
+    s.parse().ok()
+}
+
+/// Gets a month's number from its name or three-letter abbreviation.
+fn parse_month_name(s: &str) -> Option<u32> {
+    const NAMES: [&str; 12] = [
+        "january",
+        "february",
+        "march",
+        "april",
+        "may",
+        "june",
+        "july",
+        "august",
+        "september",
+        "october",
+        "november",
+        "december",
+    ];
+    let i = NAMES
+        .iter()
+        .position(|name| s.len() >= 3 && name.starts_with(s))?;
+
+    Some(i as u32 + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, month, day).unwrap()
+    }
+
+    #[test]
+    fn parse_relative_dates() {
+        // A Wednesday.
+        let today = date(6, 5);
+        let parse = |s| parse_date_from(s, today);
+
+        assert_eq!(Ok(date(3, 1)), parse("2024-03-01"));
+        assert_eq!(Ok(date(6, 4)), parse("Yesterday"));
+        assert_eq!(Ok(date(6, 2)), parse("3 days ago"));
+        assert_eq!(Ok(date(5, 22)), parse("2 weeks ago"));
+        assert_eq!(Ok(date(5, 5)), parse("1 month ago"));
+        assert_eq!(Ok(date(6, 3)), parse("monday"));
+        assert_eq!(Ok(date(6, 5)), parse("wed"));
+        assert_eq!(Ok(date(5, 29)), parse("last wednesday"));
+        assert_eq!(Ok(date(6, 3)), parse("last monday"));
+        assert_eq!(Ok(date(6, 10)), parse("next monday"));
+        assert!(parse("someday").is_err());
+        assert!(parse("x days ago").is_err());
+    }
+
+    #[test]
+    fn parse_relative_months() {
+        let today = date(2, 14);
+        let parse = |s| parse_month_from(s, today);
+
+        assert_eq!(Ok(date(2, 1)), parse("this"));
+        assert_eq!(
+            Ok(NaiveDate::from_ymd_opt(2023, 12, 1).unwrap()),
+            parse("december")
+        );
+        assert_eq!(Ok(date(1, 1)), parse("Jan"));
+        assert_eq!(Ok(date(1, 1)), parse("last"));
+        assert_eq!(Ok(date(6, 1)), parse("2024-06"));
+        assert!(parse("ju").is_err());
+    }
+}
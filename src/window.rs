@@ -0,0 +1,197 @@
+//! Describing entries after the windows you used, for `tgl daemon`. While an
+//! entry without a description runs, the daemon notes the focused window on
+//! each poll. Once the entry stops, still without a description, it's
+//! described after the window that was focused most.
+//!
+//! The focused window comes from `xdotool` on X11 and from System Events on
+//! macOS. Wayland doesn't let other programs see it.
+
+use crate::{config::Config, decorate, icon, idle::output};
+use chrono::{DateTime, Local};
+use std::collections::HashMap;
+use tgl_cli::{Client, TimeEntry, TimeEntryUpdate};
+
+/// A focused window: the app it belongs to and its title, which often names
+/// the document that's open.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Window {
+    pub app: String,
+    pub title: String,
+}
+
+/// How often each window was focused while an entry ran.
+#[derive(Default)]
+pub struct Tracker {
+    entry_id: Option<i64>,
+    samples: HashMap<Window, u32>,
+}
+
+impl Tracker {
+    /// Notes the focused window for the running entry. Returns the entry
+    /// that stopped since the last call, if it has no description, with the
+    /// description it should get.
+    pub fn sample<'a>(
+        &mut self,
+        entries: &'a [TimeEntry],
+        focused: Option<Window>,
+    ) -> Option<(&'a TimeEntry, String)> {
+        let running = entries.iter().find(|e| e.is_running && !has_description(e));
+        let mut stopped = None;
+        if self.entry_id != running.map(|e| e.id) {
+            let samples = std::mem::take(&mut self.samples);
+            // An entry given a description while it ran is left alone.
+            stopped = self
+                .entry_id
+                .and_then(|id| entries.iter().find(|e| e.id == id && !e.is_running))
+                .filter(|e| !has_description(e))
+                .zip(describe(&samples));
+            self.entry_id = running.map(|e| e.id);
+        }
+        if let (Some(_), Some(window)) = (running, focused) {
+            *self.samples.entry(window).or_default() += 1;
+        }
+
+        stopped
+    }
+}
+
+fn has_description(entry: &TimeEntry) -> bool {
+    entry.description.as_deref().is_some_and(|d| !d.is_empty())
+}
+
+/// Describes time after the app that was focused most and, within that app,
+/// the title that was focused most.
+fn describe(samples: &HashMap<Window, u32>) -> Option<String> {
+    let mut apps: HashMap<&str, u32> = HashMap::new();
+    for (window, count) in samples {
+        *apps.entry(&window.app).or_default() += count;
+    }
+    // Ties go to the first name, so the description doesn't depend on
+    // hashing order.
+    let (app, _) = apps
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(a.0)))?;
+    let (window, _) = samples
+        .iter()
+        .filter(|(window, _)| window.app == app)
+        .max_by(|a, b| a.1.cmp(b.1).then(b.0.title.cmp(&a.0.title)))?;
+
+    Some(match window.title.trim() {
+        "" => window.app.clone(),
+        title if title.to_lowercase().contains(&app.to_lowercase()) => title.to_string(),
+        title => format!("{app}: {title}"),
+    })
+}
+
+/// Gets the focused window, or `None` if this system can't tell.
+pub fn focused_window() -> Option<Window> {
+    let output = if cfg!(target_os = "macos") {
+        output(
+            "osascript",
+            &[
+                "-e",
+                "tell application \"System Events\"",
+                "-e",
+                "set frontApp to first application process whose frontmost is true",
+                "-e",
+                "set frontTitle to \"\"",
+                "-e",
+                "try",
+                "-e",
+                "set frontTitle to name of front window of frontApp",
+                "-e",
+                "end try",
+                "-e",
+                "return (name of frontApp) & linefeed & frontTitle",
+                "-e",
+                "end tell",
+            ],
+        )?
+    } else {
+        output(
+            "xdotool",
+            &["getactivewindow", "getwindowclassname", "getwindowname"],
+        )?
+    };
+    let (app, title) = output.trim_end().split_once('\n')?;
+
+    Some(Window {
+        app: app.trim().to_string(),
+        title: title.trim().to_string(),
+    })
+}
+
+/// Gives an entry that stopped without a description the one from the
+/// windows used while it ran.
+pub fn describe_entry(config: &Config, client: &Client, entry: &TimeEntry, description: &str) {
+    let update = TimeEntryUpdate::new().description(description);
+    match client.update_time_entry(entry, &update) {
+        Ok(_) => {
+            let start = entry.start.map(|start| {
+                DateTime::<Local>::from(start)
+                    .format(config.time_format.pattern())
+                    .to_string()
+            });
+            println!(
+                "{}",
+                decorate(
+                    "📝",
+                    &format!(
+                        "Described the entry from {} as \"{description}\"",
+                        start.unwrap_or_default()
+                    )
+                )
+            );
+        }
+        Err(err) => eprintln!("{} Failed to describe time entry: {err}", icon("⚠️")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describes_stopped_entry_after_most_used_window() {
+        let entry = |id: i64, is_running: bool, description: Option<&str>| -> TimeEntry {
+            serde_json::from_value(serde_json::json!({
+                "billable": false,
+                "client_id": null,
+                "description": description,
+                "duration": 600,
+                "id": id,
+                "is_running": is_running,
+                "project_id": null,
+                "start": "2024-06-03T09:00:00Z",
+                "stop": null,
+                "tags": [],
+                "task_id": null,
+                "workspace_id": 1,
+            }))
+            .unwrap()
+        };
+        let window = |app: &str, title: &str| {
+            Some(Window {
+                app: app.to_string(),
+                title: title.to_string(),
+            })
+        };
+        let mut tracker = Tracker::default();
+        let running = [entry(1, true, None)];
+        assert!(tracker
+            .sample(&running, window("Code", "main.rs"))
+            .is_none());
+        assert!(tracker
+            .sample(&running, window("Firefox", "Docs"))
+            .is_none());
+        assert!(tracker.sample(&running, window("Code", "lib.rs")).is_none());
+        tracker.sample(&running, window("Code", "lib.rs"));
+
+        let stopped = [entry(1, false, None), entry(2, true, Some("Standup"))];
+        let (described, description) = tracker.sample(&stopped, window("Zoom", "")).unwrap();
+        assert_eq!(1, described.id);
+        assert_eq!("Code: lib.rs", description);
+        // Entries with descriptions aren't tracked.
+        assert!(tracker.sample(&[entry(2, false, None)], None).is_none());
+    }
+}
@@ -0,0 +1,66 @@
+//! Drives the commands that `--demo` supports through the built binary, to
+//! keep them on the same code paths as a real account.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+/// A home of its own for the test named `name`, so that the user's config
+/// and cache stay out of it.
+fn home(name: &str) -> PathBuf {
+    let home = env::temp_dir().join(format!("tgl-demo-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&home);
+    fs::create_dir_all(&home).unwrap();
+    fs::write(
+        home.join("config.toml"),
+        "[templates.research]\nproject = \"Research\"\ndescription = \"Reading\"\n",
+    )
+    .unwrap();
+
+    home
+}
+
+fn tgl(home: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_tgl"))
+        .arg("--demo")
+        .args(args)
+        .env("HOME", home)
+        .env("XDG_CONFIG_HOME", home)
+        .env("XDG_CACHE_HOME", home)
+        .env("TGL_CONFIG", home.join("config.toml"))
+        .env("LC_ALL", "en_US.UTF-8")
+        .env("NO_COLOR", "1")
+        .env_remove("TGL_PROFILE")
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    assert!(output.status.success(), "{output:?}");
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn demo_runs_shared_commands() {
+    let home = home("shared");
+
+    let status = stdout(&tgl(&home, &["status"]));
+    assert!(status.contains("[Research] Competitor review"), "{status}");
+    assert!(status.contains("logged today"), "{status}");
+    assert!(stdout(&tgl(&home, &["current"])).contains("Competitor review"));
+    assert!(stdout(&tgl(&home, &["prompt"])).contains("Research"));
+
+    let stopped = stdout(&tgl(&home, &["--quiet", "stop"]));
+    assert!(stopped.contains("Competitor review"), "{stopped}");
+    assert!(!stopped.contains("--:--"), "{stopped}");
+    let started = stdout(&tgl(&home, &["--quiet", "start", "research"]));
+    assert!(started.contains("[Research] Reading"), "{started}");
+
+    let report = tgl(&home, &["report"]);
+    assert!(!report.status.success());
+    assert!(String::from_utf8_lossy(&report.stderr).contains("needs Toggl"));
+
+    let _ = fs::remove_dir_all(home);
+}